@@ -0,0 +1,12 @@
+# Suggest packages for unknown commands. Add to ~/.bashrc:
+#     source /path/to/command-not-found.bash
+# Requires the index built by `pd index update`.
+
+command_not_found_handle() {
+    if command -v pd >/dev/null; then
+        pd --cnf "$1"
+    else
+        printf 'bash: %s: command not found\n' "$1" >&2
+        return 127
+    fi
+}
@@ -0,0 +1,11 @@
+# Suggest packages for unknown commands. Copy to
+# ~/.config/fish/conf.d/command-not-found.fish
+# Requires the index built by `pd index update`.
+
+function fish_command_not_found
+    if command -q pd
+        pd --cnf $argv[1]
+    else
+        __fish_default_command_not_found_handler $argv
+    end
+end
@@ -0,0 +1,327 @@
+//! Reader for the appstream metadata Flatpak caches for every remote.
+//!
+//! Flatpak keeps one `appstream.xml.gz` per remote and architecture under
+//! `<installation>/appstream/<remote>/<arch>/active/`, for the system-wide
+//! installation and for the per-user one. Next to it, `icons/<size>/`
+//! holds the cached icons the components refer to.
+//!
+//! Reading it directly answers Flatpak searches without running `flatpak
+//! search`, and knows far more than its columns: the full description,
+//! developer, categories, releases, content rating and icons.
+
+use std::collections::HashSet;
+use std::fs;
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use roxmltree::{Document, Node};
+
+use crate::date::parse_date;
+use crate::package::{FlatpakKind, PackageInfo, Release, Source};
+use crate::syncdb::open_decompressed;
+
+/// The system-wide appstream cache.
+pub const SYSTEM_APPSTREAM_DIR: &str = "/var/lib/flatpak/appstream";
+
+const XML_NAMESPACE: &str = "http://www.w3.org/XML/1998/namespace";
+
+/// The system-wide and per-user appstream caches.
+pub fn default_appstream_dirs() -> Vec<PathBuf> {
+    let mut dirs = vec![PathBuf::from(SYSTEM_APPSTREAM_DIR)];
+
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")));
+    if let Some(data_home) = data_home {
+        dirs.push(data_home.join("flatpak").join("appstream"));
+    }
+
+    dirs
+}
+
+/// Every remote's appstream file for this machine's architecture, as
+/// `(remote, path)` pairs. Missing directories are skipped.
+pub fn appstream_files(dirs: &[PathBuf]) -> Vec<(String, PathBuf)> {
+    let mut files = Vec::new();
+
+    for dir in dirs {
+        let Ok(remotes) = fs::read_dir(dir) else {
+            continue;
+        };
+        let mut remotes: Vec<_> = remotes.filter_map(Result::ok).collect();
+        remotes.sort_by_key(|entry| entry.file_name());
+
+        for remote in remotes {
+            let active = remote.path().join(std::env::consts::ARCH).join("active");
+            let file = ["appstream.xml.gz", "appstream.xml"]
+                .iter()
+                .map(|name| active.join(name))
+                .find(|path| path.is_file());
+            if let (Some(file), Some(name)) = (file, remote.file_name().to_str()) {
+                files.push((name.to_string(), file));
+            }
+        }
+    }
+
+    files
+}
+
+/// Read every application from the appstream files in `dirs`.
+///
+/// An application available from the same remote in both the system-wide
+/// and the per-user installation is only listed once.
+pub fn read_appstream_dirs(dirs: &[PathBuf]) -> io::Result<Vec<PackageInfo>> {
+    let mut packages = Vec::new();
+    let mut seen = HashSet::new();
+
+    for (remote, path) in appstream_files(dirs) {
+        for package in read_appstream(&path, &remote)? {
+            if seen.insert((remote.clone(), package.app_id.clone())) {
+                packages.push(package);
+            }
+        }
+    }
+
+    Ok(packages)
+}
+
+/// Read one remote's appstream file, compressed or not, with icon paths
+/// made absolute.
+pub fn read_appstream(path: &Path, remote: &str) -> io::Result<Vec<PackageInfo>> {
+    let mut xml = String::new();
+    open_decompressed(path)?.read_to_string(&mut xml)?;
+    let mut packages = parse_appstream(&xml, remote).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {}", path.display(), e),
+        )
+    })?;
+
+    let icons = path.with_file_name("icons");
+    for package in &mut packages {
+        for icon in &mut package.icons {
+            *icon = icons.join(&*icon).to_string_lossy().into_owned();
+        }
+    }
+    Ok(packages)
+}
+
+/// Parse appstream XML into one result per Flatpak component.
+///
+/// Results are named like the `flatpak search` ones, `Name (app.id)`,
+/// `repo` holds the remote and `provides` the binaries the application
+/// declares. Icon paths are relative to the `icons` directory next to the
+/// appstream file.
+pub fn parse_appstream(xml: &str, remote: &str) -> Result<Vec<PackageInfo>, roxmltree::Error> {
+    let document = Document::parse(xml)?;
+
+    let packages = document
+        .root_element()
+        .children()
+        .filter(|node| node.has_tag_name("component"))
+        .filter_map(|component| package_from_component(component, remote))
+        .collect();
+
+    Ok(packages)
+}
+
+fn package_from_component(component: Node, remote: &str) -> Option<PackageInfo> {
+    // Only components with a Flatpak bundle can be installed from the remote
+    let bundle = component
+        .children()
+        .find(|node| node.has_tag_name("bundle") && node.attribute("type") == Some("flatpak"))?;
+    // The bundle is a ref: `app/org.mozilla.firefox/x86_64/stable`
+    let mut bundle_ref = bundle.text()?.trim().split('/');
+    let ref_kind = bundle_ref.next()?;
+    let app_id = bundle_ref.next()?.to_string();
+    let arch = bundle_ref.next().map(str::to_string);
+    let branch = bundle_ref.next().map(str::to_string);
+    let kind = if ref_kind == "app" {
+        FlatpakKind::App
+    } else if component.attribute("type") == Some("addon") {
+        FlatpakKind::Extension
+    } else {
+        FlatpakKind::of_runtime(&app_id)
+    };
+
+    let name = untranslated_text(component, "name").unwrap_or_else(|| app_id.clone());
+    let description =
+        untranslated_text(component, "summary").unwrap_or_else(|| "No description.".to_string());
+    let releases: Vec<Release> = child(component, "releases")
+        .map(|releases| {
+            releases
+                .children()
+                .filter(|node| node.has_tag_name("release"))
+                .filter_map(|release| {
+                    let date = release
+                        .attribute("timestamp")
+                        .and_then(|timestamp| timestamp.parse().ok())
+                        .or_else(|| release.attribute("date").and_then(parse_date));
+                    Some(Release {
+                        version: release.attribute("version")?.to_string(),
+                        date,
+                    })
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let version = releases
+        .first()
+        .map_or_else(|| "Unknown".to_string(), |release| release.version.clone());
+
+    let keywords = untranslated_list(component, "keywords", "keyword");
+    let categories = untranslated_list(component, "categories", "category");
+    let developer = child(component, "developer")
+        .and_then(|developer| untranslated_text(developer, "name"))
+        .or_else(|| untranslated_text(component, "developer_name"));
+    let long_description = component
+        .children()
+        .filter(|node| node.has_tag_name("description"))
+        .find(|node| node.attribute((XML_NAMESPACE, "lang")).is_none())
+        .map(description_text)
+        .filter(|text| !text.is_empty());
+    let content_rating = child(component, "content_rating")
+        .map(|rating| {
+            rating
+                .children()
+                .filter(|node| node.has_tag_name("content_attribute"))
+                .filter_map(|node| {
+                    let id = node.attribute("id")?;
+                    let intensity = node.text()?.trim();
+                    (intensity != "none").then(|| format!("{}: {}", id, intensity))
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    let url = component
+        .children()
+        .find(|node| node.has_tag_name("url") && node.attribute("type") == Some("homepage"))
+        .and_then(|node| node.text())
+        .map(|url| url.trim().to_string());
+    let licenses = untranslated_text(component, "project_license")
+        .into_iter()
+        .collect();
+    // The commands the application installs
+    let binaries = child(component, "provides")
+        .map(|provides| {
+            provides
+                .children()
+                .filter(|node| node.has_tag_name("binary"))
+                .filter_map(|node| node.text())
+                .map(|binary| binary.trim().to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+
+    Some(PackageInfo {
+        app_id: Some(app_id.clone()),
+        repo: Some(remote.to_string()),
+        branch,
+        arch,
+        kind: Some(kind),
+        appstream: true,
+        long_description,
+        developer,
+        categories,
+        provides: binaries,
+        keywords,
+        releases,
+        content_rating,
+        icons: cached_icons(component),
+        licenses,
+        url,
+        ..PackageInfo::new(
+            Source::Flatpak,
+            format!("{} ({})", name, app_id),
+            version,
+            description,
+        )
+    })
+}
+
+/// The cached icons of `component`, largest first, as paths such as
+/// `128x128/org.mozilla.firefox.png` or `64x64@2/…` for scaled ones.
+fn cached_icons(component: Node) -> Vec<String> {
+    let mut icons: Vec<(u32, String)> = component
+        .children()
+        .filter(|node| node.has_tag_name("icon") && node.attribute("type") == Some("cached"))
+        .filter_map(|icon| {
+            let file = icon.text()?.trim();
+            let size = |attribute| icon.attribute(attribute)?.parse::<u32>().ok();
+            let (width, height) = (size("width")?, size("height")?);
+            let scale = size("scale").unwrap_or(1);
+            let dir = if scale > 1 {
+                format!("{}x{}@{}", width, height, scale)
+            } else {
+                format!("{}x{}", width, height)
+            };
+            Some((width * scale, format!("{}/{}", dir, file)))
+        })
+        .collect();
+    icons.sort_by_key(|(size, _)| std::cmp::Reverse(*size));
+    icons.into_iter().map(|(_, path)| path).collect()
+}
+
+/// A `<description>` as plain text: paragraphs separated by blank lines,
+/// list items as `- item` lines.
+fn description_text(description: Node) -> String {
+    let untranslated = |node: &Node| node.attribute((XML_NAMESPACE, "lang")).is_none();
+    let mut blocks = Vec::new();
+    for block in description.children().filter(untranslated) {
+        if block.has_tag_name("p") {
+            blocks.push(inline_text(block));
+        } else if block.has_tag_name("ul") || block.has_tag_name("ol") {
+            let items: Vec<String> = block
+                .children()
+                .filter(|node| node.has_tag_name("li") && untranslated(node))
+                .map(|item| format!("- {}", inline_text(item)))
+                .collect();
+            blocks.push(items.join("\n"));
+        }
+    }
+    blocks.retain(|block| !block.is_empty());
+    blocks.join("\n\n")
+}
+
+/// The text of `node` and its inline children such as `<em>`, with runs of
+/// whitespace collapsed.
+fn inline_text(node: Node) -> String {
+    let text: String = node
+        .descendants()
+        .filter(Node::is_text)
+        .filter_map(|text| text.text())
+        .collect();
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// The untranslated `item` children of the first `list` child, e.g. the
+/// `keyword`s of `keywords`.
+fn untranslated_list(node: Node, list: &str, item: &str) -> Vec<String> {
+    child(node, list)
+        .map(|list| {
+            list.children()
+                .filter(|node| {
+                    node.has_tag_name(item) && node.attribute((XML_NAMESPACE, "lang")).is_none()
+                })
+                .filter_map(|node| node.text())
+                .map(|text| text.trim().to_string())
+                .filter(|text| !text.is_empty())
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn child<'a, 'input>(node: Node<'a, 'input>, tag: &str) -> Option<Node<'a, 'input>> {
+    node.children().find(|child| child.has_tag_name(tag))
+}
+
+/// The text of the first `tag` child without an `xml:lang` attribute.
+fn untranslated_text(node: Node, tag: &str) -> Option<String> {
+    node.children()
+        .filter(|child| child.has_tag_name(tag))
+        .find(|child| child.attribute((XML_NAMESPACE, "lang")).is_none())
+        .and_then(|child| child.text())
+        .map(|text| text.trim().to_string())
+        .filter(|text| !text.is_empty())
+}
@@ -0,0 +1,254 @@
+//! Client for the AUR RPC v5 interface.
+//!
+//! See <https://aur.archlinux.org/rpc> for the endpoints this wraps.
+
+use std::fmt;
+use std::io::{self, BufReader};
+use std::path::Path;
+
+use reqwest::Url;
+use serde::Deserialize;
+
+use crate::package::{PackageInfo, Source};
+use crate::syncdb::open_decompressed;
+
+/// The public AUR RPC endpoint.
+pub const DEFAULT_BASE_URL: &str = "https://aur.archlinux.org/rpc/v5";
+
+/// The daily dump of every AUR package's metadata, including dependencies and
+/// keywords.
+pub const METADATA_DUMP_URL: &str = "https://aur.archlinux.org/packages-meta-ext-v1.json.gz";
+
+/// Which package field a search matches against.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SearchBy {
+    Name,
+    #[default]
+    NameDesc,
+    Maintainer,
+    Depends,
+    Provides,
+}
+
+impl SearchBy {
+    fn as_str(self) -> &'static str {
+        match self {
+            SearchBy::Name => "name",
+            SearchBy::NameDesc => "name-desc",
+            SearchBy::Maintainer => "maintainer",
+            SearchBy::Depends => "depends",
+            SearchBy::Provides => "provides",
+        }
+    }
+}
+
+/// Errors returned by [`RpcClient`].
+#[derive(Debug)]
+pub enum RpcError {
+    /// The request could not be sent or its body could not be read.
+    Http(reqwest::Error),
+    /// The server answered with a non-success status and no RPC error message.
+    Status(reqwest::StatusCode),
+    /// The response body is not a valid RPC response.
+    Decode(serde_json::Error),
+    /// The RPC answered with `"type": "error"`.
+    Api(String),
+    /// The configured base URL is not a valid URL.
+    InvalidUrl(String),
+}
+
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RpcError::Http(e) => write!(f, "AUR request failed: {}", e),
+            RpcError::Status(status) => write!(f, "AUR request failed: HTTP {}", status),
+            RpcError::Decode(e) => write!(f, "invalid AUR RPC response: {}", e),
+            RpcError::Api(message) => write!(f, "AUR RPC error: {}", message),
+            RpcError::InvalidUrl(url) => write!(f, "invalid AUR RPC URL '{}'", url),
+        }
+    }
+}
+
+impl std::error::Error for RpcError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            RpcError::Http(e) => Some(e),
+            RpcError::Decode(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<reqwest::Error> for RpcError {
+    fn from(e: reqwest::Error) -> Self {
+        RpcError::Http(e)
+    }
+}
+
+/// A package record as returned by the `search` and `info` endpoints.
+///
+/// `search` only fills the fields up to `url_path`; the dependency lists are
+/// only present in `info` responses and are empty otherwise.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(rename_all = "PascalCase", default)]
+pub struct RpcPackage {
+    #[serde(rename = "ID")]
+    pub id: u64,
+    pub name: String,
+    pub package_base: String,
+    pub version: String,
+    pub description: Option<String>,
+    #[serde(rename = "URL")]
+    pub url: Option<String>,
+    pub num_votes: u32,
+    pub popularity: f64,
+    pub out_of_date: Option<i64>,
+    pub maintainer: Option<String>,
+    pub first_submitted: i64,
+    pub last_modified: i64,
+    #[serde(rename = "URLPath")]
+    pub url_path: Option<String>,
+    pub depends: Vec<String>,
+    pub make_depends: Vec<String>,
+    pub opt_depends: Vec<String>,
+    pub check_depends: Vec<String>,
+    pub conflicts: Vec<String>,
+    pub provides: Vec<String>,
+    pub replaces: Vec<String>,
+    pub groups: Vec<String>,
+    pub license: Vec<String>,
+    pub keywords: Vec<String>,
+}
+
+impl From<RpcPackage> for PackageInfo {
+    fn from(package: RpcPackage) -> Self {
+        let description = match package.description {
+            Some(d) if !d.trim().is_empty() => d.trim().to_string(),
+            _ => "No description.".to_string(),
+        };
+
+        PackageInfo {
+            repo: Some("aur".to_string()),
+            groups: package.groups,
+            provides: package.provides,
+            depends: package.depends,
+            opt_depends: package.opt_depends,
+            make_depends: package.make_depends,
+            conflicts: package.conflicts,
+            replaces: package.replaces,
+            licenses: package.license,
+            keywords: package.keywords,
+            url: package.url,
+            votes: Some(package.num_votes),
+            popularity: Some(package.popularity),
+            orphaned: package.maintainer.is_none(),
+            maintainer: package.maintainer,
+            out_of_date: package.out_of_date,
+            last_modified: Some(package.last_modified),
+            ..PackageInfo::new(Source::Aur, package.name, package.version, description)
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct RpcResponse {
+    #[serde(rename = "type")]
+    kind: String,
+    #[serde(default)]
+    error: Option<String>,
+    #[serde(default)]
+    results: Vec<RpcPackage>,
+}
+
+/// An HTTP client for the AUR RPC.
+#[derive(Clone, Debug)]
+pub struct RpcClient {
+    base_url: String,
+    http: reqwest::Client,
+}
+
+impl Default for RpcClient {
+    fn default() -> Self {
+        RpcClient::new()
+    }
+}
+
+impl RpcClient {
+    /// A client for the public AUR.
+    pub fn new() -> Self {
+        RpcClient::with_base_url(DEFAULT_BASE_URL)
+    }
+
+    /// A client for an RPC endpoint other than the public AUR, such as a
+    /// mirror or a local mock server. `base_url` should end in `/rpc/v5`.
+    pub fn with_base_url(base_url: impl Into<String>) -> Self {
+        let http = reqwest::Client::builder()
+            .user_agent(concat!("package_dictionary/", env!("CARGO_PKG_VERSION")))
+            .build()
+            .expect("Failed to build HTTP client");
+
+        RpcClient {
+            base_url: base_url.into(),
+            http,
+        }
+    }
+
+    pub fn base_url(&self) -> &str {
+        &self.base_url
+    }
+
+    /// Search packages whose `by` field matches `term`.
+    pub async fn search(&self, term: &str, by: SearchBy) -> Result<Vec<RpcPackage>, RpcError> {
+        let mut url = self.endpoint(&["search", term])?;
+        url.query_pairs_mut().append_pair("by", by.as_str());
+        self.get(url).await
+    }
+
+    /// Fetch full records, including dependencies, for the given package names.
+    pub async fn info<S: AsRef<str>>(&self, names: &[S]) -> Result<Vec<RpcPackage>, RpcError> {
+        let mut url = self.endpoint(&["info"])?;
+        {
+            let mut query = url.query_pairs_mut();
+            for name in names {
+                query.append_pair("arg[]", name.as_ref());
+            }
+        }
+        self.get(url).await
+    }
+
+    fn endpoint(&self, segments: &[&str]) -> Result<Url, RpcError> {
+        let mut url =
+            Url::parse(&self.base_url).map_err(|_| RpcError::InvalidUrl(self.base_url.clone()))?;
+        url.path_segments_mut()
+            .map_err(|_| RpcError::InvalidUrl(self.base_url.clone()))?
+            .pop_if_empty()
+            .extend(segments);
+        Ok(url)
+    }
+
+    async fn get(&self, url: Url) -> Result<Vec<RpcPackage>, RpcError> {
+        let response = self.http.get(url).send().await?;
+        let status = response.status();
+        let body = response.bytes().await?;
+
+        // Error responses carry a JSON message even when the status is not 200
+        match serde_json::from_slice::<RpcResponse>(&body) {
+            Ok(response) if response.kind == "error" => Err(RpcError::Api(
+                response
+                    .error
+                    .unwrap_or_else(|| "unknown error".to_string()),
+            )),
+            Ok(response) if status.is_success() => Ok(response.results),
+            Ok(_) => Err(RpcError::Status(status)),
+            Err(_) if !status.is_success() => Err(RpcError::Status(status)),
+            Err(e) => Err(RpcError::Decode(e)),
+        }
+    }
+}
+
+/// Read a downloaded copy of [`METADATA_DUMP_URL`], compressed or not.
+pub fn read_metadata_dump(path: &Path) -> io::Result<Vec<PackageInfo>> {
+    let reader = BufReader::new(open_decompressed(path)?);
+    let packages: Vec<RpcPackage> = serde_json::from_reader(reader)?;
+    Ok(packages.into_iter().map(PackageInfo::from).collect())
+}
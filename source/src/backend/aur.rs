@@ -0,0 +1,198 @@
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use serde::{Deserialize, Serialize};
+
+use super::{check_parsed, command, command_exists, command_output, Backend, BackendError};
+use crate::aur_rpc::{RpcClient, SearchBy};
+use crate::localdb;
+use crate::package::{PackageInfo, Source};
+use crate::parse::{parse_search_output, parse_single_line_output};
+
+/// The AUR helper used to query the AUR.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AurHelper {
+    Paru,
+    Yay,
+}
+
+impl AurHelper {
+    /// Every supported helper, in order of preference.
+    pub const ALL: [AurHelper; 2] = [AurHelper::Paru, AurHelper::Yay];
+
+    /// The helper's executable name.
+    pub fn command(self) -> &'static str {
+        match self {
+            AurHelper::Paru => "paru",
+            AurHelper::Yay => "yay",
+        }
+    }
+
+    /// Find the first installed helper, preferring paru over yay.
+    pub async fn detect() -> Option<AurHelper> {
+        for helper in AurHelper::ALL {
+            if command_exists(helper.command()).await {
+                return Some(helper);
+            }
+        }
+        None
+    }
+}
+
+impl fmt::Display for AurHelper {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.command())
+    }
+}
+
+impl FromStr for AurHelper {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "paru" => Ok(AurHelper::Paru),
+            "yay" => Ok(AurHelper::Yay),
+            other => Err(format!(
+                "unknown AUR helper '{}' (expected paru or yay)",
+                other
+            )),
+        }
+    }
+}
+
+/// AUR packages, searched through the AUR RPC.
+///
+/// Unlike [`AurBackend`] this needs no AUR helper and fills in votes,
+/// popularity, maintainer and out-of-date information.
+pub struct AurRpcBackend {
+    client: RpcClient,
+}
+
+impl AurRpcBackend {
+    pub fn new(client: RpcClient) -> Self {
+        AurRpcBackend { client }
+    }
+
+    pub fn client(&self) -> &RpcClient {
+        &self.client
+    }
+}
+
+impl Backend for AurRpcBackend {
+    fn name(&self) -> &str {
+        "AUR"
+    }
+
+    fn source(&self) -> Source {
+        Source::Aur
+    }
+
+    fn is_available(&self) -> BoxFuture<'_, bool> {
+        // Reachability is only known once the request is made
+        futures::future::ready(true).boxed()
+    }
+
+    fn search<'a>(
+        &'a self,
+        term: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<PackageInfo>, BackendError>> {
+        async move {
+            let results = self
+                .client
+                .search(term, SearchBy::NameDesc)
+                .await
+                .map_err(|e| BackendError::Request(e.to_string()))?;
+
+            let mut packages: Vec<PackageInfo> =
+                results.into_iter().map(PackageInfo::from).collect();
+            localdb::mark_installed(&mut packages, Path::new(localdb::DEFAULT_LOCAL_DIR));
+            Ok(packages)
+        }
+        .boxed()
+    }
+
+    fn info<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<Vec<PackageInfo>, BackendError>> {
+        async move {
+            // Unlike search results, `info` records carry the dependencies
+            let results = self
+                .client
+                .info(&[name])
+                .await
+                .map_err(|e| BackendError::Request(e.to_string()))?;
+
+            let mut packages: Vec<PackageInfo> =
+                results.into_iter().map(PackageInfo::from).collect();
+            localdb::mark_installed(&mut packages, Path::new(localdb::DEFAULT_LOCAL_DIR));
+            Ok(packages)
+        }
+        .boxed()
+    }
+}
+
+/// AUR packages, searched through an AUR helper's `-Ss --aur`.
+///
+/// yay prints one result per line with `--singlelineresults`; paru has no
+/// such mode, so its two-line output is parsed instead.
+pub struct AurBackend {
+    helper: AurHelper,
+}
+
+impl AurBackend {
+    pub fn new(helper: AurHelper) -> Self {
+        AurBackend { helper }
+    }
+
+    pub fn helper(&self) -> AurHelper {
+        self.helper
+    }
+}
+
+impl Backend for AurBackend {
+    fn name(&self) -> &str {
+        "AUR"
+    }
+
+    fn source(&self) -> Source {
+        Source::Aur
+    }
+
+    fn is_available(&self) -> BoxFuture<'_, bool> {
+        command_exists(self.helper.command()).boxed()
+    }
+
+    fn search<'a>(
+        &'a self,
+        term: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<PackageInfo>, BackendError>> {
+        async move {
+            let program = self.helper.command();
+            let mut search = command(program);
+            search.args(["-Ss", "--aur"]);
+            match self.helper {
+                AurHelper::Paru => search.args(["--color", "never"]),
+                // yay has no color option and relies on `NO_COLOR`
+                AurHelper::Yay => search.arg("--singlelineresults"),
+            };
+            search.arg(term);
+
+            let stdout = command_output(&mut search, program).await?;
+            let mut packages = match self.helper {
+                AurHelper::Yay => parse_single_line_output(&stdout, Source::Aur),
+                AurHelper::Paru => Vec::new(),
+            };
+            if packages.is_empty() {
+                packages = parse_search_output(&stdout, Source::Aur);
+            }
+            check_parsed(&stdout, &packages)?;
+            // The helper's install markers may be translated, the local
+            // database is not
+            localdb::mark_installed(&mut packages, Path::new(localdb::DEFAULT_LOCAL_DIR));
+            Ok(packages)
+        }
+        .boxed()
+    }
+}
@@ -0,0 +1,60 @@
+use std::fmt;
+use std::io;
+use std::time::Duration;
+
+/// Why a backend produced no results.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum BackendError {
+    /// The tool the backend relies on is not installed.
+    NotInstalled,
+    /// The tool exited unsuccessfully.
+    Exit {
+        program: String,
+        /// The exit code, or `None` if it was killed by a signal.
+        code: Option<i32>,
+        stderr: String,
+    },
+    /// The search did not finish before its deadline.
+    Timeout(Duration),
+    /// The tool's output could not be understood.
+    Parse(String),
+    /// A request to a remote service failed.
+    Request(String),
+    /// Reading local data or running the tool failed.
+    Io(String),
+}
+
+impl fmt::Display for BackendError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BackendError::NotInstalled => write!(f, "not installed"),
+            BackendError::Exit {
+                program,
+                code,
+                stderr,
+            } => {
+                match code {
+                    Some(code) => write!(f, "{} exited {}", program, code)?,
+                    None => write!(f, "{} was killed", program)?,
+                }
+                // The first line is usually the actual error
+                match stderr.lines().map(str::trim).find(|line| !line.is_empty()) {
+                    Some(line) => write!(f, ": {}", line),
+                    None => Ok(()),
+                }
+            }
+            BackendError::Timeout(timeout) => write!(f, "timed out after {:?}", timeout),
+            BackendError::Parse(message) => write!(f, "unexpected output: {}", message),
+            BackendError::Request(message) => write!(f, "request failed: {}", message),
+            BackendError::Io(message) => f.write_str(message),
+        }
+    }
+}
+
+impl std::error::Error for BackendError {}
+
+impl From<io::Error> for BackendError {
+    fn from(e: io::Error) -> Self {
+        BackendError::Io(e.to_string())
+    }
+}
@@ -0,0 +1,109 @@
+use std::path::PathBuf;
+
+use futures::future::BoxFuture;
+use futures::FutureExt;
+
+use super::{Backend, BackendError};
+use crate::files::{search_files_dir, FileQuery};
+use crate::package::{PackageInfo, Source};
+use crate::{localdb, syncdb};
+
+/// Packages owning a file, for `pd provides`. The search term is a path or a
+/// file name, see [`FileQuery::parse`].
+///
+/// For [`Source::Pacman`] the owners come from the files databases
+/// downloaded by `pacman -Fy`. For [`Source::Aur`] they are the installed
+/// foreign packages, which only the local database knows the files of.
+pub struct FilesBackend {
+    source: Source,
+    sync_dir: PathBuf,
+    local_dir: PathBuf,
+}
+
+impl FilesBackend {
+    /// File owners from `source`, which is either [`Source::Pacman`] or
+    /// [`Source::Aur`].
+    pub fn new(source: Source) -> Self {
+        FilesBackend {
+            source,
+            sync_dir: PathBuf::from(syncdb::DEFAULT_SYNC_DIR),
+            local_dir: PathBuf::from(localdb::DEFAULT_LOCAL_DIR),
+        }
+    }
+
+    pub fn with_sync_dir(mut self, sync_dir: impl Into<PathBuf>) -> Self {
+        self.sync_dir = sync_dir.into();
+        self
+    }
+
+    pub fn with_local_dir(mut self, local_dir: impl Into<PathBuf>) -> Self {
+        self.local_dir = local_dir.into();
+        self
+    }
+}
+
+impl Backend for FilesBackend {
+    fn name(&self) -> &str {
+        self.source.label()
+    }
+
+    fn source(&self) -> Source {
+        self.source
+    }
+
+    fn is_available(&self) -> BoxFuture<'_, bool> {
+        let available = match self.source {
+            Source::Pacman => self.sync_dir.is_dir(),
+            _ => self.local_dir.is_dir(),
+        };
+        async move { available }.boxed()
+    }
+
+    fn search<'a>(
+        &'a self,
+        term: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<PackageInfo>, BackendError>> {
+        let source = self.source;
+        let sync_dir = self.sync_dir.clone();
+        let local_dir = self.local_dir.clone();
+        let query = FileQuery::parse(term);
+
+        async move {
+            // Scanning every file list is CPU-bound; keep it off the runtime
+            tokio::task::spawn_blocking(move || {
+                let mut packages = match source {
+                    Source::Pacman => {
+                        let has_files = syncdb::files_databases(&sync_dir)
+                            .is_ok_and(|databases| !databases.is_empty());
+                        if !has_files {
+                            return Err(BackendError::Io(format!(
+                                "no files databases in {}, run `pacman -Fy` first",
+                                sync_dir.display()
+                            )));
+                        }
+                        let mut packages = search_files_dir(&sync_dir, &query)?;
+                        localdb::mark_installed(&mut packages, &local_dir);
+                        packages
+                    }
+                    _ => {
+                        let mut packages = localdb::search_local_files(&local_dir, &query)?;
+                        // Without sync databases nothing counts as foreign
+                        let available = syncdb::sync_versions(&sync_dir).unwrap_or_default();
+                        packages.retain(|package| {
+                            !available.is_empty() && !available.contains_key(&package.name)
+                        });
+                        for package in &mut packages {
+                            package.source = source;
+                        }
+                        packages
+                    }
+                };
+                query.prefer_commands(&mut packages);
+                Ok(packages)
+            })
+            .await
+            .map_err(|e| BackendError::Io(e.to_string()))?
+        }
+        .boxed()
+    }
+}
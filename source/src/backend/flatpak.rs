@@ -0,0 +1,270 @@
+use std::collections::HashMap;
+use std::fs;
+
+use futures::future::BoxFuture;
+use futures::FutureExt;
+
+use super::installed::{installation_dirs, installed_flatpaks, mark_flatpak_installed};
+use super::{command, command_exists, command_output, Backend, BackendError};
+use crate::appstream::{appstream_files, default_appstream_dirs, read_appstream_dirs};
+use crate::package::{FlatpakKind, PackageInfo, Source};
+use crate::sandbox::{parse_metadata, Permissions};
+
+/// Flatpak applications from every configured remote.
+pub struct FlatpakBackend;
+
+impl Backend for FlatpakBackend {
+    fn name(&self) -> &str {
+        "Flatpak"
+    }
+
+    fn source(&self) -> Source {
+        Source::Flatpak
+    }
+
+    fn is_available(&self) -> BoxFuture<'_, bool> {
+        command_exists("flatpak").boxed()
+    }
+
+    fn search<'a>(
+        &'a self,
+        term: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<PackageInfo>, BackendError>> {
+        async move {
+            let dirs = default_appstream_dirs();
+            let cached = tokio::task::spawn_blocking(move || {
+                if appstream_files(&dirs).is_empty() {
+                    return Ok(None);
+                }
+                read_appstream_dirs(&dirs).map(Some)
+            });
+            let (cached, installed) = futures::join!(cached, installed_flatpaks());
+
+            // Like `flatpak search`, match names, application IDs,
+            // descriptions and keywords; `--match` narrows that down like
+            // for other sources
+            let mut packages = match cached.map_err(|e| BackendError::Io(e.to_string()))?? {
+                Some(mut packages) => {
+                    packages.retain(|package| matches_term(package, term));
+                    packages
+                }
+                None => {
+                    // Run flatpak search with --columns to improve parsing efficiency
+                    let mut search = command("flatpak");
+                    search.args([
+                        "search",
+                        "--columns=name,application,version,branch,remotes,description",
+                        term,
+                    ]);
+                    // `flatpak search` has no column for the kind of ref
+                    let mut refs = command("flatpak");
+                    refs.args(["remote-ls", "--columns=ref"]);
+                    let (found, refs) = futures::join!(
+                        command_output(&mut search, "flatpak"),
+                        command_output(&mut refs, "flatpak")
+                    );
+
+                    let mut packages = parse_flatpak_output(&found?);
+                    if let Ok(refs) = refs {
+                        let kinds = parse_flatpak_refs(&refs);
+                        for package in &mut packages {
+                            let app_id = package.app_id.as_deref().unwrap_or_default();
+                            if let Some(&kind) = kinds.get(app_id) {
+                                package.kind = Some(kind);
+                            }
+                        }
+                    }
+                    packages
+                }
+            };
+            if let Ok(installed) = installed {
+                mark_flatpak_installed(&mut packages, &installed);
+            }
+            Ok(packages)
+        }
+        .boxed()
+    }
+
+    fn info<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<Vec<PackageInfo>, BackendError>> {
+        async move {
+            // The cached appstream data knows licenses and URLs, which
+            // `flatpak search` does not print
+            let dirs = default_appstream_dirs();
+            let mut packages = tokio::task::spawn_blocking(move || read_appstream_dirs(&dirs))
+                .await
+                .map_err(|e| BackendError::Io(e.to_string()))??;
+            packages.retain(|package| package.is_named(name));
+            if packages.is_empty() {
+                packages = self.search(name).await?;
+                packages.retain(|package| package.is_named(name));
+            }
+
+            if let Ok(installed) = installed_flatpaks().await {
+                mark_flatpak_installed(&mut packages, &installed);
+            }
+            add_flatpak_permissions(&mut packages, false).await;
+            Ok(packages)
+        }
+        .boxed()
+    }
+}
+
+/// Fill in the sandbox permissions of every Flatpak in `packages`, see
+/// [`flatpak_permissions`].
+pub async fn add_flatpak_permissions(packages: &mut [PackageInfo], offline: bool) {
+    let permissions = futures::future::join_all(
+        packages
+            .iter()
+            .map(|package| flatpak_permissions(package, offline)),
+    )
+    .await;
+    for (package, permissions) in packages.iter_mut().zip(permissions) {
+        package.permissions = permissions;
+    }
+}
+
+/// The sandbox permissions of a Flatpak, from its metadata: the installed
+/// copy when there is one, else `flatpak remote-info --show-metadata`,
+/// which offline only consults the remote's cached data.
+pub async fn flatpak_permissions(package: &PackageInfo, offline: bool) -> Option<Permissions> {
+    if package.source != Source::Flatpak {
+        return None;
+    }
+    let app_id = package.app_id.as_deref()?;
+    let ref_kind = match package.kind {
+        Some(FlatpakKind::App) | None => "app",
+        Some(FlatpakKind::Runtime | FlatpakKind::Extension) => "runtime",
+    };
+    let deployed = package
+        .arch
+        .as_deref()
+        .zip(package.branch.as_deref())
+        .map(|(arch, branch)| format!("{}/{}", arch, branch));
+
+    let mut installations: Vec<_> = installation_dirs().into_iter().collect();
+    installations.sort();
+    let installed = installations
+        .iter()
+        .flat_map(|(_, dir)| {
+            let dir = dir.join(ref_kind).join(app_id);
+            deployed
+                .iter()
+                .map(|deployed| dir.join(deployed))
+                .chain([dir.join("current")])
+                .collect::<Vec<_>>()
+        })
+        .find_map(|dir| fs::read_to_string(dir.join("active/metadata")).ok());
+    if let Some(metadata) = installed {
+        return Some(parse_metadata(&metadata));
+    }
+
+    let remote = package.repo.as_deref()?;
+    let target = match &deployed {
+        Some(deployed) => format!("{}/{}/{}", ref_kind, app_id, deployed),
+        None => app_id.to_string(),
+    };
+    let mut info = command("flatpak");
+    info.args(["remote-info", "--show-metadata", remote, &target]);
+    if offline {
+        info.arg("--cached");
+    }
+    let stdout = command_output(&mut info, "flatpak").await.ok()?;
+    Some(parse_metadata(&String::from_utf8_lossy(&stdout)))
+}
+
+/// Whether every word of `term` appears in the name, application ID,
+/// description or keywords of `package`, ignoring case.
+fn matches_term(package: &PackageInfo, term: &str) -> bool {
+    let mut text = format!("{}\n{}", package.name, package.description);
+    for keyword in &package.keywords {
+        text.push('\n');
+        text.push_str(keyword);
+    }
+    let text = text.to_lowercase();
+    term.to_lowercase()
+        .split_whitespace()
+        .all(|word| text.contains(word))
+}
+
+/// The kind of every ref in the output of `flatpak remote-ls
+/// --columns=ref`, by application ID.
+pub fn parse_flatpak_refs(stdout: &[u8]) -> HashMap<String, FlatpakKind> {
+    String::from_utf8_lossy(stdout)
+        .lines()
+        .filter_map(|line| {
+            // `app/org.gimp.GIMP/x86_64/stable`
+            let mut parts = line.trim().split('/');
+            let kind = parts.next()?;
+            let app_id = parts.next()?;
+            let kind = match kind {
+                "app" => FlatpakKind::App,
+                "runtime" => FlatpakKind::of_runtime(app_id),
+                _ => return None,
+            };
+            Some((app_id.to_string(), kind))
+        })
+        .collect()
+}
+
+/// Parse the output of `flatpak search` with the columns
+/// `name,application,version,branch,remotes,description`.
+///
+/// The kind of each result is guessed from its ID, see
+/// [`FlatpakKind::of_id`].
+pub fn parse_flatpak_output(stdout: &[u8]) -> Vec<PackageInfo> {
+    let stdout = String::from_utf8_lossy(stdout);
+
+    if stdout.is_empty() {
+        return Vec::new();
+    }
+
+    // Pre-allocate with approximate capacity
+    let mut results = Vec::with_capacity(stdout.lines().count());
+
+    // Column titles are only printed to a terminal, and "No matches found"
+    // has no columns
+    for line in stdout.lines() {
+        if line.is_empty() {
+            continue;
+        }
+
+        let parts: Vec<&str> = line.split('\t').collect();
+
+        if parts.len() >= 6 {
+            let name = parts[0].trim();
+            let application_id = parts[1].trim();
+            // Application IDs are reverse DNS names, unlike a header row
+            if !application_id.contains('.') {
+                continue;
+            }
+            let version = match parts.get(2) {
+                Some(&v) if !v.trim().is_empty() => v.trim().to_string(),
+                _ => "Unknown".to_string(),
+            };
+            let branch = Some(parts[3].trim().to_string()).filter(|b| !b.is_empty());
+
+            let description = match parts.get(5) {
+                Some(&d) if !d.trim().is_empty() => d.trim().to_string(),
+                _ => "No description.".to_string(),
+            };
+
+            // One result per remote carrying the application
+            for remote in parts[4].split(',').map(str::trim) {
+                results.push(PackageInfo {
+                    app_id: Some(application_id.to_string()),
+                    repo: Some(remote.to_string()).filter(|r| !r.is_empty()),
+                    branch: branch.clone(),
+                    kind: Some(FlatpakKind::of_id(application_id)),
+                    ..PackageInfo::new(
+                        Source::Flatpak,
+                        format!("{} ({})", name, application_id),
+                        version.clone(),
+                        description.clone(),
+                    )
+                });
+            }
+        }
+    }
+
+    results
+}
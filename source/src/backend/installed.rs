@@ -0,0 +1,366 @@
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+use futures::future::BoxFuture;
+use futures::FutureExt;
+
+use super::{command, command_exists, command_output, Backend, BackendError};
+use crate::aur_rpc::RpcClient;
+use crate::package::{FlatpakKind, InstallReason, PackageInfo, Source};
+use crate::{localdb, syncdb};
+
+/// Installed pacman packages, read from the local database.
+///
+/// Packages found in a sync database belong to [`Source::Pacman`] and are
+/// tagged with their repository; foreign ones, like `pacman -Qm` lists
+/// them, belong to [`Source::Aur`]. `version` is the version available from
+/// the sync database or, given an RPC client, the AUR.
+pub struct LocalBackend {
+    source: Source,
+    local_dir: PathBuf,
+    sync_dir: PathBuf,
+    rpc: Option<RpcClient>,
+}
+
+impl LocalBackend {
+    /// Installed packages from `source`, which is either
+    /// [`Source::Pacman`] or [`Source::Aur`].
+    pub fn new(source: Source) -> Self {
+        LocalBackend {
+            source,
+            local_dir: PathBuf::from(localdb::DEFAULT_LOCAL_DIR),
+            sync_dir: PathBuf::from(syncdb::DEFAULT_SYNC_DIR),
+            rpc: None,
+        }
+    }
+
+    /// Look up the available versions of foreign packages in the AUR.
+    pub fn with_rpc(mut self, rpc: RpcClient) -> Self {
+        self.rpc = Some(rpc);
+        self
+    }
+
+    pub fn with_local_dir(mut self, local_dir: impl Into<PathBuf>) -> Self {
+        self.local_dir = local_dir.into();
+        self
+    }
+
+    pub fn with_sync_dir(mut self, sync_dir: impl Into<PathBuf>) -> Self {
+        self.sync_dir = sync_dir.into();
+        self
+    }
+}
+
+impl Backend for LocalBackend {
+    fn name(&self) -> &str {
+        self.source.label()
+    }
+
+    fn source(&self) -> Source {
+        self.source
+    }
+
+    fn is_available(&self) -> BoxFuture<'_, bool> {
+        let available = self.local_dir.is_dir();
+        async move { available }.boxed()
+    }
+
+    fn search<'a>(
+        &'a self,
+        term: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<PackageInfo>, BackendError>> {
+        let source = self.source;
+        let local_dir = self.local_dir.clone();
+        let sync_dir = self.sync_dir.clone();
+        let term = term.to_string();
+
+        async move {
+            let mut packages = tokio::task::spawn_blocking(move || {
+                let mut packages = localdb::read_local_db(&local_dir)?;
+                packages.retain(|package| syncdb::matches_term(package, &term));
+
+                // Without sync databases every package counts as a repository one
+                let available = syncdb::sync_versions(&sync_dir).unwrap_or_default();
+                let foreign = |package: &PackageInfo| {
+                    !available.is_empty() && !available.contains_key(&package.name)
+                };
+                packages.retain(|package| foreign(package) == (source == Source::Aur));
+
+                for package in &mut packages {
+                    package.source = source;
+                    if let Some((repo, version)) = available.get(&package.name) {
+                        package.repo = Some(repo.clone());
+                        package.version = version.clone();
+                    }
+                }
+                Ok::<_, std::io::Error>(packages)
+            })
+            .await
+            .map_err(|e| BackendError::Io(e.to_string()))??;
+
+            if let (Source::Aur, Some(rpc), false) = (source, &self.rpc, packages.is_empty()) {
+                let names: Vec<&str> = packages.iter().map(|p| p.name.as_str()).collect();
+                // Without the AUR only the installed versions are known
+                if let Ok(info) = rpc.info(&names).await {
+                    let versions: HashMap<String, String> =
+                        info.into_iter().map(|p| (p.name, p.version)).collect();
+                    for package in &mut packages {
+                        if let Some(version) = versions.get(&package.name) {
+                            package.version = version.clone();
+                        }
+                    }
+                }
+            }
+            Ok(packages)
+        }
+        .boxed()
+    }
+}
+
+/// The columns requested from `flatpak list`, in the order
+/// [`parse_flatpak_list`] expects them.
+pub const FLATPAK_LIST_COLUMNS: &str =
+    "name,application,version,branch,arch,origin,installation,size,description";
+
+/// Installed Flatpak applications and runtimes, listed by `flatpak list`.
+///
+/// Applications count as explicitly installed and runtimes as dependencies.
+pub struct FlatpakInstalledBackend;
+
+impl Backend for FlatpakInstalledBackend {
+    fn name(&self) -> &str {
+        "Flatpak"
+    }
+
+    fn source(&self) -> Source {
+        Source::Flatpak
+    }
+
+    fn is_available(&self) -> BoxFuture<'_, bool> {
+        command_exists("flatpak").boxed()
+    }
+
+    fn search<'a>(
+        &'a self,
+        term: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<PackageInfo>, BackendError>> {
+        async move {
+            let columns = format!("--columns={}", FLATPAK_LIST_COLUMNS);
+            let mut apps = command("flatpak");
+            apps.args(["list", "--app", &columns]);
+            let mut runtimes = command("flatpak");
+            runtimes.args(["list", "--runtime", &columns]);
+
+            let mut updates = command("flatpak");
+            updates.args(["remote-ls", "--updates", "--columns=application,version"]);
+
+            let (apps, runtimes, updates) = futures::join!(
+                command_output(&mut apps, "flatpak"),
+                command_output(&mut runtimes, "flatpak"),
+                command_output(&mut updates, "flatpak")
+            );
+            let mut packages = parse_flatpak_list(&apps?, InstallReason::Explicit);
+            packages.extend(parse_flatpak_list(&runtimes?, InstallReason::Dependency));
+            packages.retain(|package| syncdb::matches_term(package, term));
+
+            // Checking for updates needs the network; without it only the
+            // installed versions are known
+            if let Ok(updates) = updates {
+                let updates = parse_flatpak_versions(&updates);
+                for package in &mut packages {
+                    let update = package.app_id.as_ref().and_then(|id| updates.get(id));
+                    if let Some(version) = update {
+                        package.version = version.clone();
+                    }
+                }
+            }
+            Ok(packages)
+        }
+        .boxed()
+    }
+}
+
+/// Parse `flatpak list` output with the [`FLATPAK_LIST_COLUMNS`] columns.
+///
+/// The install date is the time the active deployment was created, when
+/// the installation is the system-wide or the per-user one.
+pub fn parse_flatpak_list(stdout: &[u8], reason: InstallReason) -> Vec<PackageInfo> {
+    let stdout = String::from_utf8_lossy(stdout);
+    let installations = installation_dirs();
+
+    stdout
+        .lines()
+        .filter_map(|line| {
+            let fields: Vec<&str> = line.split('\t').map(str::trim).collect();
+            let [name, app_id, version, branch, arch, origin, installation, size, description] =
+                fields[..]
+            else {
+                return None;
+            };
+            // Application IDs are reverse DNS names, unlike a header row
+            if !app_id.contains('.') {
+                return None;
+            }
+
+            let (ref_kind, kind) = match reason {
+                InstallReason::Explicit => ("app", FlatpakKind::App),
+                InstallReason::Dependency => ("runtime", FlatpakKind::of_runtime(app_id)),
+            };
+            let install_date = installations.get(installation).and_then(|dir| {
+                deploy_date(&dir.join(ref_kind).join(app_id).join(arch).join(branch))
+            });
+            let name = if name.is_empty() { app_id } else { name };
+            let version = if version.is_empty() { branch } else { version };
+            let description = if description.is_empty() {
+                "No description."
+            } else {
+                description
+            };
+
+            Some(PackageInfo {
+                app_id: Some(app_id.to_string()),
+                repo: Some(origin.to_string()).filter(|origin| !origin.is_empty()),
+                branch: Some(branch.to_string()).filter(|branch| !branch.is_empty()),
+                arch: Some(arch.to_string()).filter(|arch| !arch.is_empty()),
+                kind: Some(kind),
+                installed_version: Some(version.to_string()),
+                installations: vec![installation.to_string()],
+                install_reason: Some(reason),
+                install_date,
+                installed_size: parse_size(size),
+                ..PackageInfo::new(
+                    Source::Flatpak,
+                    format!("{} ({})", name, app_id),
+                    version,
+                    description,
+                )
+            })
+        })
+        .collect()
+}
+
+/// An installed Flatpak application or runtime.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct InstalledFlatpak {
+    /// Empty when the application declares no version.
+    pub version: String,
+    /// The installations it is installed in, e.g. `system` and `user`.
+    pub installations: Vec<String>,
+}
+
+/// Every installed Flatpak application and runtime, by ID.
+pub async fn installed_flatpaks() -> Result<HashMap<String, InstalledFlatpak>, BackendError> {
+    let mut list = command("flatpak");
+    list.args(["list", "--columns=application,version,installation"]);
+    let stdout = command_output(&mut list, "flatpak").await?;
+    Ok(parse_installed_flatpaks(&stdout))
+}
+
+/// Parse `application\tversion\tinstallation` lines.
+pub fn parse_installed_flatpaks(stdout: &[u8]) -> HashMap<String, InstalledFlatpak> {
+    let mut installed: HashMap<String, InstalledFlatpak> = HashMap::new();
+    for line in String::from_utf8_lossy(stdout).lines() {
+        let mut fields = line.split('\t').map(str::trim);
+        let app_id = fields.next().unwrap_or_default();
+        // Application IDs are reverse DNS names, unlike a header row
+        if !app_id.contains('.') {
+            continue;
+        }
+        let entry = installed.entry(app_id.to_string()).or_default();
+        if let Some(version) = fields.next().filter(|v| !v.is_empty()) {
+            entry.version = version.to_string();
+        }
+        if let Some(installation) = fields.next().filter(|i| !i.is_empty()) {
+            if !entry.installations.iter().any(|i| i == installation) {
+                entry.installations.push(installation.to_string());
+            }
+        }
+    }
+    installed
+}
+
+/// Fill in `installed_version` and `installations` for every Flatpak in
+/// `packages` that is in `installed`, as returned by [`installed_flatpaks`].
+pub fn mark_flatpak_installed(
+    packages: &mut [PackageInfo],
+    installed: &HashMap<String, InstalledFlatpak>,
+) {
+    for package in packages {
+        let Some(flatpak) = package.app_id.as_ref().and_then(|id| installed.get(id)) else {
+            continue;
+        };
+        // Applications without a version are taken to be the available one
+        let version = if flatpak.version.is_empty() {
+            &package.version
+        } else {
+            &flatpak.version
+        };
+        package.installed_version = Some(version.clone());
+        package.installations = flatpak.installations.clone();
+    }
+}
+
+/// Parse `application\tversion` lines.
+fn parse_flatpak_versions(stdout: &[u8]) -> HashMap<String, String> {
+    String::from_utf8_lossy(stdout)
+        .lines()
+        .filter_map(|line| {
+            let (app_id, version) = line.split_once('\t').unwrap_or((line, ""));
+            let app_id = app_id.trim();
+            app_id
+                .contains('.')
+                .then(|| (app_id.to_string(), version.trim().to_string()))
+        })
+        .collect()
+}
+
+/// Parse a size as flatpak prints it, e.g. `1.2 GB` or `512 bytes`.
+pub fn parse_size(size: &str) -> Option<u64> {
+    // GLib separates the unit with a non-breaking space, which is whitespace too
+    let mut parts = size.split_whitespace();
+    let number: f64 = parts.next()?.replace(',', ".").parse().ok()?;
+    let unit = parts.next().unwrap_or("bytes");
+
+    let factor: f64 = match unit {
+        "byte" | "bytes" | "B" => 1.0,
+        "kB" | "KB" => 1e3,
+        "MB" => 1e6,
+        "GB" => 1e9,
+        "TB" => 1e12,
+        "KiB" => 1024.0,
+        "MiB" => 1024.0 * 1024.0,
+        "GiB" => 1024.0 * 1024.0 * 1024.0,
+        _ => return None,
+    };
+    Some((number * factor).round() as u64)
+}
+
+/// The directories of the system-wide and per-user installations, by the
+/// name `flatpak list` gives them.
+pub(super) fn installation_dirs() -> HashMap<&'static str, PathBuf> {
+    let mut dirs = HashMap::new();
+    dirs.insert("system", PathBuf::from("/var/lib/flatpak"));
+
+    let data_home = std::env::var_os("XDG_DATA_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".local/share")));
+    if let Some(data_home) = data_home {
+        dirs.insert("user", data_home.join("flatpak"));
+    }
+
+    dirs
+}
+
+/// When the `active` deployment link under `ref_dir` was last replaced.
+fn deploy_date(ref_dir: &Path) -> Option<i64> {
+    let modified = ref_dir
+        .join("active")
+        .symlink_metadata()
+        .ok()?
+        .modified()
+        .ok()?;
+    let elapsed = modified.duration_since(UNIX_EPOCH).ok()?;
+    Some(elapsed.as_secs() as i64)
+}
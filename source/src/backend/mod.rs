@@ -0,0 +1,176 @@
+//! Package sources and the trait they implement.
+
+mod aur;
+mod error;
+mod files;
+mod flatpak;
+mod installed;
+mod pacman;
+
+pub use aur::{AurBackend, AurHelper, AurRpcBackend};
+pub use error::BackendError;
+pub use files::FilesBackend;
+pub use flatpak::{
+    add_flatpak_permissions, flatpak_permissions, parse_flatpak_output, parse_flatpak_refs,
+    FlatpakBackend,
+};
+pub use installed::{
+    installed_flatpaks, mark_flatpak_installed, parse_flatpak_list, parse_installed_flatpaks,
+    parse_size, FlatpakInstalledBackend, InstalledFlatpak, LocalBackend, FLATPAK_LIST_COLUMNS,
+};
+pub use pacman::PacmanBackend;
+
+use std::io;
+use std::process::Stdio;
+use std::sync::Arc;
+
+use futures::future::BoxFuture;
+use futures::FutureExt;
+
+use crate::aur_rpc::RpcClient;
+use crate::package::{PackageInfo, Source};
+
+/// A searchable package source.
+///
+/// Implementations are driven concurrently by [`crate::search::search_packages`],
+/// so they must be shareable across tasks.
+pub trait Backend: Send + Sync {
+    /// Name shown in section headers and warnings.
+    fn name(&self) -> &str;
+
+    /// The source this backend's results belong to.
+    fn source(&self) -> Source;
+
+    /// Whether the tools this backend relies on are installed.
+    fn is_available(&self) -> BoxFuture<'_, bool>;
+
+    /// Search for packages matching `term`.
+    fn search<'a>(&'a self, term: &'a str)
+        -> BoxFuture<'a, Result<Vec<PackageInfo>, BackendError>>;
+
+    /// Find the packages called `name`, see [`PackageInfo::is_named`], with
+    /// every detail the source knows about.
+    ///
+    /// By default this searches for `name` and keeps the exact matches.
+    fn info<'a>(&'a self, name: &'a str) -> BoxFuture<'a, Result<Vec<PackageInfo>, BackendError>> {
+        async move {
+            let mut packages = self.search(name).await?;
+            packages.retain(|package| package.is_named(name));
+            Ok(packages)
+        }
+        .boxed()
+    }
+}
+
+/// The default backends in display order: pacman, the given AUR backend and
+/// Flatpak.
+pub fn default_backends(aur: Arc<dyn Backend>) -> Vec<Arc<dyn Backend>> {
+    vec![
+        Arc::new(PacmanBackend::default()),
+        aur,
+        Arc::new(FlatpakBackend),
+    ]
+}
+
+/// The backends for `sources`, in that order, using `aur` for the AUR.
+pub fn backends_for(sources: &[Source], aur: Arc<dyn Backend>) -> Vec<Arc<dyn Backend>> {
+    sources
+        .iter()
+        .map(|source| -> Arc<dyn Backend> {
+            match source {
+                Source::Pacman => Arc::new(PacmanBackend::default()),
+                Source::Aur => Arc::clone(&aur),
+                Source::Flatpak => Arc::new(FlatpakBackend),
+            }
+        })
+        .collect()
+}
+
+/// The backends searching the installed packages from `sources`, in that
+/// order, using `rpc` to find the versions available from the AUR.
+pub fn installed_backends_for(sources: &[Source], rpc: RpcClient) -> Vec<Arc<dyn Backend>> {
+    sources
+        .iter()
+        .map(|&source| -> Arc<dyn Backend> {
+            match source {
+                Source::Pacman => Arc::new(LocalBackend::new(source)),
+                Source::Aur => Arc::new(LocalBackend::new(source).with_rpc(rpc.clone())),
+                Source::Flatpak => Arc::new(FlatpakInstalledBackend),
+            }
+        })
+        .collect()
+}
+
+/// The backends finding which packages from `sources` own a file, in that
+/// order. Flatpak applications are not searched.
+pub fn files_backends_for(sources: &[Source]) -> Vec<Arc<dyn Backend>> {
+    sources
+        .iter()
+        .filter(|&&source| source != Source::Flatpak)
+        .map(|&source| -> Arc<dyn Backend> { Arc::new(FilesBackend::new(source)) })
+        .collect()
+}
+
+/// A command for a backend to run.
+///
+/// The child runs in the C locale, so its output is not translated, and
+/// with `NO_COLOR` set. It cannot block on a prompt, since stdin is closed,
+/// and is killed when the search is dropped, so a timed-out search does not
+/// leave it running.
+pub(crate) fn command(program: &str) -> tokio::process::Command {
+    let mut command = tokio::process::Command::new(program);
+    command
+        .env("LC_ALL", "C")
+        .env_remove("LANGUAGE")
+        .env("NO_COLOR", "1")
+        .stdin(Stdio::null())
+        .kill_on_drop(true);
+    command
+}
+
+/// Run a command built with [`command`] and return its stdout.
+///
+/// pacman and the AUR helpers exit with 1 without printing anything when
+/// nothing matches, which is not treated as a failure.
+pub(crate) async fn command_output(
+    command: &mut tokio::process::Command,
+    program: &str,
+) -> Result<Vec<u8>, BackendError> {
+    let output = command.output().await.map_err(|e| match e.kind() {
+        io::ErrorKind::NotFound => BackendError::NotInstalled,
+        _ => BackendError::Io(format!("failed to run {}: {}", program, e)),
+    })?;
+
+    let stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    let no_matches =
+        output.status.code() == Some(1) && output.stdout.is_empty() && stderr.is_empty();
+    if output.status.success() || no_matches {
+        Ok(output.stdout)
+    } else {
+        Err(BackendError::Exit {
+            program: program.to_string(),
+            code: output.status.code(),
+            stderr,
+        })
+    }
+}
+
+/// Fail when `stdout` has content but none of it parsed as a package, which
+/// means the tool's output format is not the expected one.
+pub(crate) fn check_parsed(stdout: &[u8], packages: &[PackageInfo]) -> Result<(), BackendError> {
+    let stdout = String::from_utf8_lossy(stdout);
+    match stdout.lines().find(|line| !line.trim().is_empty()) {
+        Some(line) if packages.is_empty() => Err(BackendError::Parse(line.trim().to_string())),
+        _ => Ok(()),
+    }
+}
+
+/// Check whether `program` is on `PATH`.
+pub(crate) async fn command_exists(program: &str) -> bool {
+    command("which")
+        .arg(program)
+        .output()
+        .await
+        .map(|output| output.status.success())
+        .unwrap_or(false)
+}
@@ -0,0 +1,101 @@
+use std::path::{Path, PathBuf};
+
+use futures::future::BoxFuture;
+use futures::FutureExt;
+
+use super::{check_parsed, command, command_exists, command_output, Backend, BackendError};
+use crate::package::{PackageInfo, Source};
+use crate::parse::parse_search_output;
+use crate::{localdb, syncdb};
+
+/// Official repository packages.
+///
+/// Packages are read straight from pacman's sync databases. When none are
+/// found (e.g. before the first `pacman -Sy`) this falls back to `pacman -Ss`.
+pub struct PacmanBackend {
+    sync_dir: PathBuf,
+    local_dir: PathBuf,
+}
+
+impl Default for PacmanBackend {
+    fn default() -> Self {
+        PacmanBackend::new(syncdb::DEFAULT_SYNC_DIR)
+    }
+}
+
+impl PacmanBackend {
+    /// A backend reading the sync databases in `sync_dir`.
+    pub fn new(sync_dir: impl Into<PathBuf>) -> Self {
+        PacmanBackend {
+            sync_dir: sync_dir.into(),
+            local_dir: PathBuf::from(localdb::DEFAULT_LOCAL_DIR),
+        }
+    }
+
+    /// Use the local database in `local_dir` to tell which packages are
+    /// installed.
+    pub fn with_local_dir(mut self, local_dir: impl Into<PathBuf>) -> Self {
+        self.local_dir = local_dir.into();
+        self
+    }
+
+    pub fn sync_dir(&self) -> &Path {
+        &self.sync_dir
+    }
+
+    pub fn local_dir(&self) -> &Path {
+        &self.local_dir
+    }
+
+    fn has_sync_databases(&self) -> bool {
+        syncdb::sync_databases(&self.sync_dir).is_ok_and(|dbs| !dbs.is_empty())
+    }
+}
+
+impl Backend for PacmanBackend {
+    fn name(&self) -> &str {
+        "Pacman"
+    }
+
+    fn source(&self) -> Source {
+        Source::Pacman
+    }
+
+    fn is_available(&self) -> BoxFuture<'_, bool> {
+        async move { self.has_sync_databases() || command_exists("pacman").await }.boxed()
+    }
+
+    fn search<'a>(
+        &'a self,
+        term: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<PackageInfo>, BackendError>> {
+        async move {
+            if self.has_sync_databases() {
+                let sync_dir = self.sync_dir.clone();
+                let local_dir = self.local_dir.clone();
+                let term = term.to_string();
+
+                // Decompressing the databases is CPU-bound; keep it off the runtime
+                return tokio::task::spawn_blocking(move || {
+                    let mut packages = syncdb::read_sync_dir(&sync_dir)?;
+                    packages.retain(|package| syncdb::matches_term(package, &term));
+                    localdb::mark_installed(&mut packages, &local_dir);
+                    Ok::<_, std::io::Error>(packages)
+                })
+                .await
+                .map_err(|e| BackendError::Io(e.to_string()))?
+                .map_err(BackendError::from);
+            }
+
+            let mut search = command("pacman");
+            search.args(["-Ss", "--color", "never", term]);
+            let stdout = command_output(&mut search, "pacman").await?;
+            let mut packages = parse_search_output(&stdout, Source::Pacman);
+            check_parsed(&stdout, &packages)?;
+            // The install markers may be translated, the local database is not
+            localdb::mark_installed(&mut packages, &self.local_dir);
+            Ok(packages)
+        }
+        .boxed()
+    }
+}
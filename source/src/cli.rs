@@ -0,0 +1,580 @@
+use std::ffi::{OsStr, OsString};
+use std::path::{Path, PathBuf};
+
+use clap::builder::{PossibleValuesParser, TypedValueParser};
+use clap::error::ErrorKind;
+use clap::{Args, CommandFactory, Parser, Subcommand};
+use clap_complete::engine::{ArgValueCompleter, CompletionCandidate};
+use package_dictionary::config::{Config, ConfigError};
+use package_dictionary::index::Index;
+use package_dictionary::package::FlatpakKind;
+use package_dictionary::rank::{MatchField, SortOrder};
+use package_dictionary::render::OutputFormat;
+use package_dictionary::syncdb::{sync_versions, DEFAULT_SYNC_DIR};
+
+/// The environment variable the completion scripts call `pd` with, see
+/// [`clap_complete::CompleteEnv`].
+pub const COMPLETE_VAR: &str = "COMPLETE";
+
+/// Shells `pd completions` writes scripts for.
+pub const SHELLS: [&str; 3] = ["bash", "zsh", "fish"];
+
+/// Search pacman, the AUR and Flatpak at once.
+///
+/// Without a subcommand, `pd <term>` is `pd search <term>`.
+#[derive(Debug, Parser)]
+#[command(
+    name = "pd",
+    version,
+    override_usage = "pd [OPTIONS] [TERM]...\n       pd [OPTIONS] <COMMAND>",
+    subcommand_negates_reqs = true,
+    arg_required_else_help = true
+)]
+pub struct Cli {
+    #[command(subcommand)]
+    command: Option<CliCommand>,
+    #[command(flatten)]
+    search: SearchArgs,
+    /// Say which packages provide COMMAND, for the shell's command-not-found hook
+    #[arg(long, value_name = "COMMAND", conflicts_with = "terms")]
+    cnf: Option<String>,
+    #[command(flatten)]
+    global: GlobalArgs,
+}
+
+#[derive(Debug, Subcommand)]
+enum CliCommand {
+    /// Search every source for a term (the default)
+    Search(SearchArgs),
+    /// Show the details of packages, one column per source
+    Info {
+        /// Package names or Flatpak application IDs
+        #[arg(
+            required = true,
+            value_name = "NAME",
+            add = ArgValueCompleter::new(complete_package_names)
+        )]
+        names: Vec<String>,
+        /// Only read the offline index
+        #[arg(long)]
+        offline: bool,
+        /// Exit with status 1 when any source failed
+        #[arg(long)]
+        strict: bool,
+    },
+    /// Find the packages owning a path, file name or command
+    Provides {
+        /// An absolute path, a file name or a command
+        #[arg(value_name = "PATH|FILE|COMMAND")]
+        query: String,
+        /// Show one list interleaving every source
+        #[arg(long)]
+        merged: bool,
+        /// Exit with status 1 when any source failed
+        #[arg(long)]
+        strict: bool,
+    },
+    /// Install packages by name or by their number in the last search
+    Install {
+        /// Package names, Flatpak application IDs or numbers from the last search
+        #[arg(
+            required = true,
+            value_name = "NAME|NUMBER",
+            add = ArgValueCompleter::new(complete_package_names)
+        )]
+        targets: Vec<String>,
+        /// Print the commands instead of running them
+        #[arg(long)]
+        dry_run: bool,
+        /// Install without asking for confirmation
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Manage the offline index
+    Index {
+        #[command(subcommand)]
+        command: IndexCommand,
+    },
+    /// Show the configuration
+    Config {
+        #[command(subcommand)]
+        command: Option<ConfigCommand>,
+    },
+    /// Print a shell completion script
+    Completions {
+        #[arg(value_parser = SHELLS)]
+        shell: String,
+    },
+    /// Print the man page
+    Man,
+}
+
+#[derive(Debug, Subcommand)]
+enum IndexCommand {
+    /// Rebuild the package and command indexes
+    Update {
+        /// AUR metadata dump to index
+        #[arg(long, value_name = "FILE")]
+        aur_dump: Option<PathBuf>,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum ConfigCommand {
+    /// Print every setting after applying the environment and flags (the default)
+    Show,
+    /// Print where the config file is read from
+    Path,
+}
+
+#[derive(Debug, Args)]
+struct SearchArgs {
+    /// What to search for
+    #[arg(value_name = "TERM")]
+    terms: Vec<String>,
+    /// Pick results in a full-screen picker and install them
+    #[arg(short, long)]
+    interactive: bool,
+    /// Search the installed packages instead of the available ones
+    #[arg(short = 'Q', long)]
+    installed: bool,
+    /// Only show installed packages with a newer version available
+    #[arg(long)]
+    upgradable: bool,
+    /// Only answer from the offline index
+    #[arg(long, conflicts_with_all = ["live", "installed"])]
+    offline: bool,
+    /// Ignore the offline index
+    #[arg(long)]
+    live: bool,
+    /// Exit with status 1 when any source failed
+    #[arg(long)]
+    strict: bool,
+    /// Show one list interleaving every source
+    #[arg(long)]
+    merged: bool,
+    /// Show each app once, with its packages from every source as flavors
+    #[arg(long, conflicts_with = "merged")]
+    group: bool,
+    /// Which fields the term has to match
+    #[arg(
+        long = "match",
+        value_name = "FIELD",
+        value_parser = PossibleValuesParser::new(["name", "description", "id", "all"])
+            .try_map(|field| field.parse::<MatchField>())
+    )]
+    match_field: Option<MatchField>,
+    /// Only show Flatpaks from this remote
+    #[arg(long, value_name = "REMOTE")]
+    flatpak_remote: Option<String>,
+    /// Hide Flatpak runtimes and extensions
+    #[arg(long, conflicts_with = "include_runtimes")]
+    apps_only: bool,
+    /// Show Flatpak runtimes too
+    #[arg(long)]
+    include_runtimes: bool,
+    /// Wait for every source before printing
+    #[arg(long)]
+    no_stream: bool,
+}
+
+#[derive(Debug, Args)]
+#[command(next_help_heading = "Global options")]
+struct GlobalArgs {
+    /// Search the official repositories
+    #[arg(long, global = true)]
+    repo: bool,
+    /// Search the AUR
+    #[arg(long, global = true)]
+    aur: bool,
+    /// Search Flatpak
+    #[arg(long, global = true)]
+    flatpak: bool,
+    /// Comma-separated sources to search, in display order
+    #[arg(
+        long,
+        global = true,
+        value_name = "LIST",
+        value_parser = setting("backends"),
+        conflicts_with_all = ["repo", "aur", "flatpak"]
+    )]
+    backends: Option<String>,
+    /// Output format
+    #[arg(
+        long,
+        global = true,
+        value_parser = PossibleValuesParser::new(["text", "json", "ndjson"])
+            .try_map(|format| format.parse::<OutputFormat>())
+    )]
+    format: Option<OutputFormat>,
+    /// When to color the output
+    #[arg(
+        long,
+        global = true,
+        value_name = "WHEN",
+        value_parser = ["auto", "always", "never"]
+    )]
+    color: Option<String>,
+    /// Show at most N packages per source
+    #[arg(long, global = true, value_name = "N")]
+    limit: Option<usize>,
+    /// Order of the results
+    #[arg(
+        long,
+        global = true,
+        value_name = "ORDER",
+        value_parser = PossibleValuesParser::new(["relevance", "name", "popularity", "source"])
+            .try_map(|order| order.parse::<SortOrder>())
+    )]
+    sort: Option<SortOrder>,
+    /// Seconds each source may take
+    #[arg(long, global = true, value_name = "SECONDS", value_parser = setting("timeout"))]
+    timeout: Option<String>,
+    /// Config file to read instead of the default one
+    #[arg(long, global = true, value_name = "FILE")]
+    config: Option<PathBuf>,
+    /// Pager command for long output
+    #[arg(long, global = true, value_name = "COMMAND")]
+    pager: Option<String>,
+    /// Print long output directly instead of paging it
+    #[arg(long, global = true, conflicts_with = "pager")]
+    no_pager: bool,
+    /// AUR helper for helper searches and installs
+    #[arg(long, global = true, value_name = "HELPER", value_parser = ["paru", "yay"])]
+    aur_helper: Option<String>,
+    /// How to search the AUR
+    #[arg(long, global = true, value_name = "MODE", value_parser = ["rpc", "helper"])]
+    aur_search: Option<String>,
+    /// Override any setting, e.g. `--set timeouts.aur=10`
+    #[arg(long, global = true, value_name = "KEY=VALUE", value_parser = key_value)]
+    set: Vec<(String, String)>,
+}
+
+/// What to do.
+#[derive(Debug, PartialEq, Eq)]
+pub enum Command {
+    Search,
+    /// Show the details of the packages with these names.
+    Info(Vec<String>),
+    /// Find the packages owning a path, file name or command.
+    Provides(String),
+    /// Install packages by name or by number in the last search.
+    Install {
+        targets: Vec<String>,
+        /// Print the commands instead of running them.
+        dry_run: bool,
+        /// Do not ask for confirmation.
+        yes: bool,
+    },
+    /// Rebuild the offline index.
+    IndexUpdate,
+    /// Print the effective settings.
+    ShowConfig,
+    /// Print the config file location.
+    ConfigPath,
+    /// Print the completion script for a shell in [`SHELLS`].
+    Completions(String),
+    /// Print the man page.
+    Man,
+    /// Say which packages provide a command the shell did not find.
+    NotFound(String),
+}
+
+/// Command-line options for a search.
+#[derive(Debug)]
+pub struct Options {
+    pub command: Command,
+    pub term: String,
+    /// Output format, overriding the configured one.
+    pub format: Option<OutputFormat>,
+    /// Open the interactive picker instead of printing results.
+    pub interactive: bool,
+    pub sort: SortOrder,
+    /// The fields search terms have to match.
+    pub match_field: MatchField,
+    /// Only show Flatpaks from this remote.
+    pub flatpak_remote: Option<String>,
+    /// The kinds of Flatpaks to show; Flatpaks of unknown kind are shown
+    /// as applications.
+    pub flatpak_kinds: Vec<FlatpakKind>,
+    /// Interleave all sources in one list.
+    pub merged: bool,
+    /// Show one entry per application across sources.
+    pub grouped: bool,
+    /// Only answer from the index, never running a backend.
+    pub offline: bool,
+    /// Search the installed packages instead of the available ones.
+    pub installed: bool,
+    /// Only show installed packages with a newer version available.
+    pub upgradable: bool,
+    /// Exit with an error when any source failed.
+    pub strict: bool,
+    /// Most packages shown per source.
+    pub limit: Option<usize>,
+    /// Config file to read instead of the default one.
+    pub config: Option<PathBuf>,
+    /// Settings overriding the config file and environment, as
+    /// `(key, value)` pairs for [`Config::set`].
+    pub overrides: Vec<(String, String)>,
+}
+
+/// Parse the command line, program name included.
+///
+/// Errors are usage errors; [`clap::Error::exit`] prints them with the
+/// usage and exits with status 2, or prints `--help` and `--version` and
+/// exits with status 0.
+pub fn parse_args<I, T>(args: I) -> Result<Options, clap::Error>
+where
+    I: IntoIterator<Item = T>,
+    T: Into<OsString> + Clone,
+{
+    let Cli {
+        command,
+        search,
+        cnf,
+        global,
+    } = Cli::try_parse_from(args)?;
+
+    let mut options = Options {
+        command: Command::Search,
+        term: String::new(),
+        format: global.format,
+        interactive: false,
+        sort: global.sort.unwrap_or_default(),
+        match_field: MatchField::All,
+        flatpak_remote: None,
+        flatpak_kinds: vec![FlatpakKind::App, FlatpakKind::Extension],
+        merged: false,
+        grouped: false,
+        offline: false,
+        installed: false,
+        upgradable: false,
+        strict: false,
+        limit: global.limit,
+        config: global.config.clone(),
+        overrides: global.overrides(),
+    };
+
+    if command.is_some() && (cnf.is_some() || search.is_given()) {
+        return Err(Cli::command().error(
+            ErrorKind::ArgumentConflict,
+            "search options cannot be used with a subcommand, give its options after it",
+        ));
+    }
+
+    match command {
+        None => match cnf {
+            Some(command) => options.command = Command::NotFound(command),
+            None => search.apply(&mut options)?,
+        },
+        Some(CliCommand::Search(search)) => search.apply(&mut options)?,
+        Some(CliCommand::Info {
+            names,
+            offline,
+            strict,
+        }) => {
+            options.command = Command::Info(names);
+            options.offline = offline;
+            options.strict = strict;
+        }
+        Some(CliCommand::Provides {
+            query,
+            merged,
+            strict,
+        }) => {
+            options.command = Command::Provides(query);
+            options.merged = merged;
+            options.strict = strict;
+        }
+        Some(CliCommand::Install {
+            targets,
+            dry_run,
+            yes,
+        }) => {
+            options.command = Command::Install {
+                targets,
+                dry_run,
+                yes,
+            }
+        }
+        Some(CliCommand::Index {
+            command: IndexCommand::Update { aur_dump },
+        }) => {
+            options.command = Command::IndexUpdate;
+            if let Some(dump) = aur_dump {
+                let dump = dump.to_string_lossy().into_owned();
+                options.overrides.push(("aur.dump".to_string(), dump));
+            }
+        }
+        Some(CliCommand::Config { command }) => {
+            options.command = match command {
+                None | Some(ConfigCommand::Show) => Command::ShowConfig,
+                Some(ConfigCommand::Path) => Command::ConfigPath,
+            };
+        }
+        Some(CliCommand::Completions { shell }) => options.command = Command::Completions(shell),
+        Some(CliCommand::Man) => options.command = Command::Man,
+    }
+
+    Ok(options)
+}
+
+impl SearchArgs {
+    /// Whether any search term or option was given.
+    fn is_given(&self) -> bool {
+        !self.terms.is_empty()
+            || self.interactive
+            || self.installed
+            || self.upgradable
+            || self.offline
+            || self.live
+            || self.strict
+            || self.merged
+            || self.group
+            || self.match_field.is_some()
+            || self.flatpak_remote.is_some()
+            || self.apps_only
+            || self.include_runtimes
+            || self.no_stream
+    }
+
+    fn apply(self, options: &mut Options) -> Result<(), clap::Error> {
+        if self.terms.is_empty() && !self.installed {
+            return Err(Cli::command().error(
+                ErrorKind::MissingRequiredArgument,
+                "a search term is required unless --installed is given",
+            ));
+        }
+
+        options.command = Command::Search;
+        options.term = self.terms.join(" ");
+        options.interactive = self.interactive;
+        options.installed = self.installed;
+        options.upgradable = self.upgradable;
+        options.offline = self.offline;
+        options.strict = self.strict;
+        options.merged = self.merged;
+        options.grouped = self.group;
+        options.match_field = self.match_field.unwrap_or_default();
+        options.flatpak_remote = self.flatpak_remote;
+        // Installed runtimes take space too, so `-Q` lists them unless
+        // asked for applications only
+        options.flatpak_kinds = if self.apps_only {
+            vec![FlatpakKind::App]
+        } else if self.include_runtimes || self.installed {
+            vec![
+                FlatpakKind::App,
+                FlatpakKind::Runtime,
+                FlatpakKind::Extension,
+            ]
+        } else {
+            vec![FlatpakKind::App, FlatpakKind::Extension]
+        };
+        if self.live {
+            options
+                .overrides
+                .push(("index".to_string(), "false".to_string()));
+        }
+        if self.no_stream {
+            options
+                .overrides
+                .push(("stream".to_string(), "false".to_string()));
+        }
+        Ok(())
+    }
+}
+
+impl GlobalArgs {
+    /// The settings these flags override, with `--set` last so it wins.
+    fn overrides(&self) -> Vec<(String, String)> {
+        let mut overrides = Vec::new();
+        let mut set = |key: &str, value: &str| overrides.push((key.to_string(), value.to_string()));
+
+        let sources: Vec<&str> = [
+            (self.repo, "pacman"),
+            (self.aur, "aur"),
+            (self.flatpak, "flatpak"),
+        ]
+        .into_iter()
+        .filter_map(|(selected, id)| selected.then_some(id))
+        .collect();
+        if !sources.is_empty() {
+            set("backends", &sources.join(","));
+        }
+
+        let settings = [
+            ("backends", &self.backends),
+            ("color", &self.color),
+            ("timeout", &self.timeout),
+            ("pager", &self.pager),
+            ("aur.helper", &self.aur_helper),
+            ("aur.search", &self.aur_search),
+        ];
+        for (key, value) in settings {
+            if let Some(value) = value {
+                set(key, value);
+            }
+        }
+        if self.no_pager {
+            set("pager", "");
+        }
+        for (key, value) in &self.set {
+            set(key, value);
+        }
+        overrides
+    }
+}
+
+/// A value parser accepting the values [`Config::set`] accepts for `key`.
+fn setting(key: &'static str) -> impl Fn(&str) -> Result<String, String> + Clone + Send + Sync {
+    move |value| {
+        Config::default()
+            .set(key, value)
+            .map(|()| value.to_string())
+            .map_err(setting_error)
+    }
+}
+
+/// Parse and check a `--set KEY=VALUE`.
+fn key_value(value: &str) -> Result<(String, String), String> {
+    let (key, value) = value
+        .split_once('=')
+        .ok_or_else(|| format!("expected KEY=VALUE, got '{}'", value))?;
+    Config::default().set(key, value).map_err(setting_error)?;
+    Ok((key.to_string(), value.to_string()))
+}
+
+fn setting_error(error: ConfigError) -> String {
+    match error {
+        ConfigError::Invalid { key, message } if message == "unknown setting" => {
+            format!("unknown setting '{}'", key)
+        }
+        ConfigError::Invalid { message, .. } => message,
+        error => error.to_string(),
+    }
+}
+
+/// Complete the package names `pd info` and `pd install` take, from the
+/// offline index when there is one and the sync databases otherwise.
+fn complete_package_names(current: &OsStr) -> Vec<CompletionCandidate> {
+    let Some(current) = current.to_str() else {
+        return Vec::new();
+    };
+
+    let index = Index::default_path().and_then(|path| Index::open(&path).ok());
+    let mut names: Vec<String> = match index {
+        Some(index) => index
+            .names()
+            .filter(|name| name.starts_with(current))
+            .map(str::to_string)
+            .collect(),
+        None => sync_versions(Path::new(DEFAULT_SYNC_DIR))
+            .unwrap_or_default()
+            .into_keys()
+            .filter(|name| name.starts_with(current))
+            .collect(),
+    };
+    names.sort();
+    names.dedup();
+    names.into_iter().map(CompletionCandidate::new).collect()
+}
@@ -0,0 +1,280 @@
+//! The command index behind `pd --cnf`, the command-not-found hook.
+//!
+//! `pd index update` records which packages provide each command: the
+//! files in `/usr/bin` of every package in the files databases, the binaries
+//! Flatpak applications declare in their appstream data, and for the AUR,
+//! whose metadata has no file lists, the package names and provides. A
+//! lookup reads one file and binary-searches it in place, without decoding
+//! the rest, so the shell hook answers in a few milliseconds.
+//!
+//! The file starts with [`MAGIC`], followed by one line per provider sorted
+//! by command, with tab-separated command, source id, package name,
+//! repository and application ID.
+
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::files::read_commands;
+use crate::group::base_name;
+use crate::index::cache_dir;
+use crate::package::{PackageInfo, Source};
+use crate::syncdb::files_databases;
+
+/// Identifies the command index format.
+pub const MAGIC: &[u8; 9] = b"PDCMDS01\n";
+
+/// A package providing a command.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommandProvider {
+    pub command: String,
+    pub source: Source,
+    pub name: String,
+    /// The repository or Flatpak remote.
+    pub repo: Option<String>,
+    /// Flatpak application ID.
+    pub app_id: Option<String>,
+}
+
+impl CommandProvider {
+    /// The package to install for this command, with the fields
+    /// [`crate::install::install_commands`] needs.
+    pub fn package(&self) -> PackageInfo {
+        PackageInfo {
+            repo: self.repo.clone(),
+            app_id: self.app_id.clone(),
+            ..PackageInfo::new(self.source, self.name.clone(), "", "")
+        }
+    }
+}
+
+/// Collects the commands of every package and writes them out as a
+/// command index.
+#[derive(Default)]
+pub struct CommandIndexBuilder {
+    providers: Vec<CommandProvider>,
+}
+
+impl CommandIndexBuilder {
+    pub fn new() -> Self {
+        CommandIndexBuilder::default()
+    }
+
+    /// Add the commands in `/usr/bin` of every package in the files
+    /// databases in `dir`, returning how many were found.
+    pub fn add_files_dir(&mut self, dir: &Path) -> io::Result<usize> {
+        let before = self.providers.len();
+        for (repo, path) in files_databases(dir)? {
+            for (command, name) in read_commands(&path)? {
+                self.providers.push(CommandProvider {
+                    command,
+                    source: Source::Pacman,
+                    name,
+                    repo: Some(repo.clone()),
+                    app_id: None,
+                });
+            }
+        }
+        Ok(self.providers.len() - before)
+    }
+
+    /// Add the commands AUR and Flatpak `packages` likely provide, see
+    /// [`guess_commands`].
+    pub fn add_packages<'a>(&mut self, packages: impl IntoIterator<Item = &'a PackageInfo>) {
+        for package in packages {
+            for command in guess_commands(package) {
+                self.providers.push(CommandProvider {
+                    command,
+                    source: package.source,
+                    name: package.name.clone(),
+                    repo: package.repo.clone(),
+                    app_id: package.app_id.clone(),
+                });
+            }
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.providers.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.providers.is_empty()
+    }
+
+    /// Write the index to `path`, replacing any previous one only once the
+    /// new one is complete.
+    pub fn write(mut self, path: &Path) -> io::Result<()> {
+        self.providers
+            .sort_by(|a, b| (&a.command, a.source, &a.name).cmp(&(&b.command, b.source, &b.name)));
+        self.providers.dedup();
+
+        let mut bytes = MAGIC.to_vec();
+        for provider in &self.providers {
+            let fields = [
+                provider.command.as_str(),
+                provider.source.id(),
+                provider.name.as_str(),
+                provider.repo.as_deref().unwrap_or(""),
+                provider.app_id.as_deref().unwrap_or(""),
+            ];
+            for (i, field) in fields.iter().enumerate() {
+                if i > 0 {
+                    bytes.push(b'\t');
+                }
+                // Keep every record on its own line
+                bytes.extend(
+                    field
+                        .bytes()
+                        .map(|b| if b == b'\t' || b == b'\n' { b' ' } else { b }),
+                );
+            }
+            bytes.push(b'\n');
+        }
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let partial = path.with_extension("partial");
+        let mut file = File::create(&partial)?;
+        file.write_all(&bytes)?;
+        file.sync_all()?;
+        fs::rename(&partial, path)
+    }
+}
+
+/// The commands an AUR package or Flatpak application probably provides,
+/// since neither source lists their files.
+///
+/// An AUR package provides its own name without suffixes such as `-git`,
+/// and whatever it `provides` that is not a library. A Flatpak provides the
+/// binaries its appstream data declares and the last part of its
+/// application ID, lowercased. Repository packages are indexed from their
+/// file lists instead and guess nothing.
+pub fn guess_commands(package: &PackageInfo) -> Vec<String> {
+    let mut commands = Vec::new();
+    match package.source {
+        Source::Pacman => {}
+        Source::Aur => {
+            commands.push(base_name(&package.name).to_string());
+            for provided in &package.provides {
+                let name = provided.split(['=', '<', '>']).next().unwrap_or(provided);
+                if !name.contains(".so") {
+                    commands.push(name.to_string());
+                }
+            }
+        }
+        Source::Flatpak => {
+            commands.extend(package.provides.iter().cloned());
+            if let Some(last) = package
+                .app_id
+                .as_deref()
+                .and_then(|id| id.rsplit('.').next())
+            {
+                commands.push(last.to_lowercase());
+            }
+        }
+    }
+
+    commands.retain(|command| !command.is_empty() && !command.contains('/'));
+    commands.sort();
+    commands.dedup();
+    commands
+}
+
+/// A command index opened for lookups.
+pub struct CommandIndex {
+    /// The provider lines, without [`MAGIC`].
+    data: Vec<u8>,
+}
+
+impl CommandIndex {
+    /// Where `pd index update` writes the command index.
+    pub fn default_path() -> Option<PathBuf> {
+        cache_dir().map(|dir| dir.join("commands"))
+    }
+
+    /// Read the command index at `path`.
+    pub fn open(path: &Path) -> io::Result<CommandIndex> {
+        let mut data = fs::read(path)?;
+        if !data.starts_with(MAGIC) {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!(
+                    "{}: not a command index, run `pd index update`",
+                    path.display()
+                ),
+            ));
+        }
+        data.drain(..MAGIC.len());
+        Ok(CommandIndex { data })
+    }
+
+    /// The packages providing `command`: repository packages first, then
+    /// AUR packages, then Flatpak applications.
+    pub fn lookup(&self, command: &str) -> Vec<CommandProvider> {
+        let data = &self.data;
+        let mut providers = Vec::new();
+
+        let mut start = self.first_line_from(command.as_bytes());
+        while start < data.len() {
+            let end = data[start..]
+                .iter()
+                .position(|&b| b == b'\n')
+                .map_or(data.len(), |i| start + i);
+            let line = String::from_utf8_lossy(&data[start..end]);
+            match parse_line(&line) {
+                Some(provider) if provider.command == command => providers.push(provider),
+                _ => break,
+            }
+            start = end + 1;
+        }
+
+        providers
+    }
+
+    /// Binary-search the start of the first line whose command is not
+    /// less than `command`.
+    fn first_line_from(&self, command: &[u8]) -> usize {
+        let data = &self.data;
+        // Every line starting before `low` has a smaller command, and
+        // every line starting at or after `high` does not
+        let (mut low, mut high) = (0, data.len());
+        while low < high {
+            let middle = low + (high - low) / 2;
+            let start = data[..middle]
+                .iter()
+                .rposition(|&b| b == b'\n')
+                .map_or(0, |i| i + 1);
+            let end = data[start..]
+                .iter()
+                .position(|&b| b == b'\n')
+                .map_or(data.len(), |i| start + i);
+            let key = data[start..end]
+                .split(|&b| b == b'\t')
+                .next()
+                .unwrap_or_default();
+            if key < command {
+                low = end + 1;
+            } else {
+                high = start;
+            }
+        }
+        low.min(data.len())
+    }
+}
+
+fn parse_line(line: &str) -> Option<CommandProvider> {
+    let mut fields = line.split('\t');
+    let command = fields.next()?.to_string();
+    let source = fields.next()?.parse().ok()?;
+    let name = fields.next()?.to_string();
+    let optional = |field: Option<&str>| field.filter(|f| !f.is_empty()).map(str::to_string);
+    Some(CommandProvider {
+        command,
+        source,
+        name,
+        repo: optional(fields.next()),
+        app_id: optional(fields.next()),
+    })
+}
@@ -0,0 +1,332 @@
+//! User configuration.
+//!
+//! Settings are read from `$XDG_CONFIG_HOME/package-dictionary/config.toml`
+//! and can be overridden by `PD_*` environment variables and then by
+//! command-line flags. Every layer goes through [`Config::set`], so a setting
+//! is spelled the same way everywhere:
+//!
+//! ```toml
+//! backends = ["pacman", "aur", "flatpak"]
+//! format = "text"
+//! color = "auto"
+//! timeout = 5
+//! pager = ["less", "-R", "-F"]
+//! stream = true
+//! index = true
+//!
+//! [timeouts]
+//! aur = 10
+//!
+//! [aur]
+//! search = "rpc"
+//! helper = "paru"
+//! rpc_url = "https://aur.archlinux.org/rpc/v5"
+//! dump = "/path/to/packages-meta-ext-v1.json.gz"
+//!
+//! [colors]
+//! pacman = "blue"
+//! aur = "red"
+//! flatpak = "green"
+//! ```
+
+use std::collections::HashMap;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::aur_rpc::DEFAULT_BASE_URL;
+use crate::backend::AurHelper;
+use crate::package::Source;
+use crate::render::{ColorChoice, OutputFormat, Theme};
+
+/// Environment variables and the settings they override.
+pub const ENV_VARS: &[(&str, &str)] = &[
+    ("PD_BACKENDS", "backends"),
+    ("PD_FORMAT", "format"),
+    ("PD_COLOR", "color"),
+    ("PD_TIMEOUT", "timeout"),
+    ("PD_TIMEOUT_PACMAN", "timeouts.pacman"),
+    ("PD_TIMEOUT_AUR", "timeouts.aur"),
+    ("PD_TIMEOUT_FLATPAK", "timeouts.flatpak"),
+    ("PD_PAGER", "pager"),
+    ("PD_STREAM", "stream"),
+    ("PD_INDEX", "index"),
+    ("PD_AUR_SEARCH", "aur.search"),
+    ("PD_AUR_HELPER", "aur.helper"),
+    ("PD_AUR_RPC_URL", "aur.rpc_url"),
+    ("PD_AUR_DUMP", "aur.dump"),
+    ("PD_COLOR_PACMAN", "colors.pacman"),
+    ("PD_COLOR_AUR", "colors.aur"),
+    ("PD_COLOR_FLATPAK", "colors.flatpak"),
+];
+
+/// How the AUR is searched.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AurSearch {
+    /// Query the AUR RPC directly.
+    #[default]
+    Rpc,
+    /// Run the AUR helper's `-Ss --aur`.
+    Helper,
+}
+
+impl FromStr for AurSearch {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "rpc" => Ok(AurSearch::Rpc),
+            "helper" => Ok(AurSearch::Helper),
+            other => Err(format!(
+                "unknown AUR search mode '{}' (expected rpc or helper)",
+                other
+            )),
+        }
+    }
+}
+
+/// Errors from loading or overriding the configuration.
+#[derive(Debug)]
+pub enum ConfigError {
+    Read(PathBuf, io::Error),
+    Parse(PathBuf, toml::de::Error),
+    /// A setting was given an invalid value.
+    Invalid {
+        key: String,
+        message: String,
+    },
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ConfigError::Read(path, e) => write!(f, "failed to read {}: {}", path.display(), e),
+            ConfigError::Parse(path, e) => write!(f, "invalid config {}: {}", path.display(), e),
+            ConfigError::Invalid { key, message } => write!(f, "invalid {}: {}", key, message),
+        }
+    }
+}
+
+impl std::error::Error for ConfigError {}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct AurConfig {
+    pub search: AurSearch,
+    /// The helper used for `search = "helper"` and for installing. When
+    /// unset, paru is preferred over yay.
+    pub helper: Option<AurHelper>,
+    pub rpc_url: String,
+    /// Downloaded AUR metadata dump used by `pd index update`, see
+    /// [`crate::aur_rpc::METADATA_DUMP_URL`]. Defaults to
+    /// `packages-meta-ext-v1.json.gz` in [`crate::index::cache_dir`].
+    pub dump: Option<PathBuf>,
+}
+
+impl Default for AurConfig {
+    fn default() -> Self {
+        AurConfig {
+            search: AurSearch::default(),
+            helper: None,
+            rpc_url: DEFAULT_BASE_URL.to_string(),
+            dump: None,
+        }
+    }
+}
+
+/// Color names for each source, see [`crate::render::ansi_color`].
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ColorConfig {
+    pub pacman: String,
+    pub aur: String,
+    pub flatpak: String,
+}
+
+impl Default for ColorConfig {
+    fn default() -> Self {
+        ColorConfig {
+            pacman: "blue".to_string(),
+            aur: "red".to_string(),
+            flatpak: "green".to_string(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, Deserialize, Serialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Enabled backends, in display order.
+    pub backends: Vec<Source>,
+    /// Output format used when `--format` is not given.
+    pub format: OutputFormat,
+    /// When to color text output.
+    pub color: ColorChoice,
+    /// Seconds a backend may take, unless overridden in `timeouts`.
+    pub timeout: f64,
+    /// Per-backend timeouts in seconds.
+    pub timeouts: HashMap<Source, f64>,
+    /// Pager command and arguments; empty to print directly.
+    pub pager: Vec<String>,
+    /// Print each source's results as soon as it finishes, into the pager
+    /// on a terminal.
+    pub stream: bool,
+    /// Answer searches from the index built by `pd index update` when it
+    /// exists, instead of running every backend.
+    pub index: bool,
+    pub aur: AurConfig,
+    pub colors: ColorConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            backends: vec![Source::Pacman, Source::Aur, Source::Flatpak],
+            format: OutputFormat::default(),
+            color: ColorChoice::default(),
+            timeout: 5.0,
+            timeouts: HashMap::new(),
+            pager: ["less", "-R", "-F"].map(String::from).to_vec(),
+            stream: true,
+            index: true,
+            aur: AurConfig::default(),
+            colors: ColorConfig::default(),
+        }
+    }
+}
+
+impl Config {
+    /// The config file location: `$XDG_CONFIG_HOME/package-dictionary/config.toml`,
+    /// falling back to `~/.config` when `XDG_CONFIG_HOME` is unset.
+    pub fn default_path() -> Option<PathBuf> {
+        let config_home = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".config")))?;
+        Some(config_home.join("package-dictionary").join("config.toml"))
+    }
+
+    /// Load the config file at `path`, using the defaults when it does not exist.
+    pub fn load(path: &Path) -> Result<Config, ConfigError> {
+        match fs::read_to_string(path) {
+            Ok(text) => {
+                toml::from_str(&text).map_err(|e| ConfigError::Parse(path.to_path_buf(), e))
+            }
+            Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Config::default()),
+            Err(e) => Err(ConfigError::Read(path.to_path_buf(), e)),
+        }
+    }
+
+    /// Apply the [`ENV_VARS`] found in `vars`.
+    pub fn apply_env<I, K, V>(&mut self, vars: I) -> Result<(), ConfigError>
+    where
+        I: IntoIterator<Item = (K, V)>,
+        K: AsRef<str>,
+        V: AsRef<str>,
+    {
+        for (name, value) in vars {
+            if let Some((_, key)) = ENV_VARS.iter().find(|(var, _)| *var == name.as_ref()) {
+                self.set(key, value.as_ref())?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Set one setting from its string form.
+    ///
+    /// Lists are comma-separated, except `pager`, which is split on
+    /// whitespace like a command line.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), ConfigError> {
+        let invalid = |message: String| ConfigError::Invalid {
+            key: key.to_string(),
+            message,
+        };
+
+        match key {
+            "backends" => {
+                self.backends = value
+                    .split(',')
+                    .map(str::trim)
+                    .filter(|name| !name.is_empty())
+                    .map(str::parse)
+                    .collect::<Result<_, _>>()
+                    .map_err(invalid)?;
+            }
+            "format" => self.format = value.parse().map_err(invalid)?,
+            "color" => self.color = value.parse().map_err(invalid)?,
+            "timeout" => self.timeout = parse_seconds(value).map_err(invalid)?,
+            "pager" => self.pager = value.split_whitespace().map(str::to_string).collect(),
+            "stream" => self.stream = parse_bool(value).map_err(invalid)?,
+            "index" => self.index = parse_bool(value).map_err(invalid)?,
+            "aur.search" => self.aur.search = value.parse().map_err(invalid)?,
+            "aur.helper" => self.aur.helper = Some(value.parse().map_err(invalid)?),
+            "aur.rpc_url" => self.aur.rpc_url = value.to_string(),
+            "aur.dump" => self.aur.dump = Some(PathBuf::from(value)),
+            _ => {
+                if let Some(source) = key.strip_prefix("timeouts.") {
+                    let source = source.parse().map_err(invalid)?;
+                    self.timeouts
+                        .insert(source, parse_seconds(value).map_err(invalid)?);
+                } else if let Some(source) = key.strip_prefix("colors.") {
+                    let color = match source.parse().map_err(invalid)? {
+                        Source::Pacman => &mut self.colors.pacman,
+                        Source::Aur => &mut self.colors.aur,
+                        Source::Flatpak => &mut self.colors.flatpak,
+                    };
+                    *color = value.to_string();
+                } else {
+                    return Err(invalid("unknown setting".to_string()));
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The settings in config file syntax, for `pd config`.
+    pub fn to_toml(&self) -> String {
+        toml::to_string(self).expect("every setting has a TOML representation")
+    }
+
+    /// How long `source` may take.
+    pub fn timeout_for(&self, source: Source) -> Duration {
+        let seconds = self.timeouts.get(&source).copied().unwrap_or(self.timeout);
+        Duration::from_secs_f64(seconds.max(0.0))
+    }
+
+    /// The ANSI color theme, failing on unknown color names.
+    pub fn theme(&self) -> Result<Theme, ConfigError> {
+        let color = |key: &str, name: &str| {
+            crate::render::ansi_color(name).map_err(|message| ConfigError::Invalid {
+                key: key.to_string(),
+                message,
+            })
+        };
+
+        Ok(Theme {
+            pacman: color("colors.pacman", &self.colors.pacman)?,
+            aur: color("colors.aur", &self.colors.aur)?,
+            flatpak: color("colors.flatpak", &self.colors.flatpak)?,
+        })
+    }
+}
+
+fn parse_bool(value: &str) -> Result<bool, String> {
+    value
+        .trim()
+        .parse()
+        .map_err(|_| format!("'{}' is not true or false", value))
+}
+
+fn parse_seconds(value: &str) -> Result<f64, String> {
+    match value.trim().parse::<f64>() {
+        Ok(seconds) if seconds.is_finite() && seconds >= 0.0 => Ok(seconds),
+        _ => Err(format!("'{}' is not a number of seconds", value)),
+    }
+}
@@ -0,0 +1,196 @@
+//! File ownership search over pacman's files databases.
+//!
+//! `pacman -Fy` downloads a `/var/lib/pacman/sync/<repo>.files` database per
+//! repository. It is a sync database whose package directories also hold a
+//! `files` entry listing every path in the package, relative to `/`, with
+//! directories ending in `/`. The local database keeps the same `files`
+//! entry for every installed package.
+
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+use crate::localdb::split_entry_name;
+use crate::package::PackageInfo;
+use crate::syncdb::{files_databases, open_decompressed, package_from_desc, parse_desc};
+
+/// Directories merged into `/usr` on Arch, and where their files live.
+const MERGED_DIRS: &[(&str, &str)] = &[
+    ("bin/", "usr/bin/"),
+    ("sbin/", "usr/bin/"),
+    ("usr/sbin/", "usr/bin/"),
+    ("lib/", "usr/lib/"),
+    ("lib64/", "usr/lib/"),
+    ("usr/lib64/", "usr/lib/"),
+];
+
+/// Where commands are installed.
+const BIN_DIR: &str = "usr/bin/";
+
+/// What `pd provides` looks for.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FileQuery {
+    /// An absolute path, stored without its leading `/` like the databases
+    /// store them.
+    Path(String),
+    /// A bare file name such as `libssl.so.3` or a command such as `dig`.
+    Name(String),
+}
+
+impl FileQuery {
+    /// Parse a query: anything with a `/` is a path, anything else a name.
+    ///
+    /// Paths under `/bin`, `/sbin` and `/lib` are mapped to the `/usr`
+    /// directories they are symlinks to.
+    pub fn parse(query: &str) -> FileQuery {
+        let query = query.trim();
+        if !query.contains('/') {
+            return FileQuery::Name(query.to_string());
+        }
+
+        let path = query.trim_start_matches('/');
+        let path = MERGED_DIRS
+            .iter()
+            .find_map(|(dir, merged)| Some(format!("{}{}", merged, path.strip_prefix(dir)?)))
+            .unwrap_or_else(|| path.to_string());
+        FileQuery::Path(path)
+    }
+
+    /// Whether `path`, as stored in a files database, matches the query.
+    ///
+    /// Names match any file with that name, but no directory.
+    pub fn matches(&self, path: &str) -> bool {
+        match self {
+            FileQuery::Path(query) => path == query,
+            FileQuery::Name(name) => {
+                !path.ends_with('/') && path.rsplit('/').next() == Some(name.as_str())
+            }
+        }
+    }
+
+    /// The paths in a `files` entry matching the query, made absolute.
+    pub fn matching_files(&self, files: &str) -> Vec<String> {
+        files
+            .lines()
+            .map(str::trim_end)
+            .filter(|line| !line.starts_with('%') && self.matches(line))
+            .map(|path| format!("/{}", path))
+            .collect()
+    }
+
+    /// Narrow name matches down to commands when there are any, so `dig`
+    /// finds `/usr/bin/dig` rather than every shell completion called `dig`.
+    /// Packages left without a matching file are dropped.
+    pub fn prefer_commands(&self, packages: &mut Vec<PackageInfo>) {
+        let FileQuery::Name(name) = self else {
+            return;
+        };
+        let command = format!("/{}{}", BIN_DIR, name);
+        let has_command = packages
+            .iter()
+            .any(|package| package.matched_files.contains(&command));
+        if has_command {
+            for package in packages.iter_mut() {
+                package.matched_files.retain(|file| *file == command);
+            }
+            packages.retain(|package| !package.matched_files.is_empty());
+        }
+    }
+}
+
+/// Find the packages in every files database in `dir` owning a file that
+/// matches `query`, with the matching files in `matched_files`.
+pub fn search_files_dir(dir: &Path, query: &FileQuery) -> io::Result<Vec<PackageInfo>> {
+    let mut packages = Vec::new();
+    for (repo, path) in files_databases(dir)? {
+        packages.extend(search_files_db(&path, &repo, query)?);
+    }
+    Ok(packages)
+}
+
+/// Find the packages in the files database at `path` owning a file that
+/// matches `query`, tagging them with `repo`.
+pub fn search_files_db(path: &Path, repo: &str, query: &FileQuery) -> io::Result<Vec<PackageInfo>> {
+    let mut archive = tar::Archive::new(open_decompressed(path)?);
+    let mut packages = Vec::new();
+    // The `desc` and `files` entries of a package directory come in either
+    // order, so keep the latest of each until both are known
+    let mut desc: Option<(PathBuf, String)> = None;
+    let mut matched: Option<(PathBuf, Vec<String>)> = None;
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        let (Some(dir), Some(file)) = (path.parent(), path.file_name()) else {
+            continue;
+        };
+        let dir = dir.to_path_buf();
+
+        if file == "desc" {
+            let mut text = String::new();
+            entry.read_to_string(&mut text)?;
+            desc = Some((dir, text));
+        } else if file == "files" {
+            let mut text = String::new();
+            entry.read_to_string(&mut text)?;
+            let files = query.matching_files(&text);
+            if !files.is_empty() {
+                matched = Some((dir, files));
+            }
+        } else {
+            continue;
+        }
+
+        let complete = matches!(
+            (&desc, &matched),
+            (Some((desc_dir, _)), Some((files_dir, _))) if desc_dir == files_dir
+        );
+        if complete {
+            let (Some((_, text)), Some((_, matched_files))) = (&desc, matched.take()) else {
+                continue;
+            };
+            if let Some(package) = package_from_desc(&parse_desc(text), repo) {
+                packages.push(PackageInfo {
+                    matched_files,
+                    ..package
+                });
+            }
+        }
+    }
+
+    Ok(packages)
+}
+
+/// Every command in the files database at `path`, as `(command, package)`
+/// pairs, one per file directly in `/usr/bin`.
+pub fn read_commands(path: &Path) -> io::Result<Vec<(String, String)>> {
+    let mut archive = tar::Archive::new(open_decompressed(path)?);
+    let mut commands = Vec::new();
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let path = entry.path()?.into_owned();
+        if path.file_name().is_none_or(|file| file != "files") {
+            continue;
+        }
+        let Some((name, _)) = path
+            .parent()
+            .and_then(Path::to_str)
+            .map(|dir| dir.trim_start_matches("./"))
+            .and_then(split_entry_name)
+        else {
+            continue;
+        };
+        let name = name.to_string();
+
+        let mut text = String::new();
+        entry.read_to_string(&mut text)?;
+        for line in text.lines() {
+            let command = line.trim_end().strip_prefix(BIN_DIR);
+            if let Some(command) = command.filter(|c| !c.is_empty() && !c.contains('/')) {
+                commands.push((command.to_string(), name.clone()));
+            }
+        }
+    }
+
+    Ok(commands)
+}
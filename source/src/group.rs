@@ -0,0 +1,222 @@
+//! Grouping of the same application found in several sources.
+//!
+//! A search for `gimp` finds `gimp` in the repositories, `gimp-git` in the
+//! AUR and `GIMP (org.gimp.GIMP)` on Flathub. [`group_packages`] shows them
+//! as one app with three flavors, linking two packages when any of these
+//! match:
+//!
+//! 1. their names, lowercased and without AUR suffixes such as `-git`. A
+//!    Flatpak is also known by its display name, with dashes for spaces,
+//!    and its application ID. The last meaningful part of the ID links it
+//!    to a repository or AUR package only, or `org.kde.Platform` and
+//!    `org.gnome.Platform` would be one app;
+//! 2. their upstream URLs, between the first package with that URL in
+//!    each source only, since the packages split from one repository
+//!    package all share its URL;
+//! 3. a name an AUR package both provides and conflicts with, which makes
+//!    it a drop-in replacement for that package, and the other's name.
+
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use crate::package::{PackageInfo, Source};
+
+/// Suffixes of AUR packages building another package.
+pub const AUR_SUFFIXES: &[&str] = &["-git", "-bin", "-appimage", "-nightly", "-beta"];
+
+/// Last parts of application IDs that say nothing about the application,
+/// as in `org.telegram.desktop`.
+const GENERIC_ID_PARTS: &[&str] = &["desktop", "app", "client", "gui"];
+
+/// One application and the packages providing it.
+#[derive(Clone, Debug, Serialize)]
+pub struct AppGroup {
+    /// The display name of the first flavor, without an AUR suffix.
+    pub name: String,
+    /// The first non-empty description among the flavors.
+    pub description: String,
+    /// The packages providing the application, repository packages first,
+    /// then the AUR, then Flatpak.
+    pub flavors: Vec<PackageInfo>,
+}
+
+/// `name` without an AUR suffix such as `-git`.
+pub fn base_name(name: &str) -> &str {
+    AUR_SUFFIXES
+        .iter()
+        .find_map(|suffix| name.strip_suffix(suffix))
+        .unwrap_or(name)
+}
+
+/// Group `packages` by application.
+///
+/// Groups are ordered by their first package in `packages`, and flavors
+/// from one source keep their order in `packages`.
+pub fn group_packages(packages: Vec<PackageInfo>) -> Vec<AppGroup> {
+    let mut sets = DisjointSets::new(packages.len());
+    let mut by_name: HashMap<String, usize> = HashMap::new();
+    let mut native_by_name: HashMap<String, usize> = HashMap::new();
+    let mut flatpaks_by_id_part: HashMap<String, Vec<usize>> = HashMap::new();
+    let mut by_url: HashMap<String, Vec<usize>> = HashMap::new();
+
+    for (i, package) in packages.iter().enumerate() {
+        for key in name_keys(package) {
+            let first = *by_name.entry(key.clone()).or_insert(i);
+            sets.union(first, i);
+            if package.source != Source::Flatpak {
+                native_by_name.entry(key.clone()).or_insert(i);
+                for &flatpak in flatpaks_by_id_part.get(&key).into_iter().flatten() {
+                    sets.union(flatpak, i);
+                }
+            }
+        }
+        if let Some(part) = id_part_key(package) {
+            if let Some(&native) = native_by_name.get(&part) {
+                sets.union(native, i);
+            }
+            flatpaks_by_id_part.entry(part).or_default().push(i);
+        }
+        if let Some(url) = package.url.as_deref().and_then(normalize_url) {
+            let linked = by_url.entry(url).or_default();
+            if linked
+                .iter()
+                .all(|&other| packages[other].source != package.source)
+            {
+                for &other in linked.iter() {
+                    sets.union(other, i);
+                }
+                linked.push(i);
+            }
+        }
+    }
+
+    // Keyed by the root of each set, in order of first appearance
+    let mut positions: HashMap<usize, usize> = HashMap::new();
+    let mut flavors: Vec<Vec<PackageInfo>> = Vec::new();
+    for (i, package) in packages.into_iter().enumerate() {
+        let root = sets.find(i);
+        let position = *positions.entry(root).or_insert_with(|| {
+            flavors.push(Vec::new());
+            flavors.len() - 1
+        });
+        flavors[position].push(package);
+    }
+
+    flavors
+        .into_iter()
+        .map(|mut flavors| {
+            flavors.sort_by_key(|package| package.source);
+            AppGroup {
+                name: base_name(flavors[0].display_name()).to_string(),
+                description: flavors
+                    .iter()
+                    .map(|package| package.description.as_str())
+                    .find(|description| !description.is_empty())
+                    .unwrap_or_default()
+                    .to_string(),
+                flavors,
+            }
+        })
+        .collect()
+}
+
+/// The normalized names `package` is known by.
+fn name_keys(package: &PackageInfo) -> Vec<String> {
+    let normalize = |name: &str| base_name(&name.to_lowercase()).to_string();
+
+    let mut keys = vec![normalize(&package.display_name().replace(' ', "-"))];
+    match package.source {
+        Source::Pacman => {}
+        Source::Aur => {
+            let provides: Vec<&str> = package
+                .provides
+                .iter()
+                .map(|p| dependency_name(p))
+                .collect();
+            keys.extend(
+                package
+                    .conflicts
+                    .iter()
+                    .map(|c| dependency_name(c))
+                    .filter(|name| provides.contains(name))
+                    .map(normalize),
+            );
+        }
+        Source::Flatpak => {
+            if let Some(app_id) = package.app_id.as_deref() {
+                keys.push(app_id.to_lowercase());
+            }
+        }
+    }
+
+    keys.retain(|key| !key.is_empty());
+    keys.sort();
+    keys.dedup();
+    keys
+}
+
+/// The last meaningful part of a Flatpak's application ID, normalized like
+/// the names of [`name_keys`], as `code` in `com.visualstudio.code`.
+fn id_part_key(package: &PackageInfo) -> Option<String> {
+    if package.source != Source::Flatpak {
+        return None;
+    }
+    let part = package
+        .app_id
+        .as_deref()?
+        .rsplit('.')
+        .find(|part| !GENERIC_ID_PARTS.contains(&part.to_lowercase().as_str()))?;
+    let key = base_name(&part.to_lowercase()).to_string();
+    (!key.is_empty()).then_some(key)
+}
+
+/// The package name in a dependency such as `gimp=2.10`.
+fn dependency_name(dependency: &str) -> &str {
+    dependency
+        .split(['=', '<', '>'])
+        .next()
+        .unwrap_or(dependency)
+}
+
+/// An upstream URL without its scheme, `www.` and trailing slash, so
+/// `https://www.gimp.org/` and `http://gimp.org` match.
+fn normalize_url(url: &str) -> Option<String> {
+    let url = url.trim().to_lowercase();
+    let url = url.split_once("://").map_or(url.as_str(), |(_, rest)| rest);
+    let url = url.strip_prefix("www.").unwrap_or(url);
+    let url = url.trim_end_matches('/');
+    let url = url.strip_suffix(".git").unwrap_or(url);
+    (!url.is_empty()).then(|| url.to_string())
+}
+
+/// Union-find over package positions.
+struct DisjointSets {
+    parents: Vec<usize>,
+}
+
+impl DisjointSets {
+    fn new(len: usize) -> Self {
+        DisjointSets {
+            parents: (0..len).collect(),
+        }
+    }
+
+    fn find(&mut self, mut i: usize) -> usize {
+        while self.parents[i] != i {
+            self.parents[i] = self.parents[self.parents[i]];
+            i = self.parents[i];
+        }
+        i
+    }
+
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        // The earlier package stays the root
+        if a < b {
+            self.parents[b] = a;
+        } else {
+            self.parents[a] = b;
+        }
+    }
+}
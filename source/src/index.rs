@@ -0,0 +1,504 @@
+//! The package dictionary: an on-disk full-text index of every package.
+//!
+//! `pd index update` builds it from the pacman sync databases, a downloaded
+//! AUR metadata dump and Flatpak's appstream data. A search only reads the
+//! term table, the postings of the query terms and the matching packages, so
+//! it answers in milliseconds without running pacman, an AUR helper or
+//! flatpak. Results are ranked with BM25, counting name matches three times
+//! and keyword, provides and group matches twice.
+//!
+//! The file starts with [`MAGIC`] and the length of a postcard-encoded header
+//! holding the document and term tables. The postings follow, six bytes per
+//! entry (little-endian document number and weighted term frequency), and
+//! then every package as JSON.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::{self, File};
+use std::io::{self, Write};
+use std::os::unix::fs::FileExt;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use serde::{Deserialize, Serialize};
+
+use crate::localdb::{mark_installed, DEFAULT_LOCAL_DIR};
+use crate::package::{PackageInfo, Source};
+use crate::search::SourceResults;
+
+/// Identifies the index format.
+pub const MAGIC: &[u8; 8] = b"PDINDEX1";
+
+/// BM25 term frequency saturation.
+const K1: f64 = 1.2;
+/// BM25 document length normalization.
+const B: f64 = 0.75;
+
+const NAME_WEIGHT: u16 = 3;
+const KEYWORD_WEIGHT: u16 = 2;
+const DESCRIPTION_WEIGHT: u16 = 1;
+
+/// Query words this long also match longer terms they are a prefix of.
+const MIN_PREFIX_LEN: usize = 3;
+/// Share of the score a prefix match earns compared to an exact match.
+const PREFIX_WEIGHT: f64 = 0.5;
+/// Added when the whole query is the package name.
+const EXACT_NAME_BONUS: f64 = 10.0;
+
+const POSTING_LEN: usize = 6;
+
+#[derive(Serialize, Deserialize)]
+struct Header {
+    /// Unix time the index was built.
+    built_at: i64,
+    average_length: f64,
+    postings_len: u64,
+    docs: Vec<DocEntry>,
+    /// Sorted by term.
+    terms: Vec<TermEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct DocEntry {
+    source: Source,
+    name: String,
+    /// Position of the JSON package in the documents section.
+    offset: u64,
+    len: u32,
+    /// Weighted number of terms, for BM25 length normalization.
+    length: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct TermEntry {
+    term: String,
+    /// Position of the first posting in the postings section.
+    offset: u64,
+    count: u32,
+}
+
+/// The directory the index and the AUR dump are kept in:
+/// `$XDG_CACHE_HOME/package-dictionary`, falling back to `~/.cache`.
+pub fn cache_dir() -> Option<PathBuf> {
+    let cache_home = std::env::var_os("XDG_CACHE_HOME")
+        .filter(|dir| !dir.is_empty())
+        .map(PathBuf::from)
+        .or_else(|| std::env::var_os("HOME").map(|home| Path::new(&home).join(".cache")))?;
+    Some(cache_home.join("package-dictionary"))
+}
+
+/// Split text into lowercase alphanumeric words.
+pub fn tokenize(text: &str) -> Vec<String> {
+    text.split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_lowercase)
+        .collect()
+}
+
+/// Collects packages and writes them out as an index.
+#[derive(Default)]
+pub struct IndexBuilder {
+    packages: Vec<PackageInfo>,
+}
+
+impl IndexBuilder {
+    pub fn new() -> Self {
+        IndexBuilder::default()
+    }
+
+    pub fn add(&mut self, packages: impl IntoIterator<Item = PackageInfo>) {
+        self.packages.extend(packages);
+    }
+
+    pub fn len(&self) -> usize {
+        self.packages.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.packages.is_empty()
+    }
+
+    /// Write the index to `path`, replacing any previous one only once the
+    /// new one is complete.
+    pub fn write(self, path: &Path) -> io::Result<()> {
+        let mut postings: BTreeMap<String, Vec<(u32, u16)>> = BTreeMap::new();
+        let mut docs = Vec::with_capacity(self.packages.len());
+        let mut documents = Vec::new();
+        let mut total_length = 0u64;
+
+        for (number, mut package) in self.packages.into_iter().enumerate() {
+            let frequencies = term_frequencies(&package);
+            let length: u32 = frequencies.values().map(|&tf| u32::from(tf)).sum();
+            total_length += u64::from(length);
+            for (term, tf) in frequencies {
+                postings.entry(term).or_default().push((number as u32, tf));
+            }
+
+            // Installed state and scores are looked up at search time
+            package.installed_version = None;
+            package.score = None;
+            let offset = documents.len() as u64;
+            serde_json::to_writer(&mut documents, &package)?;
+            docs.push(DocEntry {
+                source: package.source,
+                name: package.name,
+                offset,
+                len: (documents.len() as u64 - offset) as u32,
+                length,
+            });
+        }
+
+        let mut postings_bytes = Vec::new();
+        let terms = postings
+            .into_iter()
+            .map(|(term, entries)| {
+                let offset = postings_bytes.len() as u64;
+                for (doc, tf) in &entries {
+                    postings_bytes.extend_from_slice(&doc.to_le_bytes());
+                    postings_bytes.extend_from_slice(&tf.to_le_bytes());
+                }
+                TermEntry {
+                    term,
+                    offset,
+                    count: entries.len() as u32,
+                }
+            })
+            .collect();
+
+        let header = Header {
+            built_at: SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map_or(0, |elapsed| elapsed.as_secs() as i64),
+            average_length: total_length as f64 / docs.len().max(1) as f64,
+            postings_len: postings_bytes.len() as u64,
+            docs,
+            terms,
+        };
+        let header_bytes = postcard::to_stdvec(&header).map_err(io::Error::other)?;
+
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        let partial = path.with_extension("partial");
+        let mut file = io::BufWriter::new(File::create(&partial)?);
+        file.write_all(MAGIC)?;
+        file.write_all(&(header_bytes.len() as u64).to_le_bytes())?;
+        file.write_all(&header_bytes)?;
+        file.write_all(&postings_bytes)?;
+        file.write_all(&documents)?;
+        file.into_inner().map_err(|e| e.into_error())?.sync_all()?;
+        fs::rename(&partial, path)
+    }
+}
+
+/// Weighted term frequencies of every indexed field of `package`.
+fn term_frequencies(package: &PackageInfo) -> HashMap<String, u16> {
+    let mut frequencies: HashMap<String, u16> = HashMap::new();
+    let mut add = |text: &str, weight: u16| {
+        for term in tokenize(text) {
+            let tf = frequencies.entry(term).or_default();
+            *tf = tf.saturating_add(weight);
+        }
+    };
+
+    add(&package.name, NAME_WEIGHT);
+    add(&package.description, DESCRIPTION_WEIGHT);
+    for keyword in package.keywords.iter().chain(&package.groups) {
+        add(keyword, KEYWORD_WEIGHT);
+    }
+    for provided in &package.provides {
+        // Leave out version constraints such as `libfoo.so=1-64`
+        let name = provided.split(['=', '<', '>']).next().unwrap_or(provided);
+        add(name, KEYWORD_WEIGHT);
+    }
+
+    frequencies
+}
+
+/// An index opened for searching.
+pub struct Index {
+    file: File,
+    header: Header,
+    postings_start: u64,
+    docs_start: u64,
+    local_dir: PathBuf,
+}
+
+impl Index {
+    /// Where `pd index update` writes the index.
+    pub fn default_path() -> Option<PathBuf> {
+        cache_dir().map(|dir| dir.join("index"))
+    }
+
+    /// Open the index at `path`, reading only its header.
+    pub fn open(path: &Path) -> io::Result<Index> {
+        let file = File::open(path)?;
+        let invalid = |message: &str| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", path.display(), message),
+            )
+        };
+
+        let mut prefix = [0; 16];
+        file.read_exact_at(&mut prefix, 0)?;
+        if prefix[..8] != MAGIC[..] {
+            return Err(invalid("not a package index, run `pd index update`"));
+        }
+        let header_len = u64::from_le_bytes(prefix[8..].try_into().unwrap());
+
+        // Check the lengths against the file before allocating for them
+        let file_len = file.metadata()?.len();
+        let truncated = || invalid("truncated index, run `pd index update`");
+        let postings_start = (prefix.len() as u64)
+            .checked_add(header_len)
+            .filter(|&end| end <= file_len)
+            .ok_or_else(truncated)?;
+
+        let mut header_bytes = vec![0; header_len as usize];
+        file.read_exact_at(&mut header_bytes, prefix.len() as u64)?;
+        let header: Header = postcard::from_bytes(&header_bytes)
+            .map_err(|_| invalid("corrupt index header, run `pd index update`"))?;
+
+        let docs_start = postings_start
+            .checked_add(header.postings_len)
+            .filter(|&start| start <= file_len)
+            .ok_or_else(truncated)?;
+        let docs_end = header
+            .docs
+            .iter()
+            .map(|doc| doc.offset.saturating_add(doc.len.into()))
+            .max()
+            .unwrap_or(0);
+        if docs_end > file_len - docs_start {
+            return Err(truncated());
+        }
+
+        // Postings are in term order; `score_word` relies on each term's
+        // postings following the previous term's within the section
+        let mut postings_end = 0;
+        for entry in &header.terms {
+            let end = u64::from(entry.count)
+                .checked_mul(POSTING_LEN as u64)
+                .and_then(|len| entry.offset.checked_add(len))
+                .filter(|&end| entry.offset >= postings_end && end <= header.postings_len)
+                .ok_or_else(|| invalid("corrupt index postings, run `pd index update`"))?;
+            postings_end = end;
+        }
+
+        Ok(Index {
+            file,
+            header,
+            postings_start,
+            docs_start,
+            local_dir: PathBuf::from(DEFAULT_LOCAL_DIR),
+        })
+    }
+
+    /// Use another pacman local database to mark installed packages.
+    pub fn with_local_dir(mut self, local_dir: impl Into<PathBuf>) -> Self {
+        self.local_dir = local_dir.into();
+        self
+    }
+
+    /// Unix time the index was built.
+    pub fn built_at(&self) -> i64 {
+        self.header.built_at
+    }
+
+    /// The number of indexed packages from `source`.
+    pub fn count(&self, source: Source) -> usize {
+        self.header
+            .docs
+            .iter()
+            .filter(|doc| doc.source == source)
+            .count()
+    }
+
+    /// The name every indexed package is installed by: its package name,
+    /// or its application ID for Flatpaks.
+    pub fn names(&self) -> impl Iterator<Item = &str> {
+        self.header.docs.iter().map(|doc| match doc.source {
+            // Flatpak names carry the application ID: `Firefox (org.mozilla.firefox)`
+            Source::Flatpak => doc
+                .name
+                .rsplit_once(" (")
+                .and_then(|(_, app_id)| app_id.strip_suffix(')'))
+                .unwrap_or(&doc.name),
+            Source::Pacman | Source::Aur => &doc.name,
+        })
+    }
+
+    /// Find the packages from `sources` matching every word of `term`, with
+    /// one result list per source in the given order.
+    ///
+    /// Every result has its BM25 `score` set and the lists are sorted best
+    /// match first.
+    pub fn search(&self, term: &str, sources: &[Source]) -> io::Result<Vec<SourceResults>> {
+        let mut words = tokenize(term);
+        words.sort();
+        words.dedup();
+
+        let mut scores: Option<HashMap<u32, f64>> = None;
+        for word in &words {
+            let word_scores = self.score_word(word, sources)?;
+            // Every word has to match
+            scores = Some(match scores {
+                None => word_scores,
+                Some(scores) => scores
+                    .into_iter()
+                    .filter_map(|(doc, score)| word_scores.get(&doc).map(|s| (doc, score + s)))
+                    .collect(),
+            });
+        }
+
+        let query = term.trim().to_lowercase();
+        let mut matches: Vec<(u32, f64)> = scores.unwrap_or_default().into_iter().collect();
+        for (doc, score) in &mut matches {
+            let name = &self.doc_entry(*doc)?.name;
+            // Flatpak names carry the application ID: `Firefox (org.mozilla.firefox)`
+            let name = name.split(" (").next().unwrap_or(name);
+            if name.to_lowercase() == query {
+                *score += EXACT_NAME_BONUS;
+            }
+        }
+        matches.sort_by(|a, b| b.1.total_cmp(&a.1).then(a.0.cmp(&b.0)));
+
+        let mut results: Vec<SourceResults> = sources
+            .iter()
+            .map(|&source| SourceResults {
+                name: source.label().to_string(),
+                source,
+                packages: Vec::new(),
+                error: None,
+            })
+            .collect();
+        for (doc, score) in matches {
+            let mut package = self.read_doc(doc)?;
+            package.score = Some(score);
+            if let Some(r) = results.iter_mut().find(|r| r.source == package.source) {
+                r.packages.push(package);
+            }
+        }
+
+        for r in &mut results {
+            if r.source != Source::Flatpak {
+                mark_installed(&mut r.packages, &self.local_dir);
+            }
+        }
+        Ok(results)
+    }
+
+    /// Find the packages from `sources` called `name`, see
+    /// [`PackageInfo::is_named`], with one result list per source in the
+    /// given order.
+    pub fn lookup(&self, name: &str, sources: &[Source]) -> io::Result<Vec<SourceResults>> {
+        let lower = name.to_lowercase();
+        let mut results: Vec<SourceResults> = sources
+            .iter()
+            .map(|&source| SourceResults {
+                name: source.label().to_string(),
+                source,
+                packages: Vec::new(),
+                error: None,
+            })
+            .collect();
+
+        for (doc, entry) in self.header.docs.iter().enumerate() {
+            // Only Flatpaks can be named by something other than their
+            // name, and their names contain it
+            let candidate = match entry.source {
+                Source::Flatpak => entry.name.to_lowercase().contains(&lower),
+                Source::Pacman | Source::Aur => entry.name == name,
+            };
+            let Some(r) = results.iter_mut().find(|r| r.source == entry.source) else {
+                continue;
+            };
+            if candidate {
+                let package = self.read_doc(doc as u32)?;
+                if package.is_named(name) {
+                    r.packages.push(package);
+                }
+            }
+        }
+
+        for r in &mut results {
+            if r.source != Source::Flatpak {
+                mark_installed(&mut r.packages, &self.local_dir);
+            }
+        }
+        Ok(results)
+    }
+
+    /// BM25 scores of the documents from `sources` containing `word`, or a
+    /// term it is a prefix of.
+    fn score_word(&self, word: &str, sources: &[Source]) -> io::Result<HashMap<u32, f64>> {
+        let terms = &self.header.terms;
+        let first = terms.partition_point(|entry| entry.term.as_str() < word);
+        let matching: Vec<&TermEntry> = terms[first..]
+            .iter()
+            .take_while(|entry| entry.term.starts_with(word))
+            .filter(|entry| entry.term == word || word.len() >= MIN_PREFIX_LEN)
+            .collect();
+        let (Some(first), Some(last)) = (matching.first(), matching.last()) else {
+            return Ok(HashMap::new());
+        };
+
+        // Postings are stored in term order, so the matching terms' postings
+        // are one contiguous range
+        let start = first.offset;
+        let end = last.offset + u64::from(last.count) * POSTING_LEN as u64;
+        let mut postings = vec![0; (end - start) as usize];
+        self.file
+            .read_exact_at(&mut postings, self.postings_start + start)?;
+
+        let doc_count = self.header.docs.len() as f64;
+        let mut scores: HashMap<u32, f64> = HashMap::new();
+        for entry in matching {
+            let weight = if entry.term == word {
+                1.0
+            } else {
+                PREFIX_WEIGHT
+            };
+            let df = f64::from(entry.count);
+            let idf = ((doc_count - df + 0.5) / (df + 0.5) + 1.0).ln();
+
+            let begin = (entry.offset - start) as usize;
+            let bytes = &postings[begin..begin + entry.count as usize * POSTING_LEN];
+            for posting in bytes.chunks_exact(POSTING_LEN) {
+                let doc = u32::from_le_bytes(posting[..4].try_into().unwrap());
+                let tf = f64::from(u16::from_le_bytes(posting[4..].try_into().unwrap()));
+                let info = self.doc_entry(doc)?;
+                if !sources.contains(&info.source) {
+                    continue;
+                }
+
+                let length = f64::from(info.length) / self.header.average_length;
+                let score = weight * idf * tf * (K1 + 1.0) / (tf + K1 * (1.0 - B + B * length));
+                // A word counts once, through its best matching term
+                let best = scores.entry(doc).or_default();
+                *best = best.max(score);
+            }
+        }
+
+        Ok(scores)
+    }
+
+    /// The entry of a document named in the postings, which a corrupt
+    /// index may have out of range.
+    fn doc_entry(&self, doc: u32) -> io::Result<&DocEntry> {
+        self.header.docs.get(doc as usize).ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                "corrupt index postings, run `pd index update`",
+            )
+        })
+    }
+
+    fn read_doc(&self, doc: u32) -> io::Result<PackageInfo> {
+        let entry = self.doc_entry(doc)?;
+        let mut json = vec![0; entry.len as usize];
+        self.file
+            .read_exact_at(&mut json, self.docs_start + entry.offset)?;
+        Ok(serde_json::from_slice(&json)?)
+    }
+}
@@ -0,0 +1,219 @@
+//! Installing packages through the tool that owns their source.
+
+use std::fmt;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus};
+
+use crate::backend::AurHelper;
+use crate::index::cache_dir;
+use crate::package::{PackageInfo, Source};
+
+/// What `pd install` was asked to install.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum InstallTarget {
+    /// The package numbered this in the last search's text output.
+    Number(usize),
+    /// A package name or Flatpak application ID.
+    Name(String),
+}
+
+impl InstallTarget {
+    /// Parse an argument: a positive number refers to the last search,
+    /// anything else is a name.
+    pub fn parse(arg: &str) -> InstallTarget {
+        match arg.parse() {
+            Ok(number) if number > 0 => InstallTarget::Number(number),
+            _ => InstallTarget::Name(arg.to_string()),
+        }
+    }
+}
+
+/// One external command that installs a batch of packages from one source.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InstallCommand {
+    pub source: Source,
+    pub program: String,
+    pub args: Vec<String>,
+}
+
+impl InstallCommand {
+    /// Run the command with the terminal attached, so the tool can prompt.
+    pub fn run(&self) -> io::Result<ExitStatus> {
+        Command::new(&self.program).args(&self.args).status()
+    }
+}
+
+impl fmt::Display for InstallCommand {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.program)?;
+        for arg in &self.args {
+            write!(f, " {}", arg)?;
+        }
+        Ok(())
+    }
+}
+
+/// The name a package is installed by: the application ID for Flatpaks and
+/// the package name otherwise.
+pub fn install_name(package: &PackageInfo) -> &str {
+    match (&package.source, &package.app_id) {
+        (Source::Flatpak, Some(app_id)) => app_id,
+        _ => &package.name,
+    }
+}
+
+/// Where a package comes from, as `repo/name`: the repository or Flatpak
+/// remote when known, else the source id, and the [`install_name`].
+pub fn install_origin(package: &PackageInfo) -> String {
+    let repo = package.repo.as_deref().unwrap_or(package.source.id());
+    format!("{}/{}", repo, install_name(package))
+}
+
+/// Build the commands that install `packages`, one per source in
+/// pacman, AUR, Flatpak order.
+///
+/// Repository packages go through `sudo pacman -S` as `repo/name`, so the
+/// package comes from the repository it was found in. AUR packages go
+/// through `aur_helper` (which elevates on its own) and Flatpaks through
+/// `flatpak install`, once per installation and remote since it takes only
+/// one of each, so the ref the user picked is installed without flatpak
+/// asking again.
+pub fn install_commands(packages: &[PackageInfo], aur_helper: AurHelper) -> Vec<InstallCommand> {
+    let names = |source: Source| -> Vec<String> {
+        packages
+            .iter()
+            .filter(|p| p.source == source)
+            .map(|p| install_name(p).to_string())
+            .collect()
+    };
+
+    let mut commands = Vec::new();
+
+    let pacman: Vec<String> = packages
+        .iter()
+        .filter(|p| p.source == Source::Pacman)
+        .map(pacman_target)
+        .collect();
+    if !pacman.is_empty() {
+        commands.push(InstallCommand {
+            source: Source::Pacman,
+            program: "sudo".to_string(),
+            args: ["pacman", "-S"]
+                .map(String::from)
+                .into_iter()
+                .chain(pacman)
+                .collect(),
+        });
+    }
+
+    let aur = names(Source::Aur);
+    if !aur.is_empty() {
+        commands.push(InstallCommand {
+            source: Source::Aur,
+            program: aur_helper.command().to_string(),
+            args: ["-S", "--aur"]
+                .map(String::from)
+                .into_iter()
+                .chain(aur)
+                .collect(),
+        });
+    }
+
+    // Installations and remotes in order of their first Flatpak
+    type Batch<'a> = (Option<String>, Option<&'a str>, Vec<String>);
+    let mut batches: Vec<Batch> = Vec::new();
+    for package in packages.iter().filter(|p| p.source == Source::Flatpak) {
+        let installation = installation_flag(package);
+        let remote = package.repo.as_deref();
+        let position = match batches
+            .iter()
+            .position(|(i, r, _)| *i == installation && *r == remote)
+        {
+            Some(position) => position,
+            None => {
+                batches.push((installation, remote, Vec::new()));
+                batches.len() - 1
+            }
+        };
+        batches[position].2.push(flatpak_ref(package));
+    }
+    for (installation, remote, refs) in batches {
+        commands.push(InstallCommand {
+            source: Source::Flatpak,
+            program: "flatpak".to_string(),
+            args: std::iter::once("install".to_string())
+                .chain(installation)
+                .chain(remote.map(String::from))
+                .chain(refs)
+                .collect(),
+        });
+    }
+
+    commands
+}
+
+/// What `pacman -S` takes for `package`: `repo/name` when the sync
+/// repository is known, so pacman doesn't pick the first repository that
+/// has the name.
+fn pacman_target(package: &PackageInfo) -> String {
+    match package.repo.as_deref() {
+        // Installed packages only know the local database
+        Some(repo) if repo != "local" => format!("{}/{}", repo, package.name),
+        _ => package.name.clone(),
+    }
+}
+
+/// The `flatpak install` option selecting the installation `package` is
+/// in, if it is installed: `--system`, `--user` or `--installation=NAME`.
+fn installation_flag(package: &PackageInfo) -> Option<String> {
+    let flag = match package.installations.first()?.as_str() {
+        "system" => "--system".to_string(),
+        "user" => "--user".to_string(),
+        name => format!("--installation={}", name),
+    };
+    Some(flag)
+}
+
+/// The ref `flatpak install` takes for `package`: the application ID, with
+/// the branch when known, as in `org.gimp.GIMP//stable`.
+fn flatpak_ref(package: &PackageInfo) -> String {
+    match &package.branch {
+        Some(branch) => format!("{}//{}", install_name(package), branch),
+        None => install_name(package).to_string(),
+    }
+}
+
+/// Run `commands` in order, stopping at the first one that fails.
+pub fn run_all(commands: &[InstallCommand]) -> io::Result<()> {
+    for command in commands {
+        let status = command.run()?;
+        if !status.success() {
+            return Err(io::Error::other(format!(
+                "'{}' failed: {}",
+                command, status
+            )));
+        }
+    }
+    Ok(())
+}
+
+/// Where the numbered results of the last text search are kept, for
+/// `pd install <number>`.
+pub fn last_search_path() -> Option<PathBuf> {
+    cache_dir().map(|dir| dir.join("last-search.json"))
+}
+
+/// Remember `packages`, in the order they were numbered.
+pub fn save_last_search(path: &Path, packages: &[PackageInfo]) -> io::Result<()> {
+    if let Some(dir) = path.parent() {
+        fs::create_dir_all(dir)?;
+    }
+    fs::write(path, serde_json::to_vec(packages)?)
+}
+
+/// The packages saved by [`save_last_search`].
+pub fn load_last_search(path: &Path) -> io::Result<Vec<PackageInfo>> {
+    Ok(serde_json::from_slice(&fs::read(path)?)?)
+}
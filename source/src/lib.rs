@@ -0,0 +1,31 @@
+//! Search pacman, the AUR and Flatpak from one place.
+//!
+//! Every package source is a [`Backend`](backend::Backend). The `pd` binary
+//! builds the configured backends, runs them concurrently through
+//! [`search::search_stream`] and hands each source's results to [`render`] as
+//! soon as they arrive.
+
+pub mod appstream;
+pub mod aur_rpc;
+pub mod backend;
+pub mod commands;
+pub mod config;
+pub mod date;
+pub mod files;
+pub mod group;
+pub mod index;
+pub mod install;
+pub mod localdb;
+pub mod package;
+pub mod parse;
+pub mod progress;
+pub mod rank;
+pub mod render;
+pub mod sandbox;
+pub mod search;
+pub mod syncdb;
+pub mod tui;
+pub mod vercmp;
+
+pub use backend::Backend;
+pub use package::{PackageInfo, Source};
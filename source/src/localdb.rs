@@ -0,0 +1,135 @@
+//! Reader for pacman's local database of installed packages.
+//!
+//! Every installed package has a `<name>-<pkgver>-<pkgrel>` directory under
+//! `/var/lib/pacman/local`, holding a `desc` file in the same format as
+//! the sync databases' plus the install date and reason.
+
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::files::FileQuery;
+use crate::package::{InstallReason, PackageInfo};
+use crate::syncdb::{desc_number, package_from_desc, parse_desc};
+
+/// Where pacman keeps its local database.
+pub const DEFAULT_LOCAL_DIR: &str = "/var/lib/pacman/local";
+
+/// Map every installed package name to its installed version.
+///
+/// Only the directory names are read, which keeps this cheap enough to run
+/// for every search.
+pub fn installed_versions(local_dir: &Path) -> io::Result<HashMap<String, String>> {
+    let mut installed = HashMap::new();
+
+    for entry in fs::read_dir(local_dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        if let Some((name, version)) = entry.file_name().to_str().and_then(split_entry_name) {
+            installed.insert(name.to_string(), version.to_string());
+        }
+    }
+
+    Ok(installed)
+}
+
+/// Split `<name>-<pkgver>-<pkgrel>` into the name and `<pkgver>-<pkgrel>`.
+///
+/// Neither pkgver nor pkgrel may contain a hyphen, so the version starts at
+/// the second-to-last one.
+pub fn split_entry_name(entry: &str) -> Option<(&str, &str)> {
+    let release = entry.rfind('-')?;
+    let version = entry[..release].rfind('-')?;
+    let (name, version) = (&entry[..version], &entry[version + 1..]);
+    (!name.is_empty()).then_some((name, version))
+}
+
+/// Fill in `installed_version` for every package installed in `local_dir`.
+///
+/// A missing or unreadable local database leaves the packages untouched.
+pub fn mark_installed(packages: &mut [PackageInfo], local_dir: &Path) {
+    let Ok(installed) = installed_versions(local_dir) else {
+        return;
+    };
+
+    for package in packages {
+        if let Some(version) = installed.get(&package.name) {
+            package.installed_version = Some(version.clone());
+        }
+    }
+}
+
+/// Read every installed package from `local_dir`, with its install reason,
+/// date and size. Packages are tagged with the `local` repository.
+pub fn read_local_db(local_dir: &Path) -> io::Result<Vec<PackageInfo>> {
+    let mut packages = Vec::new();
+
+    for entry in fs::read_dir(local_dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        // pacman keeps an `ALPM_DB_VERSION` file and nothing else besides
+        // the package directories, but skip anything unreadable
+        if let Some(package) = read_local_package(&entry.path()) {
+            packages.push(package);
+        }
+    }
+
+    packages.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(packages)
+}
+
+/// Find the installed packages owning a file matching `query`, with the
+/// matching files in `matched_files`.
+pub fn search_local_files(local_dir: &Path, query: &FileQuery) -> io::Result<Vec<PackageInfo>> {
+    let mut packages = Vec::new();
+
+    for entry in fs::read_dir(local_dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        let Ok(files) = fs::read_to_string(entry.path().join("files")) else {
+            continue;
+        };
+
+        let matched_files = query.matching_files(&files);
+        if matched_files.is_empty() {
+            continue;
+        }
+        if let Some(package) = read_local_package(&entry.path()) {
+            packages.push(PackageInfo {
+                matched_files,
+                ..package
+            });
+        }
+    }
+
+    packages.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(packages)
+}
+
+/// Read the package installed in `dir`, a `<name>-<pkgver>-<pkgrel>`
+/// directory of the local database.
+fn read_local_package(dir: &Path) -> Option<PackageInfo> {
+    let text = fs::read_to_string(dir.join("desc")).ok()?;
+    let desc = parse_desc(&text);
+    let package = package_from_desc(&desc, "local")?;
+
+    let reason = match desc_number::<u8>(&desc, "REASON") {
+        Some(1) => InstallReason::Dependency,
+        _ => InstallReason::Explicit,
+    };
+    Some(PackageInfo {
+        installed_version: Some(package.version.clone()),
+        install_reason: Some(reason),
+        install_date: desc_number(&desc, "INSTALLDATE"),
+        // The local database records `%SIZE%` instead of `%ISIZE%`
+        installed_size: desc_number(&desc, "SIZE"),
+        ..package
+    })
+}
@@ -0,0 +1,808 @@
+mod cli;
+
+use std::env;
+use std::io::{self, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use clap::CommandFactory;
+use clap_complete::env::{CompleteEnv, Shells};
+use clap_mangen::Man;
+use package_dictionary::appstream::{default_appstream_dirs, read_appstream_dirs};
+use package_dictionary::aur_rpc::{read_metadata_dump, RpcClient, METADATA_DUMP_URL};
+use package_dictionary::backend::{
+    add_flatpak_permissions, backends_for, files_backends_for, installed_backends_for,
+    installed_flatpaks, mark_flatpak_installed, AurBackend, AurHelper, AurRpcBackend,
+};
+use package_dictionary::commands::{CommandIndex, CommandIndexBuilder};
+use package_dictionary::config::{AurSearch, Config, ConfigError};
+use package_dictionary::index::{cache_dir, Index, IndexBuilder};
+use package_dictionary::install::{
+    install_commands, install_origin, last_search_path, load_last_search, run_all,
+    save_last_search, InstallTarget,
+};
+use package_dictionary::package::FlatpakKind;
+use package_dictionary::rank::SortOrder;
+use package_dictionary::render::{
+    collect_results, numbered_packages, print_info, print_results, set_color, stream_results,
+    warn_failure, OutputFormat, RenderOptions, Theme, BOLD, RED, RESET,
+};
+use package_dictionary::search::{lookup_packages, search_stream, SourceResults};
+use package_dictionary::syncdb::{self, DEFAULT_SYNC_DIR};
+use package_dictionary::{rank, tui, Backend, PackageInfo, Source};
+use tokio::runtime::Runtime;
+
+fn main() {
+    // Answer the completion scripts before anything else is printed
+    CompleteEnv::with_factory(cli::Cli::command)
+        .var(cli::COMPLETE_VAR)
+        .complete();
+
+    // Usage errors exit with status 2
+    let options = cli::parse_args(env::args_os()).unwrap_or_else(|e| e.exit());
+
+    match &options.command {
+        cli::Command::Completions(shell) => {
+            exit_on_write_error(write_completions(shell));
+            return;
+        }
+        cli::Command::Man => {
+            exit_on_write_error(Man::new(cli::Cli::command()).render(&mut io::stdout()));
+            return;
+        }
+        _ => {}
+    }
+
+    // The shell hook must not fail over a broken config file
+    if let cli::Command::NotFound(command) = &options.command {
+        let config = load_config(&options).unwrap_or_default();
+        set_color(config.color.enabled(io::stderr().is_terminal()));
+        command_not_found(&config, command);
+    }
+
+    let config = load_config(&options).unwrap_or_else(|e| {
+        eprintln!("{}Error:{} {}", RED, RESET, e);
+        std::process::exit(1);
+    });
+    set_color(config.color.enabled(io::stdout().is_terminal()));
+
+    match &options.command {
+        cli::Command::Search => search(&options, config),
+        cli::Command::Info(names) => info(&options, config, names),
+        cli::Command::Provides(query) => provides(&options, config, query),
+        cli::Command::Install {
+            targets,
+            dry_run,
+            yes,
+        } => install(config, targets, *dry_run, *yes),
+        cli::Command::ShowConfig => print!("{}", config.to_toml()),
+        cli::Command::ConfigPath => match config_path(&options) {
+            Some(path) => println!("{}", path.display()),
+            None => {
+                eprintln!("{}Error:{} No config file location", RED, RESET);
+                std::process::exit(1);
+            }
+        },
+        cli::Command::Completions(_) | cli::Command::Man | cli::Command::NotFound(_) => {
+            unreachable!("handled before loading the config")
+        }
+        cli::Command::IndexUpdate => {
+            if let Err(e) = update_index(&config) {
+                eprintln!("{}Error:{} Failed to update the index: {}", RED, RESET, e);
+                std::process::exit(1);
+            }
+        }
+    }
+}
+
+fn search(options: &cli::Options, config: Config) {
+    let theme = config.theme().unwrap_or_else(|e| {
+        eprintln!("{}Error:{} {}", RED, RESET, e);
+        std::process::exit(1);
+    });
+
+    // Create a tokio runtime with multi-threaded executor
+    let rt = Runtime::new().expect("Failed to create runtime");
+    let rpc = RpcClient::with_base_url(config.aur.rpc_url.clone());
+    let render_options = RenderOptions {
+        merged: options.merged,
+        grouped: options.grouped,
+        sort: options.sort,
+        limit: options.limit,
+        theme,
+        pager: config.pager.clone(),
+        stream: config.stream,
+    };
+    let format = options.format.unwrap_or(config.format);
+
+    // The index only knows what is available, not what is installed
+    let use_index = !options.installed && (options.offline || config.index);
+    if let Some(index) = open_index(use_index, options.offline) {
+        let mut results = index
+            .search(&options.term, &config.backends)
+            .unwrap_or_else(|e| {
+                eprintln!("{}Error:{} Failed to read the index: {}", RED, RESET, e);
+                std::process::exit(1);
+            });
+        if let Some(flatpak) = results.iter_mut().find(|r| r.source == Source::Flatpak) {
+            // The index does not know which applications are installed
+            if let Ok(installed) = rt.block_on(installed_flatpaks()) {
+                mark_flatpak_installed(&mut flatpak.packages, &installed);
+            }
+        }
+        filter_results(&mut results, options);
+        // Ranked like live results rather than by BM25
+        rank::score_results(&mut results, &options.term);
+        rank::sort_results(&mut results, options.sort);
+        if let Some(limit) = options.limit {
+            rank::limit_results(&mut results, limit);
+        }
+
+        if options.interactive {
+            let previewer = tui::Previewer::new(rt.handle().clone(), rpc);
+            let previewer = if options.offline {
+                previewer.offline()
+            } else {
+                previewer
+            };
+            pick_and_install(&rt, &results, previewer, config.aur.helper, options.sort);
+        } else {
+            exit_on_write_error(print_results(&results, format, &render_options));
+            remember_numbered(&results, format, &render_options);
+        }
+        return;
+    }
+
+    let backends = if options.installed {
+        installed_backends_for(&config.backends, rpc.clone())
+    } else {
+        backends_for(&config.backends, aur_backend(&rt, &config, rpc.clone()))
+    };
+    let names: Vec<String> = backends.iter().map(|b| b.name().to_string()).collect();
+    let timeout = |source| config.timeout_for(source);
+
+    if options.interactive {
+        let mut results = rt.block_on(async {
+            let stream = search_stream(backends, &options.term, timeout);
+            collect_results(stream, &names).await
+        });
+        filter_results(&mut results, options);
+        rank::score_results(&mut results, &options.term);
+        if let Some(limit) = options.limit {
+            rank::sort_results(&mut results, options.sort);
+            rank::limit_results(&mut results, limit);
+        }
+        if options.strict {
+            exit_on_failure(&results);
+        }
+        let previewer = tui::Previewer::new(rt.handle().clone(), rpc);
+        pick_and_install(&rt, &results, previewer, config.aur.helper, options.sort);
+        return;
+    }
+
+    let printed = rt.block_on(async {
+        let stream = search_stream(backends, &options.term, timeout);
+        stream_results(stream, &names, format, &render_options, |results| {
+            let results = std::slice::from_mut(results);
+            filter_results(results, options);
+            rank::score_results(results, &options.term);
+            rank::sort_results(results, options.sort);
+            if let Some(limit) = options.limit {
+                rank::limit_results(results, limit);
+            }
+        })
+        .await
+    });
+    finish_printed(printed, format, options, &render_options);
+}
+
+/// Remember the results printed by [`stream_results`] and exit on failures.
+fn finish_printed(
+    printed: io::Result<Vec<SourceResults>>,
+    format: OutputFormat,
+    options: &cli::Options,
+    render_options: &RenderOptions,
+) {
+    match printed {
+        Ok(results) => {
+            remember_numbered(&results, format, render_options);
+            if options.strict {
+                exit_on_failure(&results);
+            }
+        }
+        Err(e) => exit_on_write_error(Err(e)),
+    }
+}
+
+/// Show every source's details for each of `names`, side by side.
+///
+/// With `--offline` the details come from the index.
+fn info(options: &cli::Options, config: Config, names: &[String]) {
+    let theme = config.theme().unwrap_or_else(|e| {
+        eprintln!("{}Error:{} {}", RED, RESET, e);
+        std::process::exit(1);
+    });
+
+    let rt = Runtime::new().expect("Failed to create runtime");
+    let rpc = RpcClient::with_base_url(config.aur.rpc_url.clone());
+    let render_options = RenderOptions {
+        theme,
+        pager: config.pager.clone(),
+        ..RenderOptions::default()
+    };
+    let format = options.format.unwrap_or(config.format);
+
+    let index = open_index(options.offline, options.offline);
+    let backends = backends_for(&config.backends, aur_backend(&rt, &config, rpc));
+    let mut found = Vec::new();
+    let mut missing = Vec::new();
+
+    for name in names {
+        let results = match &index {
+            Some(index) => index.lookup(name, &config.backends).unwrap_or_else(|e| {
+                eprintln!("{}Error:{} Failed to read the index: {}", RED, RESET, e);
+                std::process::exit(1);
+            }),
+            None => rt.block_on(lookup_packages(&backends, name, |source| {
+                config.timeout_for(source)
+            })),
+        };
+        for r in &results {
+            warn_failure(r);
+        }
+        if options.strict {
+            exit_on_failure(&results);
+        }
+
+        let mut packages: Vec<PackageInfo> = results.into_iter().flat_map(|r| r.packages).collect();
+        if index.is_some() {
+            // The index has no permissions, read them from the installed or
+            // cached metadata
+            rt.block_on(add_flatpak_permissions(&mut packages, true));
+        }
+        if packages.is_empty() {
+            missing.push(name.as_str());
+        }
+        found.push(packages);
+    }
+
+    exit_on_write_error(print_info(&found, format, &render_options));
+
+    if !missing.is_empty() {
+        eprintln!(
+            "{}Error:{} No package called {}",
+            RED,
+            RESET,
+            missing.join(", ")
+        );
+        std::process::exit(1);
+    }
+}
+
+/// List the packages owning `query`, a path, file name or command, with the
+/// matching files.
+fn provides(options: &cli::Options, config: Config, query: &str) {
+    let theme = config.theme().unwrap_or_else(|e| {
+        eprintln!("{}Error:{} {}", RED, RESET, e);
+        std::process::exit(1);
+    });
+
+    let rt = Runtime::new().expect("Failed to create runtime");
+    let render_options = RenderOptions {
+        merged: options.merged,
+        grouped: options.grouped,
+        sort: options.sort,
+        limit: options.limit,
+        theme,
+        pager: config.pager.clone(),
+        stream: config.stream,
+    };
+    let format = options.format.unwrap_or(config.format);
+
+    let backends = files_backends_for(&config.backends);
+    let names: Vec<String> = backends.iter().map(|b| b.name().to_string()).collect();
+    let printed = rt.block_on(async {
+        let stream = search_stream(backends, query, |source| config.timeout_for(source));
+        stream_results(stream, &names, format, &render_options, |results| {
+            let results = std::slice::from_mut(results);
+            rank::sort_results(results, SortOrder::Name);
+            if let Some(limit) = options.limit {
+                rank::limit_results(results, limit);
+            }
+        })
+        .await
+    });
+    finish_printed(printed, format, options, &render_options);
+}
+
+/// Install `targets`, package names or numbers from the last search, once
+/// the user confirmed the summary of what will be installed.
+///
+/// A name found in several sources is asked about. With `dry_run` the
+/// install commands are printed instead.
+fn install(config: Config, targets: &[String], dry_run: bool, yes: bool) {
+    let theme = config.theme().unwrap_or_else(|e| {
+        eprintln!("{}Error:{} {}", RED, RESET, e);
+        std::process::exit(1);
+    });
+
+    let rt = Runtime::new().expect("Failed to create runtime");
+    let targets: Vec<InstallTarget> = targets.iter().map(|t| InstallTarget::parse(t)).collect();
+    let last_search = if targets
+        .iter()
+        .any(|t| matches!(t, InstallTarget::Number(_)))
+    {
+        let loaded = last_search_path()
+            .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no cache directory"))
+            .and_then(|path| load_last_search(&path));
+        loaded.unwrap_or_else(|e| {
+            eprintln!(
+                "{}Error:{} No search results to install from ({}). Search first, without --format.",
+                RED, RESET, e
+            );
+            std::process::exit(1);
+        })
+    } else {
+        Vec::new()
+    };
+
+    let rpc = RpcClient::with_base_url(config.aur.rpc_url.clone());
+    let backends = backends_for(&config.backends, aur_backend(&rt, &config, rpc));
+    let mut chosen: Vec<PackageInfo> = Vec::new();
+    let mut missing = Vec::new();
+
+    for target in &targets {
+        let package = match target {
+            InstallTarget::Number(number) => match last_search.get(number - 1) {
+                Some(package) => package.clone(),
+                None => {
+                    eprintln!(
+                        "{}Error:{} The last search has no result {}",
+                        RED, RESET, number
+                    );
+                    std::process::exit(1);
+                }
+            },
+            InstallTarget::Name(name) => {
+                let results = rt.block_on(lookup_packages(&backends, name, |source| {
+                    config.timeout_for(source)
+                }));
+                for r in &results {
+                    warn_failure(r);
+                }
+                let candidates: Vec<PackageInfo> =
+                    results.into_iter().flat_map(|r| r.packages).collect();
+                if candidates.is_empty() {
+                    missing.push(name.as_str());
+                    continue;
+                }
+                choose(name, candidates, &theme)
+            }
+        };
+
+        let origin = install_origin(&package);
+        if !chosen.iter().any(|p| install_origin(p) == origin) {
+            chosen.push(package);
+        }
+    }
+
+    if !missing.is_empty() {
+        eprintln!(
+            "{}Error:{} No package called {}",
+            RED,
+            RESET,
+            missing.join(", ")
+        );
+        std::process::exit(1);
+    }
+
+    let aur_helper = config
+        .aur
+        .helper
+        .or_else(|| rt.block_on(AurHelper::detect()))
+        .unwrap_or(AurHelper::Paru);
+    let commands = install_commands(&chosen, aur_helper);
+    if dry_run {
+        for command in &commands {
+            println!("{}", command);
+        }
+        return;
+    }
+
+    println!(
+        "{}::{} Packages to install ({}):",
+        BOLD,
+        RESET,
+        chosen.len()
+    );
+    for package in &chosen {
+        println!(
+            "   {}{}{} {}",
+            theme.color(package.source),
+            install_origin(package),
+            RESET,
+            package.version
+        );
+    }
+    println!("{}::{} Commands:", BOLD, RESET);
+    for command in &commands {
+        println!("   {}", command);
+    }
+
+    if !yes {
+        let answer = prompt("Proceed with installation? [Y/n]").unwrap_or_default();
+        if !matches!(answer.trim().to_lowercase().as_str(), "" | "y" | "yes") {
+            std::process::exit(1);
+        }
+    }
+    if let Err(e) = run_all(&commands) {
+        eprintln!("{}Error:{} {}", RED, RESET, e);
+        std::process::exit(1);
+    }
+}
+
+/// Ask which of `candidates`, the packages called `name` in different
+/// sources, to install.
+fn choose(name: &str, mut candidates: Vec<PackageInfo>, theme: &Theme) -> PackageInfo {
+    if candidates.len() == 1 {
+        return candidates.remove(0);
+    }
+
+    println!(
+        "{}::{} There are {} packages called {}:",
+        BOLD,
+        RESET,
+        candidates.len(),
+        name
+    );
+    for (number, package) in (1..).zip(&candidates) {
+        println!(
+            "   {}) {}{}{} {}",
+            number,
+            theme.color(package.source),
+            install_origin(package),
+            RESET,
+            package.version
+        );
+    }
+
+    loop {
+        let Some(answer) = prompt("Enter a number (default=1):") else {
+            eprintln!(
+                "{}Error:{} No package chosen for {}; narrow the sources down with --repo, --aur or --flatpak",
+                RED, RESET, name
+            );
+            std::process::exit(1);
+        };
+        let answer = answer.trim();
+        if answer.is_empty() {
+            return candidates.remove(0);
+        }
+        match answer.parse::<usize>() {
+            Ok(number) if (1..=candidates.len()).contains(&number) => {
+                return candidates.remove(number - 1)
+            }
+            _ => eprintln!("{}Error:{} Not a choice: {}", RED, RESET, answer),
+        }
+    }
+}
+
+/// Print `question` and read a line of answer, or `None` at the end of
+/// input.
+fn prompt(question: &str) -> Option<String> {
+    print!("{}::{} {} ", BOLD, RESET, question);
+    let _ = io::stdout().flush();
+    let mut answer = String::new();
+    match io::stdin().read_line(&mut answer) {
+        Ok(0) | Err(_) => None,
+        Ok(_) => Some(answer),
+    }
+}
+
+/// Remember text results, in the order they were numbered, for
+/// `pd install <number>`.
+fn remember_numbered(results: &[SourceResults], format: OutputFormat, options: &RenderOptions) {
+    if format != OutputFormat::Text {
+        return;
+    }
+    if let Some(path) = last_search_path() {
+        // Only a convenience; an unwritable cache must not fail the search
+        let _ = save_last_search(&path, &numbered_packages(results, options));
+    }
+}
+
+/// Answer the shell's command-not-found hook from the command index: say
+/// which packages provide `command` and how to install them, then exit with
+/// status 127 like the shell would have.
+fn command_not_found(config: &Config, command: &str) -> ! {
+    eprintln!("{}: command not found", command);
+
+    let index = CommandIndex::default_path().and_then(|path| CommandIndex::open(&path).ok());
+    let providers = index.map(|index| index.lookup(command)).unwrap_or_default();
+    if providers.is_empty() {
+        std::process::exit(127);
+    }
+
+    let theme = config.theme().unwrap_or_default();
+    let aur_helper = config.aur.helper.unwrap_or_else(|| {
+        // Only look for a helper when it is needed, it takes a few milliseconds
+        if providers.iter().any(|p| p.source == Source::Aur) {
+            let rt = tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("Failed to create runtime");
+            rt.block_on(AurHelper::detect()).unwrap_or(AurHelper::Paru)
+        } else {
+            AurHelper::Paru
+        }
+    });
+
+    let origins: Vec<String> = providers
+        .iter()
+        .map(|p| install_origin(&p.package()))
+        .collect();
+    let width = origins.iter().map(|o| o.chars().count()).max().unwrap_or(0);
+
+    eprintln!("{} may be found in the following packages:", command);
+    for (provider, origin) in providers.iter().zip(&origins) {
+        let install = install_commands(&[provider.package()], aur_helper);
+        let install = install.first().map(ToString::to_string).unwrap_or_default();
+        eprintln!(
+            "  {}{:<width$}{}  {}",
+            theme.color(provider.source),
+            origin,
+            RESET,
+            install,
+            width = width
+        );
+    }
+    std::process::exit(127);
+}
+
+/// Print the script registering `pd`'s completions with `shell`.
+fn write_completions(shell: &str) -> io::Result<()> {
+    let shells = Shells::builtins();
+    let completer = shells
+        .completer(shell)
+        .expect("the parser only accepts supported shells");
+    let mut stdout = io::stdout().lock();
+    completer.write_registration(cli::COMPLETE_VAR, "pd", "pd", "pd", &mut stdout)
+}
+
+/// Apply the search filters: `--match`, the Flatpak remote and kinds, and
+/// `--upgradable`.
+fn filter_results(results: &mut [SourceResults], options: &cli::Options) {
+    rank::retain_matches(results, &options.term, options.match_field);
+    for r in results.iter_mut().filter(|r| r.source == Source::Flatpak) {
+        r.packages.retain(|package| {
+            let remote = options.flatpak_remote.as_deref();
+            remote.is_none_or(|remote| package.repo.as_deref() == Some(remote))
+                && options
+                    .flatpak_kinds
+                    .contains(&package.kind.unwrap_or(FlatpakKind::App))
+        });
+    }
+    if options.upgradable {
+        retain_upgradable(results);
+    }
+}
+
+/// Keep only the installed packages with a newer version available, for
+/// `--upgradable`.
+fn retain_upgradable(results: &mut [SourceResults]) {
+    for r in results {
+        r.packages.retain(PackageInfo::is_upgradable);
+    }
+}
+
+/// The AUR backend chosen by the `aur.search` setting.
+fn aur_backend(rt: &Runtime, config: &Config, rpc: RpcClient) -> Arc<dyn Backend> {
+    match config.aur.search {
+        AurSearch::Rpc => Arc::new(AurRpcBackend::new(rpc)),
+        AurSearch::Helper => {
+            let helper = config
+                .aur
+                .helper
+                .or_else(|| rt.block_on(AurHelper::detect()))
+                .unwrap_or(AurHelper::Paru);
+            Arc::new(AurBackend::new(helper))
+        }
+    }
+}
+
+/// Exit with an error if any source failed, for `--strict`.
+fn exit_on_failure(results: &[SourceResults]) {
+    let failed: Vec<&str> = results
+        .iter()
+        .filter(|r| r.error.is_some())
+        .map(|r| r.name.as_str())
+        .collect();
+    if !failed.is_empty() {
+        eprintln!(
+            "{}Error:{} Search failed for {}",
+            RED,
+            RESET,
+            failed.join(", ")
+        );
+        std::process::exit(1);
+    }
+}
+
+/// Open the offline index if `use_index` is set. Without an index, searches
+/// fall back to running the backends, unless `offline` is set.
+fn open_index(use_index: bool, offline: bool) -> Option<Index> {
+    if !use_index {
+        return None;
+    }
+
+    let opened = Index::default_path()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no cache directory"))
+        .and_then(|path| Index::open(&path));
+    match opened {
+        Ok(index) => Some(index),
+        Err(e) if offline => {
+            eprintln!(
+                "{}Error:{} No offline index ({}). Run `pd index update` first.",
+                RED, RESET, e
+            );
+            std::process::exit(1);
+        }
+        Err(e) if e.kind() == io::ErrorKind::NotFound => None,
+        Err(e) => {
+            eprintln!("{}Warning:{} Ignoring the index: {}", RED, RESET, e);
+            None
+        }
+    }
+}
+
+fn exit_on_write_error(result: io::Result<()>) {
+    if let Err(e) = result {
+        // A closed pipe (e.g. `pd foo --format ndjson | head`) is not an error
+        if e.kind() != io::ErrorKind::BrokenPipe {
+            eprintln!("{}Error:{} Failed to write results: {}", RED, RESET, e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Rebuild the offline index from the sync databases, the AUR metadata dump
+/// and Flatpak's appstream data, and the command index from the files
+/// databases too. Missing sources are skipped with a warning.
+fn update_index(config: &Config) -> io::Result<()> {
+    let path = Index::default_path()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no cache directory"))?;
+    let commands_path = CommandIndex::default_path()
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "no cache directory"))?;
+    let mut builder = IndexBuilder::new();
+    let mut commands = CommandIndexBuilder::new();
+    let mut counts = Vec::new();
+
+    match syncdb::read_sync_dir(Path::new(DEFAULT_SYNC_DIR)) {
+        Ok(packages) => {
+            counts.push(format!("{} Pacman", packages.len()));
+            builder.add(packages);
+        }
+        Err(e) => eprintln!(
+            "{}Warning:{} Skipping pacman: {}: {}",
+            RED, RESET, DEFAULT_SYNC_DIR, e
+        ),
+    }
+    match commands.add_files_dir(Path::new(DEFAULT_SYNC_DIR)) {
+        Ok(0) => eprintln!(
+            "{}Warning:{} No repository commands: run `pacman -Fy` to download the files databases",
+            RED, RESET
+        ),
+        Ok(_) => {}
+        Err(e) => eprintln!(
+            "{}Warning:{} Skipping repository commands: {}: {}",
+            RED, RESET, DEFAULT_SYNC_DIR, e
+        ),
+    }
+
+    let dump = config
+        .aur
+        .dump
+        .clone()
+        .or_else(|| cache_dir().map(|dir| dir.join("packages-meta-ext-v1.json.gz")));
+    match dump.as_deref().map(read_metadata_dump) {
+        Some(Ok(packages)) => {
+            counts.push(format!("{} AUR", packages.len()));
+            commands.add_packages(&packages);
+            builder.add(packages);
+        }
+        Some(Err(e)) if e.kind() == io::ErrorKind::NotFound => eprintln!(
+            "{}Warning:{} Skipping the AUR: download {} to {} or set aur.dump",
+            RED,
+            RESET,
+            METADATA_DUMP_URL,
+            dump.unwrap_or_default().display()
+        ),
+        Some(Err(e)) => eprintln!(
+            "{}Warning:{} Skipping the AUR: {}: {}",
+            RED,
+            RESET,
+            dump.unwrap_or_default().display(),
+            e
+        ),
+        None => {}
+    }
+
+    let packages = read_appstream_dirs(&default_appstream_dirs())?;
+    counts.push(format!("{} Flatpak", packages.len()));
+    commands.add_packages(&packages);
+    builder.add(packages);
+
+    builder.write(&path)?;
+    let command_count = commands.len();
+    commands.write(&commands_path)?;
+    println!(
+        "{}::{} Indexed {} packages into {} and {} commands into {}",
+        BOLD,
+        RESET,
+        counts.join(", "),
+        path.display(),
+        command_count,
+        commands_path.display()
+    );
+    Ok(())
+}
+
+/// The config file: `--config`, else `$PD_CONFIG`, else the default location.
+fn config_path(options: &cli::Options) -> Option<PathBuf> {
+    options
+        .config
+        .clone()
+        .or_else(|| env::var_os("PD_CONFIG").map(PathBuf::from))
+        .or_else(Config::default_path)
+}
+
+/// Read the config file, then apply environment and command-line overrides.
+fn load_config(options: &cli::Options) -> Result<Config, ConfigError> {
+    let mut config = match config_path(options) {
+        Some(path) => Config::load(&path)?,
+        None => Config::default(),
+    };
+    config.apply_env(env::vars())?;
+    for (key, value) in &options.overrides {
+        config.set(key, value)?;
+    }
+    Ok(config)
+}
+
+/// Run the interactive picker and install whatever the user chose.
+fn pick_and_install(
+    rt: &Runtime,
+    results: &[SourceResults],
+    previewer: tui::Previewer,
+    aur_helper: Option<AurHelper>,
+    sort: SortOrder,
+) {
+    let packages = rank::merge_results(results, sort);
+
+    let chosen = match tui::run(packages, previewer) {
+        Ok(Some(chosen)) => chosen,
+        Ok(None) => return,
+        Err(e) => {
+            eprintln!("{}Error:{} Interactive mode failed: {}", RED, RESET, e);
+            std::process::exit(1);
+        }
+    };
+    install_packages(rt, &chosen, aur_helper);
+}
+
+/// Install `packages` through pacman, the AUR helper or flatpak.
+fn install_packages(rt: &Runtime, packages: &[PackageInfo], aur_helper: Option<AurHelper>) {
+    // Prefer whichever helper is installed, paru first
+    let aur_helper = aur_helper
+        .or_else(|| rt.block_on(AurHelper::detect()))
+        .unwrap_or(AurHelper::Paru);
+
+    let commands = install_commands(packages, aur_helper);
+    for command in &commands {
+        println!("{}::{} {}", BOLD, RESET, command);
+    }
+    if let Err(e) = run_all(&commands) {
+        eprintln!("{}Error:{} {}", RED, RESET, e);
+        std::process::exit(1);
+    }
+}
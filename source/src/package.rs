@@ -0,0 +1,355 @@
+use std::str::FromStr;
+
+use serde::{Deserialize, Serialize};
+
+use crate::sandbox::Permissions;
+use crate::vercmp;
+
+/// The package source a result came from, ordered repositories first, then
+/// the AUR, then Flatpak.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Source {
+    Pacman,
+    Aur,
+    Flatpak,
+}
+
+impl Source {
+    /// Human-readable name used in section headers and warnings.
+    pub fn label(self) -> &'static str {
+        match self {
+            Source::Pacman => "Pacman",
+            Source::Aur => "AUR",
+            Source::Flatpak => "Flatpak",
+        }
+    }
+
+    /// Stable lowercase identifier used in machine-readable output.
+    pub fn id(self) -> &'static str {
+        match self {
+            Source::Pacman => "pacman",
+            Source::Aur => "aur",
+            Source::Flatpak => "flatpak",
+        }
+    }
+}
+
+impl FromStr for Source {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "pacman" => Ok(Source::Pacman),
+            "aur" => Ok(Source::Aur),
+            "flatpak" => Ok(Source::Flatpak),
+            other => Err(format!(
+                "unknown source '{}' (expected pacman, aur or flatpak)",
+                other
+            )),
+        }
+    }
+}
+
+/// Why a package is installed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum InstallReason {
+    /// Installed at the user's request.
+    Explicit,
+    /// Pulled in as a dependency of another package.
+    Dependency,
+}
+
+impl InstallReason {
+    pub fn label(self) -> &'static str {
+        match self {
+            InstallReason::Explicit => "explicit",
+            InstallReason::Dependency => "dependency",
+        }
+    }
+}
+
+/// What a Flatpak ref holds.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum FlatpakKind {
+    App,
+    Runtime,
+    /// A runtime extending an application or another runtime, such as a
+    /// plugin, a GL driver or translations.
+    Extension,
+}
+
+impl FlatpakKind {
+    pub fn label(self) -> &'static str {
+        match self {
+            FlatpakKind::App => "app",
+            FlatpakKind::Runtime => "runtime",
+            FlatpakKind::Extension => "extension",
+        }
+    }
+
+    /// The kind of a runtime ref, telling extensions apart by the parts of
+    /// their IDs, as in `org.freedesktop.Platform.GL.default` or
+    /// `org.gimp.GIMP.Locale`.
+    pub fn of_runtime(id: &str) -> FlatpakKind {
+        const EXTENSION_PARTS: &[&str] = &[
+            "Locale",
+            "Debug",
+            "Sources",
+            "GL",
+            "GL32",
+            "Extension",
+            "Plugin",
+            "Codecs",
+        ];
+
+        if id.split('.').any(|part| EXTENSION_PARTS.contains(&part)) {
+            FlatpakKind::Extension
+        } else {
+            FlatpakKind::Runtime
+        }
+    }
+
+    /// The kind of a ref known only by its ID: extensions as in
+    /// [`FlatpakKind::of_runtime`], runtimes by the usual last parts of
+    /// their IDs, as in `org.gnome.Platform`, and applications otherwise.
+    pub fn of_id(id: &str) -> FlatpakKind {
+        const RUNTIME_PARTS: &[&str] = &["Platform", "Sdk", "BaseApp"];
+
+        match FlatpakKind::of_runtime(id) {
+            FlatpakKind::Extension => FlatpakKind::Extension,
+            _ if id
+                .rsplit('.')
+                .next()
+                .is_some_and(|part| RUNTIME_PARTS.contains(&part)) =>
+            {
+                FlatpakKind::Runtime
+            }
+            _ => FlatpakKind::App,
+        }
+    }
+}
+
+/// A release listed in appstream metadata.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Release {
+    pub version: String,
+    /// Unix time of the release.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub date: Option<i64>,
+}
+
+/// A single search result, independent of the backend that produced it.
+///
+/// Only `source`, `name`, `version` and `description` are always known; the
+/// remaining fields are filled in by backends that can provide them.
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct PackageInfo {
+    pub source: Source,
+    pub name: String,
+    pub version: String,
+    pub description: String,
+    /// Flatpak application ID, e.g. `org.mozilla.firefox`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub app_id: Option<String>,
+    /// Repository the package comes from, e.g. `core` or `extra`, or the
+    /// Flatpak remote, e.g. `flathub`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub repo: Option<String>,
+    /// Flatpak branch, e.g. `stable`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub branch: Option<String>,
+    /// Flatpak architecture, e.g. `x86_64`.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub arch: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub kind: Option<FlatpakKind>,
+    /// Whether this Flatpak was read from a remote's appstream data, which
+    /// already holds everything `flatpak remote-info` would tell.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub appstream: bool,
+    /// The full description from appstream metadata, with paragraphs
+    /// separated by blank lines; `description` holds the one-line summary.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub long_description: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub developer: Option<String>,
+    /// Freedesktop menu categories, e.g. `Graphics`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub categories: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub groups: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub provides: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub depends: Vec<String>,
+    /// Optional dependencies, as `name: reason`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub opt_depends: Vec<String>,
+    /// Build-time dependencies.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub make_depends: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub conflicts: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub replaces: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub licenses: Vec<String>,
+    /// Search keywords from the AUR or appstream metadata.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+    /// Releases from appstream metadata, newest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub releases: Vec<Release>,
+    /// OARS content rating attributes with a non-`none` intensity, as
+    /// `attribute: intensity`, e.g. `social-chat: intense`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub content_rating: Vec<String>,
+    /// Paths of the cached icons, largest first.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub icons: Vec<String>,
+    /// Flatpak sandbox permissions, only looked up for `pd info` and the
+    /// interactive preview.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub permissions: Option<Permissions>,
+    /// Upstream project URL.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub packager: Option<String>,
+    /// Unix time the package was built.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub build_date: Option<i64>,
+    /// Download size in bytes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub compressed_size: Option<u64>,
+    /// Size on disk once installed, in bytes.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub installed_size: Option<u64>,
+    /// The locally installed version, if the package is installed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub installed_version: Option<String>,
+    /// The Flatpak installations the package is installed in: `system`,
+    /// `user` or the name of a custom installation.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub installations: Vec<String>,
+    /// Why the package is installed, for installed packages.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub install_reason: Option<InstallReason>,
+    /// Unix time the package was installed.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub install_date: Option<i64>,
+    /// AUR vote count.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub votes: Option<u32>,
+    /// AUR popularity score.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub popularity: Option<f64>,
+    /// AUR maintainer, known to RPC results only.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub maintainer: Option<String>,
+    /// Whether the AUR package has no maintainer.
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub orphaned: bool,
+    /// Unix time at which the package was flagged out of date, or `0` when the
+    /// flag date is unknown.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub out_of_date: Option<i64>,
+    /// Unix time of the last update.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_modified: Option<i64>,
+    /// The package's files matching a `pd provides` query, as absolute paths.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub matched_files: Vec<String>,
+    /// Relevance for the search term, see [`crate::rank`].
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub score: Option<f64>,
+}
+
+impl PackageInfo {
+    /// A result with only the fields every backend knows about.
+    pub fn new(
+        source: Source,
+        name: impl Into<String>,
+        version: impl Into<String>,
+        description: impl Into<String>,
+    ) -> Self {
+        PackageInfo {
+            source,
+            name: name.into(),
+            version: version.into(),
+            description: description.into(),
+            app_id: None,
+            repo: None,
+            branch: None,
+            arch: None,
+            kind: None,
+            appstream: false,
+            long_description: None,
+            developer: None,
+            categories: Vec::new(),
+            groups: Vec::new(),
+            provides: Vec::new(),
+            depends: Vec::new(),
+            opt_depends: Vec::new(),
+            make_depends: Vec::new(),
+            conflicts: Vec::new(),
+            replaces: Vec::new(),
+            licenses: Vec::new(),
+            keywords: Vec::new(),
+            releases: Vec::new(),
+            content_rating: Vec::new(),
+            icons: Vec::new(),
+            permissions: None,
+            url: None,
+            packager: None,
+            build_date: None,
+            compressed_size: None,
+            installed_size: None,
+            installed_version: None,
+            installations: Vec::new(),
+            install_reason: None,
+            install_date: None,
+            votes: None,
+            popularity: None,
+            maintainer: None,
+            orphaned: false,
+            out_of_date: None,
+            last_modified: None,
+            matched_files: Vec::new(),
+            score: None,
+        }
+    }
+
+    /// Whether the package is called `name`: its package name or, for
+    /// Flatpaks, its application ID or display name, ignoring case.
+    pub fn is_named(&self, name: &str) -> bool {
+        if self.name == name {
+            return true;
+        }
+        self.source == Source::Flatpak
+            && (self.display_name().eq_ignore_ascii_case(name)
+                || self
+                    .app_id
+                    .as_deref()
+                    .is_some_and(|id| id.eq_ignore_ascii_case(name)))
+    }
+
+    /// The name without the application ID Flatpak names carry, as in
+    /// `Firefox (org.mozilla.firefox)`.
+    pub fn display_name(&self) -> &str {
+        match self.source {
+            Source::Flatpak => self.name.split(" (").next().unwrap_or(&self.name),
+            Source::Pacman | Source::Aur => &self.name,
+        }
+    }
+
+    /// Whether the package is installed in an older version than `version`.
+    pub fn is_upgradable(&self) -> bool {
+        self.installed_version
+            .as_deref()
+            .is_some_and(|installed| vercmp::is_newer(&self.version, installed))
+    }
+}
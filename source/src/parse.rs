@@ -0,0 +1,255 @@
+//! Parser for the `-Ss` output of pacman and the AUR helpers.
+//!
+//! Each package takes two lines: a header line followed by an indented
+//! description, or a single line with the description after a tab for
+//! `yay --singlelineresults`. The header line has the form
+//!
+//! ```text
+//! repo/name version [annotation ...]
+//! ```
+//!
+//! where each annotation is wrapped in parentheses or brackets:
+//!
+//! * `(group1 group2)`: pacman groups
+//! * `[installed]` or `[installed: 1.0-1]`: the local install state
+//! * `(+votes popularity)`: AUR votes and popularity
+//! * `(Out-of-date: YYYY-MM-DD)`: AUR out-of-date flag
+//! * `(Orphaned)`: AUR package without a maintainer
+//!
+//! Backends run the tools in the C locale without color, but the parser
+//! does not rely on it: escape sequences are stripped, any bracketed
+//! annotation is taken as the install marker, and a parenthesized
+//! `label: date` as the out-of-date flag, whatever the label's language.
+//! Untranslatable annotations of AUR entries are ignored rather than taken
+//! for groups.
+
+use crate::date::parse_date;
+use crate::package::{PackageInfo, Source};
+
+/// The fields of a header line.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct HeaderLine {
+    pub repo: String,
+    pub name: String,
+    pub version: String,
+    pub groups: Vec<String>,
+    /// The installed version, if the package is installed.
+    pub installed_version: Option<String>,
+    pub votes: Option<u32>,
+    pub popularity: Option<f64>,
+    /// Unix time the package was flagged out of date, or `0` when flagged
+    /// without a date.
+    pub out_of_date: Option<i64>,
+    pub orphaned: bool,
+}
+
+/// Parse a `repo/name version [annotation ...]` header line.
+///
+/// Returns `None` for lines that are not header lines, such as indented
+/// descriptions.
+pub fn parse_header_line(line: &str) -> Option<HeaderLine> {
+    if line.starts_with(char::is_whitespace) {
+        return None;
+    }
+
+    let mut tokens = line.trim_end().splitn(3, ' ');
+    let (repo, name) = tokens.next()?.split_once('/')?;
+    let version = tokens.next()?;
+    if repo.is_empty() || name.is_empty() || version.is_empty() {
+        return None;
+    }
+
+    let mut header = HeaderLine {
+        repo: repo.to_string(),
+        name: name.to_string(),
+        version: version.to_string(),
+        ..HeaderLine::default()
+    };
+
+    for (open, content) in annotations(tokens.next().unwrap_or("")) {
+        apply_annotation(&mut header, open, content);
+    }
+
+    Some(header)
+}
+
+/// Split the text after the version into `(...)` and `[...]` annotations.
+fn annotations(mut rest: &str) -> Vec<(char, &str)> {
+    let mut found = Vec::new();
+
+    loop {
+        rest = rest.trim_start();
+        let Some(open) = rest.chars().next() else {
+            break;
+        };
+
+        let close = match open {
+            '(' => ')',
+            '[' => ']',
+            _ => {
+                // Skip unknown bare words
+                rest = rest.split_once(' ').map_or("", |(_, tail)| tail);
+                continue;
+            }
+        };
+
+        match rest[1..].find(close) {
+            Some(end) => {
+                found.push((open, rest[1..end + 1].trim()));
+                rest = &rest[end + 2..];
+            }
+            None => break,
+        }
+    }
+
+    found
+}
+
+fn apply_annotation(header: &mut HeaderLine, open: char, content: &str) {
+    let lower = content.to_lowercase();
+
+    if let Some(votes) = content.strip_prefix('+') {
+        let mut parts = votes.split_whitespace();
+        header.votes = parts.next().and_then(|v| v.parse().ok());
+        header.popularity = parts
+            .next()
+            .and_then(|p| p.trim_start_matches('~').replace(',', ".").parse().ok());
+    } else if lower.starts_with("out-of-date") {
+        let date = content
+            .split_once(':')
+            .and_then(|(_, date)| parse_date(date));
+        header.out_of_date = Some(date.unwrap_or(0));
+    } else if lower == "orphaned" {
+        header.orphaned = true;
+    } else if open == '[' || lower == "installed" || lower.starts_with("installed:") {
+        // `[installed]`, `[installed: 1.0-1]` or a translation of them
+        let version = content.split_once(':').map(|(_, version)| version.trim());
+        header.installed_version = Some(version.unwrap_or(&header.version).to_string());
+    } else if let Some(date) = content
+        .split_once(':')
+        .and_then(|(_, date)| parse_date(date))
+    {
+        // A translated `Out-of-date: YYYY-MM-DD`
+        header.out_of_date = Some(date);
+    } else if header.repo != "aur" {
+        header
+            .groups
+            .extend(content.split_whitespace().map(str::to_string));
+    }
+}
+
+/// Remove ANSI escape sequences from `text`.
+pub fn strip_ansi(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\x1b' {
+            stripped.push(c);
+            continue;
+        }
+        match chars.next() {
+            // CSI: parameters up to a final byte in `@`..=`~`
+            Some('[') => {
+                for c in chars.by_ref() {
+                    if ('@'..='~').contains(&c) {
+                        break;
+                    }
+                }
+            }
+            // OSC: up to BEL or ST
+            Some(']') => {
+                while let Some(c) = chars.next() {
+                    if c == '\x07' || (c == '\x1b' && chars.next() == Some('\\')) {
+                        break;
+                    }
+                }
+            }
+            _ => {}
+        }
+    }
+
+    stripped
+}
+
+/// Parse `-Ss` output into packages tagged with `source`.
+///
+/// For [`Source::Aur`] only `aur/` entries are kept, since some helpers mix
+/// repository packages into their results.
+pub fn parse_search_output(stdout: &[u8], source: Source) -> Vec<PackageInfo> {
+    let stdout = decolor(stdout);
+
+    // Pre-allocate with approximate capacity
+    let mut results = Vec::with_capacity(stdout.lines().count() / 2);
+    let mut lines = stdout.lines().peekable();
+
+    while let Some(line) = lines.next() {
+        let Some(header) = parse_header_line(line) else {
+            continue;
+        };
+        if source == Source::Aur && header.repo != "aur" {
+            continue;
+        }
+
+        // The description is the following indented line, if any
+        let description = match lines.peek() {
+            Some(next) if next.starts_with(char::is_whitespace) => {
+                let description = next.trim().to_string();
+                lines.next();
+                description
+            }
+            _ => String::new(),
+        };
+
+        results.push(package_from_header(header, description, source));
+    }
+
+    results
+}
+
+/// Parse `yay -Ss --singlelineresults` output, one package per line with
+/// the description after a tab, into packages tagged with `source`.
+///
+/// Lines without a tab are skipped, so two-line output parses to nothing;
+/// like [`parse_search_output`], only `aur/` entries are kept for
+/// [`Source::Aur`].
+pub fn parse_single_line_output(stdout: &[u8], source: Source) -> Vec<PackageInfo> {
+    decolor(stdout)
+        .lines()
+        .filter_map(|line| {
+            let (header, description) = line.split_once('\t')?;
+            let header = parse_header_line(header)?;
+            (source != Source::Aur || header.repo == "aur")
+                .then(|| package_from_header(header, description.trim().to_string(), source))
+        })
+        .collect()
+}
+
+/// `stdout` as text without escape sequences.
+fn decolor(stdout: &[u8]) -> String {
+    let stdout = String::from_utf8_lossy(stdout);
+    if stdout.contains('\x1b') {
+        strip_ansi(&stdout)
+    } else {
+        stdout.into_owned()
+    }
+}
+
+fn package_from_header(header: HeaderLine, description: String, source: Source) -> PackageInfo {
+    let description = if description.is_empty() {
+        "No description.".to_string()
+    } else {
+        description
+    };
+
+    PackageInfo {
+        repo: Some(header.repo),
+        groups: header.groups,
+        installed_version: header.installed_version,
+        votes: header.votes,
+        popularity: header.popularity,
+        out_of_date: header.out_of_date,
+        orphaned: header.orphaned,
+        ..PackageInfo::new(source, header.name, header.version, description)
+    }
+}
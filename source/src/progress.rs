@@ -0,0 +1,77 @@
+//! A one-line spinner listing the sources that are still being searched.
+
+use std::io::{self, IsTerminal, Write};
+
+const FRAMES: [char; 10] = ['⠋', '⠙', '⠹', '⠸', '⠼', '⠴', '⠦', '⠧', '⠇', '⠏'];
+
+/// Spinner drawn on stderr, or nothing at all when stderr is not a terminal.
+pub struct Spinner {
+    /// Backend index and name of every source still searching.
+    pending: Vec<(usize, String)>,
+    frame: usize,
+    enabled: bool,
+    drawn: bool,
+}
+
+impl Spinner {
+    pub fn new(names: &[String]) -> Self {
+        Spinner {
+            pending: names.iter().cloned().enumerate().collect(),
+            frame: 0,
+            enabled: io::stderr().is_terminal(),
+            drawn: false,
+        }
+    }
+
+    /// Mark the source at `index` as done.
+    pub fn finish(&mut self, index: usize) {
+        self.pending.retain(|(i, _)| *i != index);
+    }
+
+    /// Advance the animation and redraw.
+    pub fn tick(&mut self) {
+        self.frame = (self.frame + 1) % FRAMES.len();
+        self.draw();
+    }
+
+    /// Draw the current state, or clear the line once nothing is pending.
+    pub fn draw(&mut self) {
+        if !self.enabled {
+            return;
+        }
+        if self.pending.is_empty() {
+            self.clear();
+            return;
+        }
+
+        let names: Vec<&str> = self.pending.iter().map(|(_, name)| name.as_str()).collect();
+        let mut stderr = io::stderr().lock();
+        // Progress output is best effort. The cursor goes back to the start of
+        // the line so that warnings overwrite the spinner instead of being
+        // appended to it.
+        let _ = write!(
+            stderr,
+            "\r\x1B[2K{} Searching {}…\r",
+            FRAMES[self.frame],
+            names.join(", ")
+        );
+        let _ = stderr.flush();
+        self.drawn = true;
+    }
+
+    /// Erase the spinner line so other output can be printed in its place.
+    pub fn clear(&mut self) {
+        if self.drawn {
+            let mut stderr = io::stderr().lock();
+            let _ = write!(stderr, "\r\x1B[2K");
+            let _ = stderr.flush();
+            self.drawn = false;
+        }
+    }
+}
+
+impl Drop for Spinner {
+    fn drop(&mut self) {
+        self.clear();
+    }
+}
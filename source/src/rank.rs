@@ -0,0 +1,252 @@
+//! Relevance ranking of search results.
+//!
+//! Every package gets a score made of a match tier and a bonus:
+//!
+//! 1. the tier is how the search term matches: the exact name or Flatpak
+//!    application ID, a prefix of either, a substring of either, or only
+//!    the description;
+//! 2. the bonus, always smaller than the gap between two tiers, favors
+//!    installed packages, official repositories over the AUR, popular and
+//!    up-to-date AUR packages, and names close in length to the term.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::package::{PackageInfo, Source};
+use crate::search::SourceResults;
+
+const TIER_EXACT: f64 = 4000.0;
+const TIER_PREFIX: f64 = 3000.0;
+const TIER_SUBSTRING: f64 = 2000.0;
+const TIER_DESCRIPTION: f64 = 1000.0;
+
+/// How results are ordered.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SortOrder {
+    /// Best match first.
+    #[default]
+    Relevance,
+    /// Alphabetically by name, ignoring case.
+    Name,
+    /// Most popular AUR packages first; packages without a popularity score
+    /// follow in relevance order.
+    Popularity,
+    /// Grouped by source in backend order, by relevance within a source.
+    Source,
+}
+
+impl fmt::Display for SortOrder {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            SortOrder::Relevance => "relevance",
+            SortOrder::Name => "name",
+            SortOrder::Popularity => "popularity",
+            SortOrder::Source => "source",
+        })
+    }
+}
+
+impl FromStr for SortOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "relevance" => Ok(SortOrder::Relevance),
+            "name" => Ok(SortOrder::Name),
+            "popularity" => Ok(SortOrder::Popularity),
+            "source" => Ok(SortOrder::Source),
+            other => Err(format!(
+                "unknown sort order '{}' (expected relevance, name, popularity or source)",
+                other
+            )),
+        }
+    }
+}
+
+/// Which fields of a package a search term has to match.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum MatchField {
+    /// The package name, or a Flatpak's display name.
+    Name,
+    Description,
+    /// The Flatpak application ID, or the package name elsewhere.
+    Id,
+    /// Whatever each source matches on its own: names and descriptions,
+    /// and for Flatpak application IDs and keywords too.
+    #[default]
+    All,
+}
+
+impl fmt::Display for MatchField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            MatchField::Name => "name",
+            MatchField::Description => "description",
+            MatchField::Id => "id",
+            MatchField::All => "all",
+        })
+    }
+}
+
+impl FromStr for MatchField {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "name" => Ok(MatchField::Name),
+            "description" => Ok(MatchField::Description),
+            "id" => Ok(MatchField::Id),
+            "all" => Ok(MatchField::All),
+            other => Err(format!(
+                "unknown match field '{}' (expected name, description, id or all)",
+                other
+            )),
+        }
+    }
+}
+
+/// Whether every word of `term` appears in the `field` of `package`,
+/// ignoring case. [`MatchField::All`] keeps whatever the source matched.
+pub fn matches_field(package: &PackageInfo, term: &str, field: MatchField) -> bool {
+    let text = match field {
+        MatchField::Name => package.display_name(),
+        MatchField::Description => &package.description,
+        MatchField::Id => package.app_id.as_deref().unwrap_or(&package.name),
+        MatchField::All => return true,
+    }
+    .to_lowercase();
+    term.to_lowercase()
+        .split_whitespace()
+        .all(|word| text.contains(word))
+}
+
+/// Keep the packages matching `term` in `field`, see [`matches_field`].
+pub fn retain_matches(results: &mut [SourceResults], term: &str, field: MatchField) {
+    for r in results {
+        r.packages
+            .retain(|package| matches_field(package, term, field));
+    }
+}
+
+/// Score how relevant `package` is for `term`.
+pub fn relevance(package: &PackageInfo, term: &str) -> f64 {
+    let term = term.trim().to_lowercase();
+    // `pd foo bar` should find `foo-bar`
+    let joined = term.split_whitespace().collect::<Vec<_>>().join("-");
+    let name = package.display_name().to_lowercase();
+
+    let id_tier = package
+        .app_id
+        .as_deref()
+        .and_then(|id| field_tier(&id.to_lowercase(), &term, &joined));
+    let tier = field_tier(&name, &term, &joined)
+        .into_iter()
+        .chain(id_tier)
+        .fold(TIER_DESCRIPTION, f64::max);
+
+    tier + source_bonus(package) + length_bonus(&name, &term)
+}
+
+/// The tier of a name or ID matching `term`, or of `joined`, its words
+/// joined with dashes.
+fn field_tier(field: &str, term: &str, joined: &str) -> Option<f64> {
+    if field == term || field == joined {
+        Some(TIER_EXACT)
+    } else if field.starts_with(term) || field.starts_with(joined) {
+        Some(TIER_PREFIX)
+    } else if field.contains(term) || field.contains(joined) {
+        Some(TIER_SUBSTRING)
+    } else {
+        None
+    }
+}
+
+/// Source signals, worth at most 600 points.
+fn source_bonus(package: &PackageInfo) -> f64 {
+    let mut bonus = 0.0;
+
+    if package.installed_version.is_some() {
+        bonus += 300.0;
+    }
+
+    bonus += match package.source {
+        Source::Pacman => 300.0,
+        Source::Flatpak => 150.0,
+        Source::Aur => {
+            // AUR popularity is a decaying vote count, mostly between 0 and 50
+            let popularity = package.popularity.unwrap_or(0.0).max(0.0);
+            let mut aur = (popularity + 1.0).ln() * 50.0;
+            if package.out_of_date.is_some() {
+                aur -= 100.0;
+            }
+            aur.clamp(0.0, 200.0)
+        }
+    };
+
+    bonus
+}
+
+/// Up to 100 points for names barely longer than the term.
+fn length_bonus(name: &str, term: &str) -> f64 {
+    let extra = name.len().saturating_sub(term.len()) as f64;
+    (100.0 - extra * 4.0).max(0.0)
+}
+
+/// Set the relevance score of every package.
+pub fn score_results(results: &mut [SourceResults], term: &str) {
+    for package in results.iter_mut().flat_map(|r| r.packages.iter_mut()) {
+        package.score = Some(relevance(package, term));
+    }
+}
+
+/// Sort the packages within each source.
+pub fn sort_results(results: &mut [SourceResults], order: SortOrder) {
+    for r in results {
+        sort_packages(&mut r.packages, order);
+    }
+}
+
+/// Keep the first `limit` packages of each source.
+pub fn limit_results(results: &mut [SourceResults], limit: usize) {
+    for r in results {
+        r.packages.truncate(limit);
+    }
+}
+
+/// Sort packages by `order`, using scores set by [`score_results`].
+///
+/// Within a single source, [`SortOrder::Source`] is the same as relevance.
+pub fn sort_packages(packages: &mut [PackageInfo], order: SortOrder) {
+    match order {
+        SortOrder::Relevance | SortOrder::Source => packages.sort_by(by_score),
+        SortOrder::Name => packages.sort_by(|a, b| {
+            a.name
+                .to_lowercase()
+                .cmp(&b.name.to_lowercase())
+                .then_with(|| a.name.cmp(&b.name))
+        }),
+        SortOrder::Popularity => packages.sort_by(|a, b| {
+            match (a.popularity, b.popularity) {
+                (Some(a), Some(b)) => b.total_cmp(&a),
+                (Some(_), None) => Ordering::Less,
+                (None, Some(_)) => Ordering::Greater,
+                (None, None) => Ordering::Equal,
+            }
+            .then_with(|| by_score(a, b))
+        }),
+    }
+}
+
+/// Interleave every source into one list ordered by `order`.
+pub fn merge_results(results: &[SourceResults], order: SortOrder) -> Vec<PackageInfo> {
+    let mut merged: Vec<PackageInfo> = results.iter().flat_map(|r| r.packages.clone()).collect();
+    if order != SortOrder::Source {
+        sort_packages(&mut merged, order);
+    }
+    merged
+}
+
+fn by_score(a: &PackageInfo, b: &PackageInfo) -> Ordering {
+    b.score.unwrap_or(0.0).total_cmp(&a.score.unwrap_or(0.0))
+}
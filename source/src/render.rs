@@ -0,0 +1,1134 @@
+use std::env;
+use std::fmt;
+use std::io::{self, IsTerminal, Write};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::Duration;
+
+use serde::ser::{SerializeMap, Serializer};
+use serde::{Deserialize, Serialize};
+use tokio::sync::mpsc::UnboundedReceiver;
+use tokio::time::MissedTickBehavior;
+
+use crate::date::format_date;
+use crate::group::{group_packages, AppGroup};
+use crate::package::{FlatpakKind, PackageInfo, Release, Source};
+use crate::progress::Spinner;
+use crate::rank::{merge_results, SortOrder};
+use crate::sandbox::Permission;
+use crate::search::SourceResults;
+
+// ANSI color codes as constants
+pub const BOLD: Ansi = Ansi("\x1B[1m");
+pub const BLUE: Ansi = Ansi("\x1B[34m");
+pub const RED: Ansi = Ansi("\x1B[31m");
+pub const GREEN: Ansi = Ansi("\x1B[32m");
+pub const YELLOW: Ansi = Ansi("\x1B[33m");
+pub const RESET: Ansi = Ansi("\x1B[0m");
+
+/// Whether escape sequences are written, see [`set_color`].
+static COLOR: AtomicBool = AtomicBool::new(true);
+
+/// An ANSI escape sequence, formatted as nothing while colors are off.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Ansi(&'static str);
+
+impl Ansi {
+    /// The escape sequence, whether or not colors are on.
+    pub fn code(self) -> &'static str {
+        self.0
+    }
+}
+
+impl fmt::Display for Ansi {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if color_enabled() {
+            f.write_str(self.0)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+/// Turn colors on or off for everything printed from now on.
+pub fn set_color(enabled: bool) {
+    COLOR.store(enabled, Ordering::Relaxed);
+}
+
+pub fn color_enabled() -> bool {
+    COLOR.load(Ordering::Relaxed)
+}
+
+/// When to color the output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorChoice {
+    /// Only when writing to a terminal and `NO_COLOR` is unset.
+    #[default]
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    /// Whether to color output going to a stream that is a `terminal` or not.
+    pub fn enabled(self, terminal: bool) -> bool {
+        match self {
+            ColorChoice::Auto => terminal && env::var_os("NO_COLOR").is_none_or(|v| v.is_empty()),
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        }
+    }
+}
+
+impl fmt::Display for ColorChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ColorChoice::Auto => "auto",
+            ColorChoice::Always => "always",
+            ColorChoice::Never => "never",
+        })
+    }
+}
+
+impl FromStr for ColorChoice {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            other => Err(format!(
+                "unknown color mode '{}' (expected auto, always or never)",
+                other
+            )),
+        }
+    }
+}
+
+/// How often the spinner advances while results stream in.
+const SPINNER_INTERVAL: Duration = Duration::from_millis(80);
+
+/// How search results are written to stdout.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OutputFormat {
+    /// Colored sections for a human, paged through `less` when long.
+    #[default]
+    Text,
+    /// One JSON document mapping each source id to its packages.
+    Json,
+    /// One JSON-encoded [`PackageInfo`] per line.
+    Ndjson,
+}
+
+impl fmt::Display for OutputFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            OutputFormat::Text => "text",
+            OutputFormat::Json => "json",
+            OutputFormat::Ndjson => "ndjson",
+        })
+    }
+}
+
+impl FromStr for OutputFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(OutputFormat::Text),
+            "json" => Ok(OutputFormat::Json),
+            "ndjson" => Ok(OutputFormat::Ndjson),
+            other => Err(format!(
+                "unknown output format '{}' (expected text, json or ndjson)",
+                other
+            )),
+        }
+    }
+}
+
+/// ANSI escape sequences for each source's package names.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Theme {
+    pub pacman: String,
+    pub aur: String,
+    pub flatpak: String,
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Theme {
+            pacman: BLUE.code().to_string(),
+            aur: RED.code().to_string(),
+            flatpak: GREEN.code().to_string(),
+        }
+    }
+}
+
+impl Theme {
+    /// The color used for a source's package names, empty while colors
+    /// are off.
+    pub fn color(&self, source: Source) -> &str {
+        if !color_enabled() {
+            return "";
+        }
+        match source {
+            Source::Pacman => &self.pacman,
+            Source::Aur => &self.aur,
+            Source::Flatpak => &self.flatpak,
+        }
+    }
+}
+
+/// Translate a color name into its ANSI escape sequence.
+///
+/// Accepts the eight basic colors (`red`, `blue`, ...), their `bright-`
+/// variants, a 256-color palette index such as `208`, and `none` for no
+/// color.
+pub fn ansi_color(name: &str) -> Result<String, String> {
+    const NAMES: [&str; 8] = [
+        "black", "red", "green", "yellow", "blue", "magenta", "cyan", "white",
+    ];
+
+    let name = name.trim().to_lowercase();
+    if name.is_empty() || name == "none" {
+        return Ok(String::new());
+    }
+    if let Ok(index) = name.parse::<u8>() {
+        return Ok(format!("\x1B[38;5;{}m", index));
+    }
+
+    let (base, name) = match name.strip_prefix("bright-") {
+        Some(name) => (90, name),
+        None => (30, name.as_str()),
+    };
+    NAMES
+        .iter()
+        .position(|&known| known == name)
+        .map(|offset| format!("\x1B[{}m", base + offset))
+        .ok_or_else(|| format!("unknown color '{}'", name))
+}
+
+/// Presentation options shared by every output format.
+#[derive(Clone, Debug, Default)]
+pub struct RenderOptions {
+    /// Show one list interleaving every source instead of a section per
+    /// source. JSON output stays grouped by source.
+    pub merged: bool,
+    /// Show one entry per application with the packages providing it in
+    /// every source as its flavors, see [`crate::group`]. Takes precedence
+    /// over `merged`; JSON output stays grouped by source.
+    pub grouped: bool,
+    /// Order of the merged list and of the grouped apps.
+    pub sort: SortOrder,
+    /// Most packages in the merged list, or apps in the grouped one. Each
+    /// source's own results are limited with [`crate::rank::limit_results`]
+    /// before rendering.
+    pub limit: Option<usize>,
+    pub theme: Theme,
+    /// Pager command and arguments; empty to always print directly.
+    pub pager: Vec<String>,
+    /// Print text and NDJSON results as each source finishes, see
+    /// [`stream_results`].
+    pub stream: bool,
+}
+
+/// Print results in the requested format.
+///
+/// The machine-readable formats never use colors or the pager.
+pub fn print_results(
+    results: &[SourceResults],
+    format: OutputFormat,
+    options: &RenderOptions,
+) -> io::Result<()> {
+    match format {
+        OutputFormat::Text => {
+            print_results_with_pager(results, options);
+            Ok(())
+        }
+        OutputFormat::Json => {
+            let mut stdout = io::stdout().lock();
+            write_json(&mut stdout, results)?;
+            stdout.write_all(b"\n")
+        }
+        OutputFormat::Ndjson if options.grouped => {
+            let mut stdout = io::stdout().lock();
+            for group in grouped_results(results, options) {
+                serde_json::to_writer(&mut stdout, &group)?;
+                stdout.write_all(b"\n")?;
+            }
+            stdout.flush()
+        }
+        OutputFormat::Ndjson if options.merged => {
+            write_ndjson(&mut io::stdout().lock(), &merged_results(results, options))
+        }
+        OutputFormat::Ndjson => {
+            let packages: Vec<&PackageInfo> = results.iter().flat_map(|r| &r.packages).collect();
+            write_ndjson(&mut io::stdout().lock(), packages)
+        }
+    }
+}
+
+/// Print results as backends finish and return them all in the order they
+/// were printed, see [`numbered_packages`].
+///
+/// `prepare` runs on each source's results as they arrive, before anything
+/// is printed. With [`RenderOptions::stream`] set, text output prints a
+/// section per source as soon as it arrives, with a spinner for the sources
+/// still searching and the summary line last, and NDJSON records are written
+/// as they arrive; the results are returned in that order. On a terminal the
+/// sections go through the pager, started up front so it shows them as they
+/// arrive. JSON and the merged and grouped views need every result first
+/// and are printed at the end in backend order, like everything else
+/// without streaming.
+pub async fn stream_results(
+    mut stream: UnboundedReceiver<(usize, SourceResults)>,
+    names: &[String],
+    format: OutputFormat,
+    options: &RenderOptions,
+    mut prepare: impl FnMut(&mut SourceResults),
+) -> io::Result<Vec<SourceResults>> {
+    let progressive = options.stream && !options.merged && !options.grouped;
+    let mut pager = match format {
+        OutputFormat::Text if progressive && io::stdout().is_terminal() => {
+            spawn_pager(&options.pager)
+        }
+        _ => None,
+    };
+    // The spinner would draw over the pager
+    let mut spinner = match format {
+        OutputFormat::Text if pager.is_none() => Some(Spinner::new(names)),
+        _ => None,
+    };
+    let mut ticker = tokio::time::interval(SPINNER_INTERVAL);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+    let mut arrived = Vec::with_capacity(names.len());
+    let mut number = 1;
+    loop {
+        let (index, mut results) = tokio::select! {
+            finished = stream.recv() => match finished {
+                Some(finished) => finished,
+                None => break,
+            },
+            _ = ticker.tick() => {
+                if let Some(spinner) = &mut spinner {
+                    spinner.tick();
+                }
+                continue;
+            }
+        };
+        prepare(&mut results);
+
+        if let Some(spinner) = &mut spinner {
+            spinner.finish(index);
+            spinner.clear();
+        }
+        // Text output reports failures on the summary line instead
+        if format != OutputFormat::Text {
+            warn_failure(&results);
+        }
+        if progressive {
+            let mut stdout = io::stdout().lock();
+            match format {
+                OutputFormat::Text => {
+                    let mut section = String::new();
+                    push_category_results(
+                        &mut section,
+                        &results.name,
+                        &results.packages,
+                        number,
+                        false,
+                        &options.theme,
+                    );
+                    number += results.packages.len();
+                    match &mut pager {
+                        Some(pager) => pager.write(&section),
+                        None => {
+                            stdout.write_all(section.as_bytes())?;
+                            stdout.flush()?;
+                        }
+                    }
+                }
+                OutputFormat::Ndjson => write_ndjson(&mut stdout, &results.packages)?,
+                OutputFormat::Json => {}
+            }
+        }
+        if let Some(spinner) = &mut spinner {
+            spinner.draw();
+        }
+
+        arrived.push((index, results));
+    }
+    drop(spinner);
+
+    // Streamed sections were numbered as they arrived
+    if !progressive || format == OutputFormat::Json {
+        arrived.sort_by_key(|(index, _)| *index);
+    }
+    let results: Vec<SourceResults> = arrived.into_iter().map(|(_, r)| r).collect();
+
+    match format {
+        OutputFormat::Text if progressive => {
+            // The summary line keeps backend order
+            let mut summary: Vec<&SourceResults> = results.iter().collect();
+            summary.sort_by_key(|r| names.iter().position(|name| *name == r.name));
+            let summary = format_summary(summary);
+            match pager {
+                Some(mut pager) => {
+                    pager.write(&format!("{}\n", summary));
+                    pager.wait();
+                }
+                None => println!("{}", summary),
+            }
+        }
+        OutputFormat::Ndjson if progressive => {}
+        _ => print_results(&results, format, options)?,
+    }
+    Ok(results)
+}
+
+/// Wait for every backend while showing a spinner, and return the results in
+/// backend order.
+pub async fn collect_results(
+    mut stream: UnboundedReceiver<(usize, SourceResults)>,
+    names: &[String],
+) -> Vec<SourceResults> {
+    let mut spinner = Spinner::new(names);
+    let mut ticker = tokio::time::interval(SPINNER_INTERVAL);
+    ticker.set_missed_tick_behavior(MissedTickBehavior::Skip);
+
+    let mut arrived = Vec::with_capacity(names.len());
+    loop {
+        tokio::select! {
+            finished = stream.recv() => match finished {
+                Some((index, results)) => {
+                    spinner.finish(index);
+                    spinner.clear();
+                    warn_failure(&results);
+                    spinner.draw();
+                    arrived.push((index, results));
+                }
+                None => break,
+            },
+            _ = ticker.tick() => spinner.tick(),
+        }
+    }
+
+    arrived.sort_by_key(|(index, _)| *index);
+    arrived.into_iter().map(|(_, r)| r).collect()
+}
+
+/// Print a warning on stderr if the backend behind `results` failed.
+pub fn warn_failure(results: &SourceResults) {
+    if let Some(error) = &results.error {
+        eprintln!(
+            "{}Warning:{} {} search failed: {}",
+            RED, RESET, results.name, error
+        );
+    }
+}
+
+/// Write results as a single JSON object keyed by source id, in backend order.
+pub fn write_json<W: Write>(writer: W, results: &[SourceResults]) -> io::Result<()> {
+    serde_json::to_writer_pretty(writer, &GroupedResults(results)).map_err(io::Error::from)
+}
+
+/// Write every package as one JSON object per line.
+pub fn write_ndjson<'a, W, I>(mut writer: W, packages: I) -> io::Result<()>
+where
+    W: Write,
+    I: IntoIterator<Item = &'a PackageInfo>,
+{
+    for package in packages {
+        serde_json::to_writer(&mut writer, package)?;
+        writer.write_all(b"\n")?;
+    }
+    writer.flush()
+}
+
+struct GroupedResults<'a>(&'a [SourceResults]);
+
+impl Serialize for GroupedResults<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for r in self.0 {
+            map.serialize_entry(r.source.id(), &r.packages)?;
+        }
+        map.end()
+    }
+}
+
+/// Format results as colored text: a summary line, then one section per
+/// source, a single merged section or the grouped apps.
+pub fn format_results(results: &[SourceResults], options: &RenderOptions) -> String {
+    // Pre-allocate string buffer with approximate capacity
+    let package_count: usize = results.iter().map(|r| r.packages.len()).sum();
+    let mut output = String::with_capacity(package_count * 150); // ~150 chars per package
+
+    output.push_str(&format_summary(results));
+    output.push_str("\n\n");
+
+    if options.grouped {
+        push_group_results(
+            &mut output,
+            &grouped_results(results, options),
+            &options.theme,
+        );
+    } else if options.merged {
+        let merged = merged_results(results, options);
+        push_category_results(&mut output, "All", &merged, 1, true, &options.theme);
+    } else {
+        let mut number = 1;
+        for r in results {
+            push_category_results(
+                &mut output,
+                &r.name,
+                &r.packages,
+                number,
+                false,
+                &options.theme,
+            );
+            number += r.packages.len();
+        }
+    }
+
+    output
+}
+
+/// The packages in the order text output numbers them, for
+/// `pd install <number>`: the flavors of every grouped app, the merged list,
+/// or every source's results in the order of `results`.
+pub fn numbered_packages(results: &[SourceResults], options: &RenderOptions) -> Vec<PackageInfo> {
+    if options.grouped {
+        grouped_results(results, options)
+            .into_iter()
+            .flat_map(|group| group.flavors)
+            .collect()
+    } else if options.merged {
+        merged_results(results, options)
+    } else {
+        results.iter().flat_map(|r| r.packages.clone()).collect()
+    }
+}
+
+/// Every source's results in one list, cut to [`RenderOptions::limit`].
+fn merged_results(results: &[SourceResults], options: &RenderOptions) -> Vec<PackageInfo> {
+    let mut merged = merge_results(results, options.sort);
+    if let Some(limit) = options.limit {
+        merged.truncate(limit);
+    }
+    merged
+}
+
+/// Every source's results grouped by application, cut to
+/// [`RenderOptions::limit`] apps.
+fn grouped_results(results: &[SourceResults], options: &RenderOptions) -> Vec<AppGroup> {
+    let mut groups = group_packages(merge_results(results, options.sort));
+    if let Some(limit) = options.limit {
+        groups.truncate(limit);
+    }
+    groups
+}
+
+/// The summary line: the number of results per source, or why a source
+/// failed.
+fn format_summary<'a>(results: impl IntoIterator<Item = &'a SourceResults>) -> String {
+    let summary: Vec<String> = results
+        .into_iter()
+        .map(|r| match &r.error {
+            Some(error) => format!(
+                "{}{}:{} {}failed{} ({})",
+                BOLD, r.name, RESET, RED, RESET, error
+            ),
+            None => format!(
+                "{}{}:{} {}",
+                BOLD,
+                r.name,
+                RESET,
+                format_package_count(r.packages.len())
+            ),
+        })
+        .collect();
+    summary.join(" | ")
+}
+
+fn format_package_count(count: usize) -> String {
+    if count == 1 {
+        "1 package".to_string()
+    } else {
+        format!("{} packages", count)
+    }
+}
+
+/// Push a section listing `results`, numbered from `first_number`.
+fn push_category_results(
+    output: &mut String,
+    category_name: &str,
+    results: &[PackageInfo],
+    first_number: usize,
+    tag_source: bool,
+    theme: &Theme,
+) {
+    if results.is_empty() {
+        return;
+    }
+
+    output.push_str(&format!("{}{} Results:{}\n", BOLD, category_name, RESET));
+    output.push_str(&format!("{}\n", "=".repeat(category_name.len() + 9)));
+    for (number, package) in (first_number..).zip(results) {
+        let tag = if tag_source {
+            format!(" ({})", package.source.label())
+        } else {
+            String::new()
+        };
+        output.push_str(&format!(
+            "{}{}.{} {}{}{}{}{}{}\n",
+            BOLD,
+            number,
+            RESET,
+            BOLD,
+            theme.color(package.source),
+            package.name,
+            RESET,
+            tag,
+            installed_marker(package)
+        ));
+        output.push_str(&format!("  {}\n", package.description));
+        output.push_str(&format!(
+            "  {}Version:{} {}\n",
+            BOLD, RESET, package.version
+        ));
+        push_repo_details(output, package);
+        push_file_details(output, package);
+        push_install_details(output, package);
+        push_aur_details(output, package);
+        output.push('\n');
+    }
+}
+
+/// Push a section listing `groups`, one app per entry with a line per
+/// flavor: its number, name, version, origin and size.
+fn push_group_results(output: &mut String, groups: &[AppGroup], theme: &Theme) {
+    if groups.is_empty() {
+        return;
+    }
+
+    output.push_str(&format!("{}App Results:{}\n", BOLD, RESET));
+    output.push_str(&format!("{}\n", "=".repeat(12)));
+    let mut number = 1;
+    for group in groups {
+        output.push_str(&format!("{}{}{}\n", BOLD, group.name, RESET));
+        output.push_str(&format!("  {}\n", group.description));
+
+        let rows: Vec<[String; 3]> = group
+            .flavors
+            .iter()
+            .map(|p| [p.name.clone(), p.version.clone(), info_origin(p)])
+            .collect();
+        let width = |column: usize| {
+            rows.iter()
+                .map(|row| row[column].chars().count())
+                .max()
+                .unwrap_or(0)
+        };
+        let (name_width, version_width, origin_width) = (width(0), width(1), width(2));
+
+        for (package, [name, version, origin]) in group.flavors.iter().zip(&rows) {
+            let size = package
+                .installed_size
+                .or(package.compressed_size)
+                .map(format_size)
+                .unwrap_or_default();
+            let line = format!(
+                "  {}{}.{} {}{}{:<name_width$}{}  {:<version_width$}  {:<origin_width$}  {}",
+                BOLD,
+                number,
+                RESET,
+                BOLD,
+                theme.color(package.source),
+                name,
+                RESET,
+                version,
+                origin,
+                size,
+            );
+            output.push_str(line.trim_end());
+            output.push_str(&installed_marker(package));
+            output.push('\n');
+            number += 1;
+        }
+        output.push('\n');
+    }
+}
+
+/// The installed state, with the Flatpak installations, e.g.
+/// `[installed, user]`.
+fn installed_marker(package: &PackageInfo) -> String {
+    let installations = if package.installations.is_empty() {
+        String::new()
+    } else {
+        format!(", {}", package.installations.join(", "))
+    };
+    match &package.installed_version {
+        Some(version) if package.is_upgradable() => format!(
+            " {}[installed {} → available {}{}]{}",
+            YELLOW, version, package.version, installations, RESET
+        ),
+        Some(version) if *version == package.version => {
+            format!(" {}[installed{}]{}", GREEN, installations, RESET)
+        }
+        Some(version) => format!(
+            " {}[installed: {}{}]{}",
+            GREEN, version, installations, RESET
+        ),
+        None => String::new(),
+    }
+}
+
+fn push_repo_details(output: &mut String, package: &PackageInfo) {
+    let mut details = Vec::new();
+    // "aur" is already implied by the section
+    if let Some(repo) = package.repo.as_deref().filter(|repo| *repo != "aur") {
+        details.push(format!("{}Repo:{} {}", BOLD, RESET, repo));
+    }
+    if let Some(branch) = &package.branch {
+        details.push(format!("{}Branch:{} {}", BOLD, RESET, branch));
+    }
+    // Applications are what Flatpak results usually are
+    if let Some(kind) = package.kind.filter(|kind| *kind != FlatpakKind::App) {
+        details.push(format!("{}Kind:{} {}", BOLD, RESET, kind.label()));
+    }
+    if !package.groups.is_empty() {
+        details.push(format!(
+            "{}Groups:{} {}",
+            BOLD,
+            RESET,
+            package.groups.join(" ")
+        ));
+    }
+    if !details.is_empty() {
+        output.push_str(&format!("  {}\n", details.join(" | ")));
+    }
+}
+
+fn push_file_details(output: &mut String, package: &PackageInfo) {
+    for file in &package.matched_files {
+        output.push_str(&format!("  {}File:{} {}\n", BOLD, RESET, file));
+    }
+}
+
+fn push_install_details(output: &mut String, package: &PackageInfo) {
+    let mut details = Vec::new();
+    if let Some(date) = package.install_date {
+        details.push(format!("{}Installed:{} {}", BOLD, RESET, format_date(date)));
+    }
+    if let Some(reason) = package.install_reason {
+        details.push(format!("{}Reason:{} {}", BOLD, RESET, reason.label()));
+    }
+    // Available packages carry a size too, but only the installed one matters here
+    if let Some(size) = package
+        .installed_size
+        .filter(|_| package.install_reason.is_some())
+    {
+        details.push(format!("{}Size:{} {}", BOLD, RESET, format_size(size)));
+    }
+    if !details.is_empty() {
+        output.push_str(&format!("  {}\n", details.join(" | ")));
+    }
+}
+
+/// Format a byte count with binary units, e.g. `12.3 MiB`.
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KiB", "MiB", "GiB", "TiB"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{} B", bytes)
+    } else {
+        format!("{:.1} {}", size, UNITS[unit])
+    }
+}
+
+/// The maintainer of an AUR package, or `orphan`. AUR helper output only
+/// tells orphans apart.
+fn aur_maintainer(package: &PackageInfo) -> Option<&str> {
+    package
+        .maintainer
+        .as_deref()
+        .or(package.orphaned.then_some("orphan"))
+}
+
+fn push_aur_details(output: &mut String, package: &PackageInfo) {
+    let mut details = Vec::new();
+    if let Some(votes) = package.votes {
+        details.push(format!("{}Votes:{} {}", BOLD, RESET, votes));
+    }
+    if let Some(popularity) = package.popularity {
+        details.push(format!("{}Popularity:{} {:.2}", BOLD, RESET, popularity));
+    }
+    if let Some(maintainer) = aur_maintainer(package) {
+        details.push(format!("{}Maintainer:{} {}", BOLD, RESET, maintainer));
+    }
+    if let Some(last_modified) = package.last_modified {
+        details.push(format!(
+            "{}Updated:{} {}",
+            BOLD,
+            RESET,
+            format_date(last_modified)
+        ));
+    }
+    if !details.is_empty() {
+        output.push_str(&format!("  {}\n", details.join(" | ")));
+    }
+
+    match package.out_of_date {
+        Some(0) => output.push_str(&format!("  {}{}Out of date{}\n", BOLD, RED, RESET)),
+        Some(flagged) => output.push_str(&format!(
+            "  {}{}Out of date{} since {}\n",
+            BOLD,
+            RED,
+            RESET,
+            format_date(flagged)
+        )),
+        None => {}
+    }
+}
+
+/// Print results, going through the pager when they don't fit the terminal.
+pub fn print_results_with_pager(results: &[SourceResults], options: &RenderOptions) {
+    page(&format_results(results, options), &options.pager);
+}
+
+/// Print `output` through `pager` when it doesn't fit the terminal.
+fn page(output: &str, pager: &[String]) {
+    // Get terminal height for better pager decisioning
+    let term_height = terminal_size().map_or(24, |(height, _)| height);
+
+    // Check if we should use pager based on output size and terminal height
+    let output_lines = output.lines().count();
+    let use_pager = output_lines > term_height.saturating_sub(2);
+
+    match pager.split_first() {
+        Some((command, args)) if use_pager && command_available(command) => {
+            let mut pager = Command::new(command)
+                .args(args)
+                .stdin(Stdio::piped())
+                .spawn()
+                .expect("Failed to start pager");
+
+            if let Some(mut pager_stdin) = pager.stdin.take() {
+                // The user may quit the pager before reading everything
+                let _ = pager_stdin.write_all(output.as_bytes());
+            }
+
+            pager.wait().expect("Pager process wasn't running");
+        }
+        // Print directly for small outputs or when the pager is missing
+        _ => println!("{}", output),
+    }
+}
+
+/// A pager reading output as it is written, for streamed results.
+struct StreamPager {
+    child: Child,
+    stdin: Option<ChildStdin>,
+}
+
+impl StreamPager {
+    fn write(&mut self, text: &str) {
+        if let Some(stdin) = &mut self.stdin {
+            // The user may quit the pager before everything arrived
+            if stdin
+                .write_all(text.as_bytes())
+                .and_then(|_| stdin.flush())
+                .is_err()
+            {
+                self.stdin = None;
+            }
+        }
+    }
+
+    /// Close the pager's input and wait for the user to quit it.
+    fn wait(mut self) {
+        drop(self.stdin.take());
+        let _ = self.child.wait();
+    }
+}
+
+/// Start `pager` with its input piped, if it is set and installed.
+fn spawn_pager(pager: &[String]) -> Option<StreamPager> {
+    let (command, args) = pager.split_first()?;
+    if !command_available(command) {
+        return None;
+    }
+    let mut child = Command::new(command)
+        .args(args)
+        .stdin(Stdio::piped())
+        .spawn()
+        .ok()?;
+    let stdin = child.stdin.take();
+    Some(StreamPager { child, stdin })
+}
+
+fn command_available(command: &str) -> bool {
+    Command::new("which")
+        .arg(command)
+        .stdout(Stdio::null())
+        .stderr(Stdio::null())
+        .status()
+        .map(|status| status.success())
+        .unwrap_or(false)
+}
+
+/// The terminal's rows and columns.
+fn terminal_size() -> Option<(usize, usize)> {
+    // Try to get terminal size using stty
+    let output = Command::new("stty")
+        .arg("size")
+        .stdin(Stdio::inherit())
+        .stderr(Stdio::null())
+        .output()
+        .ok()?;
+
+    let size = String::from_utf8_lossy(&output.stdout);
+    let mut size = size.split_whitespace().map(|n| n.parse::<usize>().ok());
+    Some((size.next()??, size.next()??))
+}
+
+/// Width of the label column of the info card.
+const INFO_LABEL_WIDTH: usize = 16;
+/// The info card's columns never get narrower than this.
+const MIN_INFO_COLUMN_WIDTH: usize = 24;
+const INFO_COLUMN_GAP: usize = 2;
+
+/// A row of the info card and how to get its value from a package.
+type InfoField = (&'static str, fn(&PackageInfo) -> Option<String>);
+
+const INFO_FIELDS: &[InfoField] = &[
+    ("Version", |p| Some(p.version.clone())),
+    ("Installed", |p| p.installed_version.clone()),
+    ("Installed In", |p| info_list(&p.installations)),
+    ("Description", |p| Some(p.description.clone())),
+    ("About", |p| p.long_description.clone()),
+    ("Application ID", |p| p.app_id.clone()),
+    ("Branch", |p| p.branch.clone()),
+    ("Arch", |p| p.arch.clone()),
+    ("Kind", |p| p.kind.map(|kind| kind.label().to_string())),
+    ("Developer", |p| p.developer.clone()),
+    ("Categories", |p| info_list(&p.categories)),
+    ("URL", |p| p.url.clone()),
+    ("Licenses", |p| info_list(&p.licenses)),
+    ("Content Rating", |p| info_list(&p.content_rating)),
+    ("Releases", |p| info_releases(&p.releases)),
+    ("Icon", |p| p.icons.first().cloned()),
+    ("Warning", |p| info_permission(p, Permission::Warning)),
+    ("Filesystem", |p| info_permission(p, Permission::Filesystem)),
+    ("Network", |p| info_permission(p, Permission::Network)),
+    ("Display", |p| info_permission(p, Permission::Display)),
+    ("Sockets", |p| info_permission(p, Permission::Sockets)),
+    ("Devices", |p| info_permission(p, Permission::Devices)),
+    ("D-Bus Talk", |p| info_permission(p, Permission::DBusTalk)),
+    ("D-Bus Own", |p| info_permission(p, Permission::DBusOwn)),
+    ("Groups", |p| info_list(&p.groups)),
+    ("Provides", |p| info_list(&p.provides)),
+    ("Depends On", |p| info_list(&p.depends)),
+    ("Optional Deps", |p| info_list(&p.opt_depends)),
+    ("Make Deps", |p| info_list(&p.make_depends)),
+    ("Conflicts With", |p| info_list(&p.conflicts)),
+    ("Replaces", |p| info_list(&p.replaces)),
+    ("Download Size", |p| p.compressed_size.map(format_size)),
+    ("Installed Size", |p| p.installed_size.map(format_size)),
+    ("Packager", |p| p.packager.clone()),
+    ("Maintainer", |p| aur_maintainer(p).map(str::to_string)),
+    ("Build Date", |p| p.build_date.map(format_date)),
+    ("Last Updated", |p| p.last_modified.map(format_date)),
+    ("Votes", |p| p.votes.map(|votes| votes.to_string())),
+    ("Popularity", |p| p.popularity.map(|p| format!("{:.2}", p))),
+    ("Out Of Date", |p| match p.out_of_date {
+        Some(0) => Some("yes".to_string()),
+        Some(flagged) => Some(format!("since {}", format_date(flagged))),
+        None => None,
+    }),
+];
+
+fn info_list(values: &[String]) -> Option<String> {
+    (!values.is_empty()).then(|| values.join("  "))
+}
+
+/// One kind of access in the summary of a Flatpak's sandbox permissions.
+fn info_permission(package: &PackageInfo, kind: Permission) -> Option<String> {
+    package.permissions.as_ref()?.get(kind)
+}
+
+/// The latest releases, e.g. `131.0 (2024-10-01)  130.0 (2024-09-03)`.
+pub fn info_releases(releases: &[Release]) -> Option<String> {
+    const SHOWN: usize = 3;
+
+    let shown: Vec<String> = releases
+        .iter()
+        .take(SHOWN)
+        .map(|release| match release.date {
+            Some(date) => format!("{} ({})", release.version, format_date(date)),
+            None => release.version.clone(),
+        })
+        .collect();
+    info_list(&shown)
+}
+
+/// Where a package comes from, e.g. `Pacman (extra)`.
+fn info_origin(package: &PackageInfo) -> String {
+    match package.repo.as_deref().filter(|repo| *repo != "aur") {
+        Some(repo) => format!("{} ({})", package.source.label(), repo),
+        None => package.source.label().to_string(),
+    }
+}
+
+/// Format the details of `packages` as one card, with a column per package
+/// so the same name from several sources can be compared side by side.
+///
+/// Rows no package has a value for are left out. The columns share `width`
+/// terminal columns, but wrap rather than get narrower than
+/// [`MIN_INFO_COLUMN_WIDTH`].
+pub fn format_info(packages: &[PackageInfo], width: usize, theme: &Theme) -> String {
+    if packages.is_empty() {
+        return String::new();
+    }
+
+    let gaps = INFO_COLUMN_GAP * packages.len();
+    let column_width =
+        (width.saturating_sub(INFO_LABEL_WIDTH + gaps) / packages.len()).max(MIN_INFO_COLUMN_WIDTH);
+    let mut output = String::new();
+
+    // The header row names each column's package and source
+    let mut rows: Vec<(&str, Vec<String>, bool)> = vec![
+        (
+            "Name",
+            packages.iter().map(|p| p.name.clone()).collect(),
+            true,
+        ),
+        ("Source", packages.iter().map(info_origin).collect(), false),
+    ];
+    for (label, field) in INFO_FIELDS {
+        let values: Vec<Option<String>> = packages.iter().map(field).collect();
+        if values.iter().any(Option::is_some) {
+            let values = values
+                .into_iter()
+                .map(|value| value.unwrap_or_else(|| "None".to_string()))
+                .collect();
+            rows.push((label, values, false));
+        }
+    }
+
+    for (label, values, colored) in rows {
+        let cells: Vec<Vec<String>> = values
+            .iter()
+            .map(|value| wrap(value, column_width))
+            .collect();
+        let height = cells.iter().map(Vec::len).max().unwrap_or(1);
+
+        for line in 0..height {
+            let label = if line == 0 { label } else { "" };
+            let mut text = format!(
+                "{}{:<width$}{}",
+                BOLD,
+                label,
+                RESET,
+                width = INFO_LABEL_WIDTH
+            );
+            for (package, cell) in packages.iter().zip(&cells) {
+                let value = cell.get(line).map_or("", String::as_str);
+                let padding = " ".repeat(column_width.saturating_sub(value.chars().count()));
+                text.push_str(&" ".repeat(INFO_COLUMN_GAP));
+                if colored {
+                    text.push_str(&format!(
+                        "{}{}{}{}",
+                        BOLD,
+                        theme.color(package.source),
+                        value,
+                        RESET
+                    ));
+                } else {
+                    text.push_str(value);
+                }
+                text.push_str(&padding);
+            }
+            output.push_str(text.trim_end());
+            output.push('\n');
+        }
+    }
+
+    output
+}
+
+/// Split `text` into lines of at most `width` characters, breaking between
+/// words where possible.
+fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut line = String::new();
+
+    for word in text.split_whitespace() {
+        let mut word = word.to_string();
+        if !line.is_empty() && line.chars().count() + 1 + word.chars().count() > width {
+            lines.push(std::mem::take(&mut line));
+        }
+        // Break words that don't fit on a line of their own
+        while word.chars().count() > width {
+            let split = word
+                .char_indices()
+                .nth(width)
+                .map_or(word.len(), |(i, _)| i);
+            let rest = word.split_off(split);
+            lines.push(word);
+            word = rest;
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(&word);
+    }
+
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Print the details of each group of packages: an info card per group for
+/// text output, see [`format_info`], and every full record for the
+/// machine-readable formats.
+pub fn print_info(
+    groups: &[Vec<PackageInfo>],
+    format: OutputFormat,
+    options: &RenderOptions,
+) -> io::Result<()> {
+    match format {
+        OutputFormat::Text => {
+            let width = terminal_size().map_or(80, |(_, width)| width);
+            let cards: Vec<String> = groups
+                .iter()
+                .filter(|packages| !packages.is_empty())
+                .map(|packages| format_info(packages, width, &options.theme))
+                .collect();
+            if !cards.is_empty() {
+                page(&cards.join("\n"), &options.pager);
+            }
+            Ok(())
+        }
+        OutputFormat::Json => {
+            let mut stdout = io::stdout().lock();
+            serde_json::to_writer_pretty(&mut stdout, &groups.concat())?;
+            stdout.write_all(b"\n")
+        }
+        OutputFormat::Ndjson => write_ndjson(&mut io::stdout().lock(), groups.iter().flatten()),
+    }
+}
@@ -0,0 +1,214 @@
+//! Flatpak sandbox permissions.
+//!
+//! Every Flatpak ships a `metadata` key file declaring what its sandbox
+//! may reach, as `flatpak run` options without the dashes:
+//!
+//! ```text
+//! [Context]
+//! shared=network;ipc;
+//! sockets=x11;wayland;
+//! devices=dri;
+//! filesystems=host;xdg-config/kdeglobals:ro;
+//!
+//! [Session Bus Policy]
+//! org.freedesktop.Notifications=talk
+//! ```
+//!
+//! [`parse_metadata`] reads the permissions as shipped, without the local
+//! overrides of `flatpak override`.
+
+use serde::{Deserialize, Serialize};
+
+/// What a Flatpak's sandbox gives access to.
+#[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+pub struct Permissions {
+    /// Filesystem access, e.g. `host`, `xdg-download` or `~/Games:ro`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub filesystems: Vec<String>,
+    #[serde(default)]
+    pub network: bool,
+    /// Sockets, e.g. `x11`, `wayland` or `pulseaudio`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sockets: Vec<String>,
+    /// Devices, e.g. `dri` or `all`.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub devices: Vec<String>,
+    /// Session bus names the application may talk to.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub talk_names: Vec<String>,
+    /// Session bus names the application may own.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub own_names: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub system_talk_names: Vec<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub system_own_names: Vec<String>,
+}
+
+/// A kind of access in the [`Permissions::summary`], in the order it is
+/// listed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Permission {
+    /// Full access to the host filesystem, only listed when granted.
+    Warning,
+    Filesystem,
+    Network,
+    Display,
+    Sockets,
+    Devices,
+    DBusTalk,
+    DBusOwn,
+}
+
+impl Permission {
+    /// Every kind of access, in summary order.
+    pub const ALL: [Permission; 8] = [
+        Permission::Warning,
+        Permission::Filesystem,
+        Permission::Network,
+        Permission::Display,
+        Permission::Sockets,
+        Permission::Devices,
+        Permission::DBusTalk,
+        Permission::DBusOwn,
+    ];
+
+    /// The label the access is listed under.
+    pub fn label(self) -> &'static str {
+        match self {
+            Permission::Warning => "Warning",
+            Permission::Filesystem => "Filesystem",
+            Permission::Network => "Network",
+            Permission::Display => "Display",
+            Permission::Sockets => "Sockets",
+            Permission::Devices => "Devices",
+            Permission::DBusTalk => "D-Bus Talk",
+            Permission::DBusOwn => "D-Bus Own",
+        }
+    }
+}
+
+impl Permissions {
+    /// Whether the sandbox can read the whole host filesystem, as with
+    /// `--filesystem=host`.
+    pub fn has_host_filesystem(&self) -> bool {
+        self.filesystems
+            .iter()
+            .any(|fs| fs.split(':').next() == Some("host"))
+    }
+
+    /// Which display servers the application can use, e.g. `Wayland, X11
+    /// fallback`.
+    pub fn display(&self) -> &'static str {
+        let has = |socket: &str| self.sockets.iter().any(|s| s == socket);
+        match (has("wayland"), has("x11"), has("fallback-x11")) {
+            (true, true, _) => "X11 and Wayland",
+            (true, false, true) => "Wayland, X11 fallback",
+            (true, false, false) => "Wayland",
+            (false, true, _) | (false, false, true) => "X11",
+            (false, false, false) => "none",
+        }
+    }
+
+    /// One `(label, value)` line per kind of access, with a warning first
+    /// for host filesystem access.
+    pub fn summary(&self) -> Vec<(&'static str, String)> {
+        Permission::ALL
+            .into_iter()
+            .filter_map(|kind| Some((kind.label(), self.get(kind)?)))
+            .collect()
+    }
+
+    /// The summary line for one kind of access; `None` only for
+    /// [`Permission::Warning`] without host filesystem access.
+    pub fn get(&self, kind: Permission) -> Option<String> {
+        let list = |values: &[String]| {
+            if values.is_empty() {
+                "none".to_string()
+            } else {
+                values.join("  ")
+            }
+        };
+        let names = |session: &[String], system: &[String]| {
+            let mut names = session.to_vec();
+            names.extend(system.iter().map(|name| format!("{} (system)", name)));
+            list(&names)
+        };
+
+        let line = match kind {
+            Permission::Warning => {
+                if !self.has_host_filesystem() {
+                    return None;
+                }
+                "full access to the host filesystem (--filesystem=host)".to_string()
+            }
+            Permission::Filesystem => list(&self.filesystems),
+            Permission::Network => if self.network { "yes" } else { "no" }.to_string(),
+            Permission::Display => self.display().to_string(),
+            Permission::Sockets => {
+                let other_sockets: Vec<String> = self
+                    .sockets
+                    .iter()
+                    .filter(|s| !matches!(s.as_str(), "x11" | "wayland" | "fallback-x11"))
+                    .cloned()
+                    .collect();
+                list(&other_sockets)
+            }
+            Permission::Devices => list(&self.devices),
+            Permission::DBusTalk => names(&self.talk_names, &self.system_talk_names),
+            Permission::DBusOwn => names(&self.own_names, &self.system_own_names),
+        };
+        Some(line)
+    }
+}
+
+/// Parse a Flatpak `metadata` key file.
+///
+/// Negated entries such as `!host` are left out, since they only take away
+/// what a runtime or an override would grant.
+pub fn parse_metadata(text: &str) -> Permissions {
+    let mut permissions = Permissions::default();
+    let mut section = "";
+
+    for line in text.lines().map(str::trim) {
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        if let Some(name) = line.strip_prefix('[').and_then(|l| l.strip_suffix(']')) {
+            section = name;
+            continue;
+        }
+        let Some((key, value)) = line.split_once('=') else {
+            continue;
+        };
+        let (key, value) = (key.trim(), value.trim());
+        let list = || -> Vec<String> {
+            value
+                .split(';')
+                .map(str::trim)
+                .filter(|entry| !entry.is_empty() && !entry.starts_with('!'))
+                .map(str::to_string)
+                .collect()
+        };
+
+        match (section, key) {
+            ("Context", "filesystems") => permissions.filesystems = list(),
+            ("Context", "shared") => permissions.network = list().iter().any(|s| s == "network"),
+            ("Context", "sockets") => permissions.sockets = list(),
+            ("Context", "devices") => permissions.devices = list(),
+            ("Session Bus Policy", name) => match value {
+                "talk" => permissions.talk_names.push(name.to_string()),
+                "own" => permissions.own_names.push(name.to_string()),
+                _ => {}
+            },
+            ("System Bus Policy", name) => match value {
+                "talk" => permissions.system_talk_names.push(name.to_string()),
+                "own" => permissions.system_own_names.push(name.to_string()),
+                _ => {}
+            },
+            _ => {}
+        }
+    }
+
+    permissions
+}
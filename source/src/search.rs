@@ -0,0 +1,148 @@
+use std::panic::AssertUnwindSafe;
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use futures::FutureExt;
+use tokio::sync::mpsc;
+
+use crate::backend::{Backend, BackendError};
+use crate::package::{PackageInfo, Source};
+
+/// How long a single backend may take before its results are dropped, unless
+/// configured otherwise.
+pub const BACKEND_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The results of one backend.
+#[derive(Clone, Debug)]
+pub struct SourceResults {
+    pub name: String,
+    pub source: Source,
+    pub packages: Vec<PackageInfo>,
+    /// Why the backend failed, in which case `packages` is empty.
+    pub error: Option<BackendError>,
+}
+
+/// Run every backend concurrently and collect their results in backend order.
+///
+/// A backend that is unavailable, fails or exceeds [`BACKEND_TIMEOUT`]
+/// contributes no packages and the reason as its `error`. The search is
+/// cancelled at the deadline, killing any command it was running.
+pub async fn search_packages(backends: Vec<Arc<dyn Backend>>, term: &str) -> Vec<SourceResults> {
+    search_packages_with_timeout(backends, term, |_| BACKEND_TIMEOUT).await
+}
+
+/// Like [`search_packages`], with the time each backend may take given by
+/// `timeout`.
+pub async fn search_packages_with_timeout(
+    backends: Vec<Arc<dyn Backend>>,
+    term: &str,
+    timeout: impl Fn(Source) -> Duration,
+) -> Vec<SourceResults> {
+    let mut stream = search_stream(backends, term, timeout);
+
+    let mut results = Vec::new();
+    while let Some(finished) = stream.recv().await {
+        results.push(finished);
+    }
+    results.sort_by_key(|(index, _)| *index);
+    results.into_iter().map(|(_, r)| r).collect()
+}
+
+/// Start every backend concurrently and send each one's results, tagged with
+/// its index in `backends`, as soon as it finishes.
+///
+/// Every backend reports exactly once, after which the stream ends, and
+/// failures are reported as in [`search_packages`]. Must be called from within a Tokio
+/// runtime.
+pub fn search_stream(
+    backends: Vec<Arc<dyn Backend>>,
+    term: &str,
+    timeout: impl Fn(Source) -> Duration,
+) -> mpsc::UnboundedReceiver<(usize, SourceResults)> {
+    // Use a shared string to avoid cloning for each backend
+    let term: Arc<str> = Arc::from(term);
+    let (sender, receiver) = mpsc::unbounded_channel();
+
+    for (index, backend) in backends.into_iter().enumerate() {
+        let term = Arc::clone(&term);
+        let timeout = timeout(backend.source());
+        let sender = sender.clone();
+        tokio::spawn(async move {
+            let search = AssertUnwindSafe(run_backend(
+                backend.as_ref(),
+                || backend.search(&term),
+                timeout,
+            ));
+            let outcome = search
+                .catch_unwind()
+                .await
+                .unwrap_or_else(|_| Err(BackendError::Io("search panicked".to_string())));
+            let (packages, error) = match outcome {
+                Ok(packages) => (packages, None),
+                Err(e) => (Vec::new(), Some(e)),
+            };
+            let results = SourceResults {
+                name: backend.name().to_string(),
+                source: backend.source(),
+                packages,
+                error,
+            };
+            // The receiver may have stopped listening
+            let _ = sender.send((index, results));
+        });
+    }
+
+    receiver
+}
+
+/// Look up the packages called `name` in every backend concurrently, see
+/// [`Backend::info`], and collect the results in backend order.
+///
+/// Failures are reported as in [`search_packages`].
+pub async fn lookup_packages(
+    backends: &[Arc<dyn Backend>],
+    name: &str,
+    timeout: impl Fn(Source) -> Duration,
+) -> Vec<SourceResults> {
+    let lookups = backends.iter().map(|backend| {
+        let lookup = run_backend(
+            backend.as_ref(),
+            || backend.info(name),
+            timeout(backend.source()),
+        );
+        async move {
+            let (packages, error) = match lookup.await {
+                Ok(packages) => (packages, None),
+                Err(e) => (Vec::new(), Some(e)),
+            };
+            SourceResults {
+                name: backend.name().to_string(),
+                source: backend.source(),
+                packages,
+                error,
+            }
+        }
+    });
+
+    futures::future::join_all(lookups).await
+}
+
+/// Start `query` on `backend` once it is known to be available, giving up
+/// after `timeout`.
+async fn run_backend<'a>(
+    backend: &'a dyn Backend,
+    query: impl FnOnce() -> BoxFuture<'a, Result<Vec<PackageInfo>, BackendError>>,
+    timeout: Duration,
+) -> Result<Vec<PackageInfo>, BackendError> {
+    let search = async {
+        if !backend.is_available().await {
+            return Err(BackendError::NotInstalled);
+        }
+        query().await
+    };
+
+    tokio::time::timeout(timeout, search)
+        .await
+        .unwrap_or(Err(BackendError::Timeout(timeout)))
+}
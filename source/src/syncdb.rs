@@ -0,0 +1,231 @@
+//! Reader for pacman's sync databases.
+//!
+//! A sync database (`/var/lib/pacman/sync/<repo>.db`) is a tar archive,
+//! usually compressed with gzip or zstd, holding one `<name>-<version>/desc`
+//! file per package. Reading it directly avoids parsing the localized,
+//! human-oriented output of `pacman -Ss` and gives us every field pacman
+//! knows about.
+
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::io::{self, BufReader, Read};
+use std::path::{Component, Path, PathBuf};
+use std::str::FromStr;
+
+use crate::localdb::split_entry_name;
+use crate::package::{PackageInfo, Source};
+
+/// Where pacman keeps its sync databases.
+pub const DEFAULT_SYNC_DIR: &str = "/var/lib/pacman/sync";
+
+/// pacman's configuration file, used to order repositories.
+pub const PACMAN_CONF: &str = "/etc/pacman.conf";
+
+const GZIP_MAGIC: &[u8] = &[0x1f, 0x8b];
+const ZSTD_MAGIC: &[u8] = &[0x28, 0xb5, 0x2f, 0xfd];
+
+/// The `%KEY%` sections of a `desc` file.
+pub type Desc = HashMap<String, Vec<String>>;
+
+/// List the `.db` files in `dir`, ordered like the repositories in
+/// `pacman.conf` when it is readable and alphabetically otherwise.
+pub fn sync_databases(dir: &Path) -> io::Result<Vec<(String, PathBuf)>> {
+    databases(dir, "db")
+}
+
+/// List the `.files` databases `pacman -Fy` downloads to `dir`, ordered like
+/// [`sync_databases`].
+pub fn files_databases(dir: &Path) -> io::Result<Vec<(String, PathBuf)>> {
+    databases(dir, "files")
+}
+
+fn databases(dir: &Path, extension: &str) -> io::Result<Vec<(String, PathBuf)>> {
+    let mut databases = Vec::new();
+    for entry in fs::read_dir(dir)? {
+        let path = entry?.path();
+        if path.extension().is_some_and(|ext| ext == extension) {
+            if let Some(repo) = path.file_stem().and_then(|stem| stem.to_str()) {
+                databases.push((repo.to_string(), path.clone()));
+            }
+        }
+    }
+
+    let order = fs::read_to_string(PACMAN_CONF)
+        .map(|conf| repo_order(&conf))
+        .unwrap_or_default();
+    databases.sort_by_key(|(repo, _)| {
+        (
+            order.iter().position(|r| r == repo).unwrap_or(usize::MAX),
+            repo.clone(),
+        )
+    });
+
+    Ok(databases)
+}
+
+/// The repository sections of a `pacman.conf`, in declaration order.
+pub fn repo_order(conf: &str) -> Vec<String> {
+    conf.lines()
+        .filter_map(|line| line.trim().strip_prefix('[')?.strip_suffix(']'))
+        .filter(|section| *section != "options")
+        .map(str::to_string)
+        .collect()
+}
+
+/// Read every package of every sync database in `dir`.
+pub fn read_sync_dir(dir: &Path) -> io::Result<Vec<PackageInfo>> {
+    let mut packages = Vec::new();
+    for (repo, path) in sync_databases(dir)? {
+        packages.extend(read_db(&path, &repo)?);
+    }
+    Ok(packages)
+}
+
+/// Read every package in the sync database at `path`, tagging them with `repo`.
+pub fn read_db(path: &Path, repo: &str) -> io::Result<Vec<PackageInfo>> {
+    let mut archive = tar::Archive::new(open_decompressed(path)?);
+    let mut packages = Vec::new();
+
+    for entry in archive.entries()? {
+        let mut entry = entry?;
+        let is_desc = entry.path()?.file_name().is_some_and(|name| name == "desc");
+        if !is_desc {
+            continue;
+        }
+
+        let mut text = String::new();
+        entry.read_to_string(&mut text)?;
+        if let Some(package) = package_from_desc(&parse_desc(&text), repo) {
+            packages.push(package);
+        }
+    }
+
+    Ok(packages)
+}
+
+/// Map the name of every package in the sync databases in `dir` to the
+/// repository it comes from and its version, reading only the archive entry
+/// names.
+///
+/// A package in several repositories maps to the first one.
+pub fn sync_versions(dir: &Path) -> io::Result<HashMap<String, (String, String)>> {
+    let mut versions = HashMap::new();
+
+    for (repo, path) in sync_databases(dir)? {
+        let mut archive = tar::Archive::new(open_decompressed(&path)?);
+        for entry in archive.entries()? {
+            let entry = entry?;
+            let path = entry.path()?;
+            let directory = path.components().find_map(|part| match part {
+                Component::Normal(part) => part.to_str(),
+                _ => None,
+            });
+            if let Some((name, version)) = directory.and_then(split_entry_name) {
+                versions
+                    .entry(name.to_string())
+                    .or_insert_with(|| (repo.clone(), version.to_string()));
+            }
+        }
+    }
+
+    Ok(versions)
+}
+
+/// Open `path`, transparently decompressing gzip and zstd archives.
+pub fn open_decompressed(path: &Path) -> io::Result<Box<dyn Read>> {
+    let mut file = File::open(path)?;
+    let mut magic = [0u8; 4];
+    let read = file.read(&mut magic)?;
+    let magic = &magic[..read];
+
+    // Put the magic bytes back in front of the rest of the file
+    let reader = BufReader::new(io::Cursor::new(magic.to_vec()).chain(file));
+
+    if magic.starts_with(GZIP_MAGIC) {
+        Ok(Box::new(flate2::read::GzDecoder::new(reader)))
+    } else if magic.starts_with(ZSTD_MAGIC) {
+        Ok(Box::new(zstd::stream::read::Decoder::new(reader)?))
+    } else {
+        // Uncompressed tar
+        Ok(Box::new(reader))
+    }
+}
+
+/// Split a `desc` file into its `%KEY%` sections.
+pub fn parse_desc(text: &str) -> Desc {
+    let mut desc = Desc::new();
+    let mut current: Option<&mut Vec<String>> = None;
+
+    for line in text.lines() {
+        let line = line.trim_end();
+        if let Some(key) = line.strip_prefix('%').and_then(|l| l.strip_suffix('%')) {
+            current = Some(desc.entry(key.to_string()).or_default());
+        } else if line.is_empty() {
+            current = None;
+        } else if let Some(values) = current.as_mut() {
+            values.push(line.to_string());
+        }
+    }
+
+    desc
+}
+
+/// Build a [`PackageInfo`] from a parsed `desc` file.
+///
+/// Returns `None` when the entry has no `%NAME%` or `%VERSION%`.
+pub fn package_from_desc(desc: &Desc, repo: &str) -> Option<PackageInfo> {
+    let description = desc_value(desc, "DESC")
+        .filter(|d| !d.trim().is_empty())
+        .unwrap_or_else(|| "No description.".to_string());
+
+    Some(PackageInfo {
+        repo: Some(repo.to_string()),
+        groups: desc_list(desc, "GROUPS"),
+        provides: desc_list(desc, "PROVIDES"),
+        depends: desc_list(desc, "DEPENDS"),
+        opt_depends: desc_list(desc, "OPTDEPENDS"),
+        make_depends: desc_list(desc, "MAKEDEPENDS"),
+        conflicts: desc_list(desc, "CONFLICTS"),
+        replaces: desc_list(desc, "REPLACES"),
+        licenses: desc_list(desc, "LICENSE"),
+        url: desc_value(desc, "URL"),
+        packager: desc_value(desc, "PACKAGER"),
+        build_date: desc_number(desc, "BUILDDATE"),
+        compressed_size: desc_number(desc, "CSIZE"),
+        installed_size: desc_number(desc, "ISIZE"),
+        ..PackageInfo::new(
+            Source::Pacman,
+            desc_value(desc, "NAME")?,
+            desc_value(desc, "VERSION")?,
+            description,
+        )
+    })
+}
+
+/// The first value of a `desc` section.
+pub fn desc_value(desc: &Desc, key: &str) -> Option<String> {
+    desc.get(key).and_then(|values| values.first()).cloned()
+}
+
+/// Every value of a `desc` section, empty when the section is missing.
+pub fn desc_list(desc: &Desc, key: &str) -> Vec<String> {
+    desc.get(key).cloned().unwrap_or_default()
+}
+
+/// The first value of a `desc` section, parsed as a number.
+pub fn desc_number<T: FromStr>(desc: &Desc, key: &str) -> Option<T> {
+    desc.get(key)?.first()?.parse().ok()
+}
+
+/// Whether every whitespace-separated word of `term` appears, ignoring case,
+/// in the package's name or description. This mirrors `pacman -Ss` with one
+/// target per word.
+pub fn matches_term(package: &PackageInfo, term: &str) -> bool {
+    let name = package.name.to_lowercase();
+    let description = package.description.to_lowercase();
+
+    term.split_whitespace().all(|word| {
+        let word = word.to_lowercase();
+        name.contains(&word) || description.contains(&word)
+    })
+}
@@ -0,0 +1,31 @@
+/// Score how well `query` fuzzily matches `candidate`, ignoring case.
+///
+/// Every non-whitespace character of `query` must appear in `candidate` in
+/// order. Consecutive matches and matches at the start of a word score
+/// higher; returns `None` when the query does not match at all.
+pub fn fuzzy_score(query: &str, candidate: &str) -> Option<i64> {
+    let candidate: Vec<char> = candidate.to_lowercase().chars().collect();
+    let mut score = 0;
+    let mut position = 0;
+    let mut previous_match: Option<usize> = None;
+
+    for wanted in query.to_lowercase().chars().filter(|c| !c.is_whitespace()) {
+        let offset = candidate[position..].iter().position(|&c| c == wanted)?;
+        let index = position + offset;
+
+        score += 16;
+        if previous_match.is_some_and(|previous| previous + 1 == index) {
+            score += 8;
+        }
+        if index == 0 || !candidate[index - 1].is_alphanumeric() {
+            score += 8;
+        }
+        // Penalize gaps, but don't let long names drown out good matches
+        score -= offset.min(8) as i64;
+
+        previous_match = Some(index);
+        position = index + 1;
+    }
+
+    Some(score)
+}
@@ -0,0 +1,305 @@
+//! Interactive full-screen picker for search results (`pd -i`).
+//!
+//! The picker lists every result, narrows it down with fuzzy filtering as
+//! the user types, previews the highlighted package and returns the packages
+//! the user chose to install.
+
+mod fuzzy;
+mod preview;
+
+pub use fuzzy::fuzzy_score;
+pub use preview::Previewer;
+
+use std::collections::hash_map::Entry;
+use std::collections::{HashMap, HashSet};
+use std::io;
+use std::time::Duration;
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::layout::{Constraint, Layout};
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span};
+use ratatui::widgets::{Block, Borders, List, ListItem, ListState, Paragraph, Wrap};
+use ratatui::{DefaultTerminal, Frame};
+
+use crate::package::{PackageInfo, Source};
+
+const HELP: &str = " type to filter · ↑/↓ move · Tab select · Enter install · Esc quit ";
+
+/// Show the picker and return the packages chosen for installation, or
+/// `None` if the user quit without installing.
+///
+/// When nothing was explicitly selected, Enter installs the highlighted
+/// package.
+pub fn run(
+    packages: Vec<PackageInfo>,
+    previewer: Previewer,
+) -> io::Result<Option<Vec<PackageInfo>>> {
+    let mut terminal = ratatui::try_init()?;
+    let result = Picker::new(packages, previewer).run(&mut terminal);
+    ratatui::try_restore()?;
+    result
+}
+
+enum PreviewState {
+    Loading,
+    Ready(String),
+    Failed(String),
+}
+
+struct Picker {
+    packages: Vec<PackageInfo>,
+    previewer: Previewer,
+    query: String,
+    /// Indices into `packages` that match `query`, best match first.
+    visible: Vec<usize>,
+    list_state: ListState,
+    selected: HashSet<usize>,
+    previews: HashMap<usize, PreviewState>,
+}
+
+impl Picker {
+    fn new(packages: Vec<PackageInfo>, previewer: Previewer) -> Self {
+        let mut picker = Picker {
+            visible: (0..packages.len()).collect(),
+            packages,
+            previewer,
+            query: String::new(),
+            list_state: ListState::default(),
+            selected: HashSet::new(),
+            previews: HashMap::new(),
+        };
+        picker
+            .list_state
+            .select((!picker.visible.is_empty()).then_some(0));
+        picker
+    }
+
+    fn run(mut self, terminal: &mut DefaultTerminal) -> io::Result<Option<Vec<PackageInfo>>> {
+        loop {
+            for (key, details) in self.previewer.poll() {
+                let state = match details {
+                    Ok(text) => PreviewState::Ready(text),
+                    Err(e) => PreviewState::Failed(e),
+                };
+                self.previews.insert(key, state);
+            }
+            self.request_preview();
+
+            terminal.draw(|frame| self.draw(frame))?;
+
+            // Wake up regularly so finished previews get drawn
+            if !event::poll(Duration::from_millis(100))? {
+                continue;
+            }
+            if let Event::Key(key) = event::read()? {
+                if key.kind != KeyEventKind::Press {
+                    continue;
+                }
+                if let Some(done) = self.handle_key(key) {
+                    return Ok(done);
+                }
+            }
+        }
+    }
+
+    /// Handle a key press, returning `Some` when the picker should close.
+    fn handle_key(&mut self, key: KeyEvent) -> Option<Option<Vec<PackageInfo>>> {
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+
+        match key.code {
+            KeyCode::Esc => return Some(None),
+            KeyCode::Char('c') if ctrl => return Some(None),
+            KeyCode::Enter => return Some(self.chosen()),
+            KeyCode::Tab => {
+                if let Some(index) = self.current() {
+                    if !self.selected.remove(&index) {
+                        self.selected.insert(index);
+                    }
+                }
+                self.move_cursor(1);
+            }
+            KeyCode::Up => self.move_cursor(-1),
+            KeyCode::Char('p') if ctrl => self.move_cursor(-1),
+            KeyCode::Down => self.move_cursor(1),
+            KeyCode::Char('n') if ctrl => self.move_cursor(1),
+            KeyCode::PageUp => self.move_cursor(-10),
+            KeyCode::PageDown => self.move_cursor(10),
+            KeyCode::Home => self.move_cursor(isize::MIN),
+            KeyCode::End => self.move_cursor(isize::MAX),
+            KeyCode::Char('u') if ctrl => {
+                self.query.clear();
+                self.refilter();
+            }
+            KeyCode::Backspace => {
+                self.query.pop();
+                self.refilter();
+            }
+            KeyCode::Char(c) if !ctrl => {
+                self.query.push(c);
+                self.refilter();
+            }
+            _ => {}
+        }
+
+        None
+    }
+
+    /// The packages to install: the selection, or the highlighted package.
+    fn chosen(&self) -> Option<Vec<PackageInfo>> {
+        let mut indices: Vec<usize> = if self.selected.is_empty() {
+            self.current().into_iter().collect()
+        } else {
+            self.selected.iter().copied().collect()
+        };
+        indices.sort_unstable();
+
+        (!indices.is_empty()).then(|| {
+            indices
+                .into_iter()
+                .map(|i| self.packages[i].clone())
+                .collect()
+        })
+    }
+
+    fn current(&self) -> Option<usize> {
+        self.list_state
+            .selected()
+            .and_then(|row| self.visible.get(row).copied())
+    }
+
+    fn move_cursor(&mut self, delta: isize) {
+        if self.visible.is_empty() {
+            return;
+        }
+        let row = self.list_state.selected().unwrap_or(0);
+        let last = self.visible.len() - 1;
+        let row = row.saturating_add_signed(delta).min(last);
+        self.list_state.select(Some(row));
+    }
+
+    fn refilter(&mut self) {
+        if self.query.trim().is_empty() {
+            self.visible = (0..self.packages.len()).collect();
+        } else {
+            let query = self.query.trim().to_lowercase();
+            let mut scored: Vec<(i64, usize)> = self
+                .packages
+                .iter()
+                .enumerate()
+                .filter_map(|(index, package)| {
+                    // Fuzzy name matches rank above plain description matches
+                    let score = fuzzy_score(&query, &package.name).or_else(|| {
+                        package
+                            .description
+                            .to_lowercase()
+                            .contains(&query)
+                            .then_some(i64::MIN / 2)
+                    })?;
+                    Some((score, index))
+                })
+                .collect();
+            scored.sort_by_key(|&(score, index)| (std::cmp::Reverse(score), index));
+            self.visible = scored.into_iter().map(|(_, index)| index).collect();
+        }
+
+        self.list_state
+            .select((!self.visible.is_empty()).then_some(0));
+    }
+
+    fn request_preview(&mut self) {
+        if let Some(index) = self.current() {
+            if let Entry::Vacant(entry) = self.previews.entry(index) {
+                entry.insert(PreviewState::Loading);
+                self.previewer.request(index, &self.packages[index]);
+            }
+        }
+    }
+
+    fn draw(&mut self, frame: &mut Frame) {
+        let [query_area, body_area, help_area] = Layout::vertical([
+            Constraint::Length(3),
+            Constraint::Min(1),
+            Constraint::Length(1),
+        ])
+        .areas(frame.area());
+        let [list_area, preview_area] =
+            Layout::horizontal([Constraint::Percentage(45), Constraint::Percentage(55)])
+                .areas(body_area);
+
+        let title = format!(" {}/{} ", self.visible.len(), self.packages.len());
+        let query = Paragraph::new(format!("> {}", self.query))
+            .block(Block::default().borders(Borders::ALL).title(title));
+        frame.render_widget(query, query_area);
+
+        let items: Vec<ListItem> = self
+            .visible
+            .iter()
+            .map(|&index| self.list_item(index))
+            .collect();
+        let list = List::new(items)
+            .block(Block::default().borders(Borders::ALL).title(" Packages "))
+            .highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+        frame.render_stateful_widget(list, list_area, &mut self.list_state);
+
+        let preview = Paragraph::new(self.preview_text())
+            .block(Block::default().borders(Borders::ALL).title(" Details "))
+            .wrap(Wrap { trim: false });
+        frame.render_widget(preview, preview_area);
+
+        let selected = format!(" {} selected ", self.selected.len());
+        let help = Line::from(vec![
+            Span::styled(selected, Style::default().add_modifier(Modifier::BOLD)),
+            Span::raw(HELP),
+        ]);
+        frame.render_widget(Paragraph::new(help), help_area);
+    }
+
+    fn list_item(&self, index: usize) -> ListItem<'static> {
+        let package = &self.packages[index];
+        let marker = if self.selected.contains(&index) {
+            "● "
+        } else {
+            "  "
+        };
+
+        ListItem::new(Line::from(vec![
+            Span::raw(marker),
+            Span::styled(
+                format!("{:<8}", package.source.label()),
+                Style::default().fg(source_color(package.source)),
+            ),
+            Span::styled(
+                package.name.clone(),
+                Style::default().add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(format!(" {}", package.version)),
+        ]))
+    }
+
+    fn preview_text(&self) -> String {
+        let Some(index) = self.current() else {
+            return "No matching packages.".to_string();
+        };
+        let package = &self.packages[index];
+
+        let mut text = format!(
+            "{} {}\n{}\n\n",
+            package.name, package.version, package.description
+        );
+        match self.previews.get(&index) {
+            Some(PreviewState::Ready(details)) => text.push_str(details),
+            Some(PreviewState::Failed(e)) => text.push_str(&format!("Details unavailable: {}", e)),
+            Some(PreviewState::Loading) | None => text.push_str("Loading details…"),
+        }
+        text
+    }
+}
+
+fn source_color(source: Source) -> Color {
+    match source {
+        Source::Pacman => Color::Blue,
+        Source::Aur => Color::Red,
+        Source::Flatpak => Color::Green,
+    }
+}
@@ -0,0 +1,239 @@
+use std::sync::mpsc::{self, Receiver, Sender};
+
+use tokio::runtime::Handle;
+
+use crate::aur_rpc::{RpcClient, RpcPackage};
+use crate::backend::flatpak_permissions;
+use crate::date::format_date;
+use crate::package::{PackageInfo, Source};
+use crate::render::info_releases;
+use crate::sandbox::Permissions;
+
+/// Fetches detailed package information in the background.
+///
+/// Requests are spawned on the given runtime and their results are collected
+/// with [`Previewer::poll`], so the UI never blocks on a slow tool.
+pub struct Previewer {
+    handle: Handle,
+    rpc: RpcClient,
+    offline: bool,
+    sender: Sender<(usize, Result<String, String>)>,
+    receiver: Receiver<(usize, Result<String, String>)>,
+}
+
+impl Previewer {
+    pub fn new(handle: Handle, rpc: RpcClient) -> Self {
+        let (sender, receiver) = mpsc::channel();
+        Previewer {
+            handle,
+            rpc,
+            offline: false,
+            sender,
+            receiver,
+        }
+    }
+
+    /// Only show details available without network access.
+    pub fn offline(mut self) -> Self {
+        self.offline = true;
+        self
+    }
+
+    /// Start fetching details for `package`, identified by `key` in the
+    /// results of [`Previewer::poll`].
+    pub fn request(&self, key: usize, package: &PackageInfo) {
+        let sender = self.sender.clone();
+        let rpc = self.rpc.clone();
+        let package = package.clone();
+        let offline = self.offline;
+
+        self.handle.spawn(async move {
+            let details = fetch_details(&rpc, &package, offline).await;
+            let _ = sender.send((key, details));
+        });
+    }
+
+    /// Collect every preview that finished since the last call.
+    pub fn poll(&self) -> Vec<(usize, Result<String, String>)> {
+        self.receiver.try_iter().collect()
+    }
+}
+
+async fn fetch_details(
+    rpc: &RpcClient,
+    package: &PackageInfo,
+    offline: bool,
+) -> Result<String, String> {
+    match package.source {
+        Source::Pacman => {
+            // Qualify with the repo so the same name in testing isn't picked up
+            let target = match &package.repo {
+                Some(repo) => format!("{}/{}", repo, package.name),
+                None => package.name.clone(),
+            };
+            command_output("pacman", &["-Si", &target]).await
+        }
+        Source::Aur if offline => Err("AUR details are not available offline".to_string()),
+        Source::Aur => {
+            let results = rpc
+                .info(&[&package.name])
+                .await
+                .map_err(|e| e.to_string())?;
+            let info = results
+                .into_iter()
+                .next()
+                .ok_or_else(|| format!("{} not found in the AUR", package.name))?;
+            Ok(format_aur_info(&info))
+        }
+        // Results read from the appstream cache already know everything but
+        // the sandbox permissions
+        Source::Flatpak if package.appstream => {
+            let package = PackageInfo {
+                permissions: flatpak_permissions(package, offline).await,
+                ..package.clone()
+            };
+            Ok(format_appstream_info(&package))
+        }
+        Source::Flatpak => {
+            let app_id = package
+                .app_id
+                .as_deref()
+                .ok_or_else(|| "Unknown application ID".to_string())?;
+            let mut info = flatpak_remote_info(app_id, offline).await?;
+            if let Some(permissions) = flatpak_permissions(package, offline).await {
+                info.push('\n');
+                info.push_str(&permission_lines(&permissions).join("\n"));
+            }
+            Ok(info)
+        }
+    }
+}
+
+/// Run `flatpak remote-info` against each configured remote until one of
+/// them knows the application. Offline, only the locally cached metadata is
+/// consulted.
+async fn flatpak_remote_info(app_id: &str, offline: bool) -> Result<String, String> {
+    let remotes = command_output("flatpak", &["remotes", "--columns=name"]).await?;
+
+    for remote in remotes.lines().map(str::trim).filter(|r| !r.is_empty()) {
+        let mut args = vec!["remote-info", remote, app_id];
+        if offline {
+            args.push("--cached");
+        }
+        if let Ok(info) = command_output("flatpak", &args).await {
+            return Ok(format!("Remote: {}\n{}", remote, info.trim_start()));
+        }
+    }
+
+    Err(format!("{} was not found in any Flatpak remote", app_id))
+}
+
+async fn command_output(program: &str, args: &[&str]) -> Result<String, String> {
+    let output = crate::backend::command(program)
+        .args(args)
+        .output()
+        .await
+        .map_err(|e| format!("Failed to run {}: {}", program, e))?;
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(String::from_utf8_lossy(&output.stderr).trim().to_string())
+    }
+}
+
+fn format_aur_info(info: &RpcPackage) -> String {
+    let list = |values: &[String]| {
+        if values.is_empty() {
+            "None".to_string()
+        } else {
+            values.join("  ")
+        }
+    };
+
+    let mut lines = vec![
+        format!("Name            : {}", info.name),
+        format!("Package Base    : {}", info.package_base),
+        format!("Version         : {}", info.version),
+        format!(
+            "Description     : {}",
+            info.description.as_deref().unwrap_or("None")
+        ),
+        format!(
+            "URL             : {}",
+            info.url.as_deref().unwrap_or("None")
+        ),
+        format!("Licenses        : {}", list(&info.license)),
+        format!("Groups          : {}", list(&info.groups)),
+        format!("Provides        : {}", list(&info.provides)),
+        format!("Depends On      : {}", list(&info.depends)),
+        format!("Make Deps       : {}", list(&info.make_depends)),
+        format!("Optional Deps   : {}", list(&info.opt_depends)),
+        format!("Conflicts With  : {}", list(&info.conflicts)),
+        format!("Replaces        : {}", list(&info.replaces)),
+        format!(
+            "Maintainer      : {}",
+            info.maintainer.as_deref().unwrap_or("None")
+        ),
+        format!("Votes           : {}", info.num_votes),
+        format!("Popularity      : {:.2}", info.popularity),
+        format!("First Submitted : {}", format_date(info.first_submitted)),
+        format!("Last Modified   : {}", format_date(info.last_modified)),
+    ];
+    if let Some(flagged) = info.out_of_date {
+        lines.push(format!("Out Of Date     : {}", format_date(flagged)));
+    }
+
+    lines.join("\n")
+}
+
+fn format_appstream_info(package: &PackageInfo) -> String {
+    let value = |value: Option<&str>| value.unwrap_or("None").to_string();
+    let list = |values: &[String]| {
+        if values.is_empty() {
+            "None".to_string()
+        } else {
+            values.join("  ")
+        }
+    };
+
+    let mut lines = vec![
+        format!("Application ID  : {}", value(package.app_id.as_deref())),
+        format!("Remote          : {}", value(package.repo.as_deref())),
+        format!("Branch          : {}", value(package.branch.as_deref())),
+        format!("Arch            : {}", value(package.arch.as_deref())),
+        format!(
+            "Kind            : {}",
+            value(package.kind.map(|kind| kind.label()))
+        ),
+        format!("Version         : {}", package.version),
+        format!("Developer       : {}", value(package.developer.as_deref())),
+        format!("Categories      : {}", list(&package.categories)),
+        format!("URL             : {}", value(package.url.as_deref())),
+        format!("Licenses        : {}", list(&package.licenses)),
+        format!("Content Rating  : {}", list(&package.content_rating)),
+        format!(
+            "Releases        : {}",
+            info_releases(&package.releases).unwrap_or_else(|| "None".to_string())
+        ),
+    ];
+    if let Some(permissions) = &package.permissions {
+        lines.extend(permission_lines(permissions));
+    }
+    if let Some(description) = &package.long_description {
+        lines.push(String::new());
+        lines.push(description.clone());
+    }
+
+    lines.join("\n")
+}
+
+/// The summary of a Flatpak's sandbox permissions, aligned with the other
+/// detail lines.
+fn permission_lines(permissions: &Permissions) -> Vec<String> {
+    permissions
+        .summary()
+        .into_iter()
+        .map(|(label, value)| format!("{:<16}: {}", label, value))
+        .collect()
+}
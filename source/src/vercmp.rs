@@ -0,0 +1,130 @@
+//! Version comparison with the semantics of pacman's `vercmp`.
+//!
+//! A version is `[epoch:]pkgver[-pkgrel]`. Epochs compare first, then the
+//! pkgver and, when both sides have one, the pkgrel. Each part is split into
+//! runs of digits and runs of letters, separated by anything else. Numeric
+//! runs compare as numbers and are newer than letter runs, and a trailing
+//! letter run marks a pre-release, so `1.0a` is older than `1.0`.
+
+use std::cmp::Ordering;
+
+/// Compare two versions like `vercmp`.
+pub fn vercmp(a: &str, b: &str) -> Ordering {
+    if a == b {
+        return Ordering::Equal;
+    }
+
+    let (epoch_a, version_a, release_a) = split_version(a);
+    let (epoch_b, version_b, release_b) = split_version(b);
+
+    compare_part(epoch_a, epoch_b)
+        .then_with(|| compare_part(version_a, version_b))
+        .then_with(|| match (release_a, release_b) {
+            (Some(a), Some(b)) => compare_part(a, b),
+            _ => Ordering::Equal,
+        })
+}
+
+/// Whether `available` is newer than `installed`.
+pub fn is_newer(available: &str, installed: &str) -> bool {
+    vercmp(available, installed) == Ordering::Greater
+}
+
+/// Split a version into epoch, pkgver and pkgrel. A missing epoch is `0`.
+fn split_version(version: &str) -> (&str, &str, Option<&str>) {
+    let (epoch, rest) = match version.split_once(':') {
+        Some((epoch, rest)) if epoch.bytes().all(|b| b.is_ascii_digit()) => (epoch, rest),
+        _ => ("0", version),
+    };
+    match rest.rsplit_once('-') {
+        Some((version, release)) => (epoch, version, Some(release)),
+        None => (epoch, rest, None),
+    }
+}
+
+/// Compare one part of a version, following libalpm's `rpmvercmp`.
+fn compare_part(a: &str, b: &str) -> Ordering {
+    if a == b {
+        return Ordering::Equal;
+    }
+
+    let (a, b) = (a.as_bytes(), b.as_bytes());
+    let (mut i, mut j) = (0, 0);
+
+    while i < a.len() && j < b.len() {
+        let (start_a, start_b) = (i, j);
+        while i < a.len() && !a[i].is_ascii_alphanumeric() {
+            i += 1;
+        }
+        while j < b.len() && !b[j].is_ascii_alphanumeric() {
+            j += 1;
+        }
+        if i == a.len() || j == b.len() {
+            break;
+        }
+        // More separators make a newer version: `1..0` is newer than `1.0`
+        if i - start_a != j - start_b {
+            return (i - start_a).cmp(&(j - start_b));
+        }
+
+        let (segment_start_a, segment_start_b) = (i, j);
+        let numeric = a[i].is_ascii_digit();
+        let in_segment = |c: u8| {
+            if numeric {
+                c.is_ascii_digit()
+            } else {
+                c.is_ascii_alphabetic()
+            }
+        };
+        while i < a.len() && in_segment(a[i]) {
+            i += 1;
+        }
+        while j < b.len() && in_segment(b[j]) {
+            j += 1;
+        }
+
+        // The segments are of different kinds, and numbers are newer
+        if j == segment_start_b {
+            return if numeric {
+                Ordering::Greater
+            } else {
+                Ordering::Less
+            };
+        }
+
+        let mut segment_a = &a[segment_start_a..i];
+        let mut segment_b = &b[segment_start_b..j];
+        if numeric {
+            segment_a = trim_leading_zeros(segment_a);
+            segment_b = trim_leading_zeros(segment_b);
+            // The longer number is the bigger one
+            let by_length = segment_a.len().cmp(&segment_b.len());
+            if by_length != Ordering::Equal {
+                return by_length;
+            }
+        }
+        let by_value = segment_a.cmp(segment_b);
+        if by_value != Ordering::Equal {
+            return by_value;
+        }
+    }
+
+    let (rest_a, rest_b) = (&a[i..], &b[j..]);
+    if rest_a.is_empty() && rest_b.is_empty() {
+        return Ordering::Equal;
+    }
+    // A trailing letter segment is older, anything else newer: `1.0a` is
+    // older than `1.0`, `1.0.1` newer
+    if (rest_a.is_empty() && !rest_b[0].is_ascii_alphabetic())
+        || rest_a.first().is_some_and(u8::is_ascii_alphabetic)
+    {
+        Ordering::Less
+    } else {
+        Ordering::Greater
+    }
+}
+
+fn trim_leading_zeros(digits: &[u8]) -> &[u8] {
+    let zeros = digits.iter().take_while(|&&d| d == b'0').count();
+    &digits[zeros..]
+}
@@ -0,0 +1,148 @@
+use std::fs;
+
+use package_dictionary::appstream::{parse_appstream, read_appstream_dirs};
+use package_dictionary::package::{FlatpakKind, Release};
+use package_dictionary::Source;
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/appstream");
+
+/// Lay out `tests/fixtures/appstream/<remote>.xml` like Flatpak's appstream
+/// cache for this machine's architecture.
+fn appstream_dir(remotes: &[&str]) -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    for remote in remotes {
+        let active = dir
+            .path()
+            .join(remote)
+            .join(std::env::consts::ARCH)
+            .join("active");
+        fs::create_dir_all(&active).unwrap();
+        fs::copy(
+            format!("{}/{}.xml", FIXTURES, remote),
+            active.join("appstream.xml"),
+        )
+        .unwrap();
+    }
+    dir
+}
+
+#[test]
+fn reads_flatpak_components() {
+    let dir = appstream_dir(&["flathub"]);
+    let packages = read_appstream_dirs(&[dir.path().to_path_buf()]).unwrap();
+    let names: Vec<&str> = packages.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(
+        names,
+        [
+            "Firefox (org.mozilla.firefox)",
+            "Text Editor (org.gnome.TextEditor)"
+        ]
+    );
+
+    let firefox = &packages[0];
+    assert_eq!(firefox.source, Source::Flatpak);
+    assert_eq!(firefox.app_id.as_deref(), Some("org.mozilla.firefox"));
+    assert_eq!(firefox.repo.as_deref(), Some("flathub"));
+    assert_eq!(firefox.version, "131.0");
+    assert_eq!(firefox.description, "Fast, Private & Safe Web Browser");
+    assert_eq!(firefox.keywords, ["internet", "web"]);
+    assert_eq!(firefox.licenses, ["MPL-2.0"]);
+    assert_eq!(firefox.provides, ["firefox"]);
+    assert_eq!(
+        firefox.url.as_deref(),
+        Some("https://www.mozilla.org/firefox/")
+    );
+
+    assert_eq!(firefox.branch.as_deref(), Some("stable"));
+    assert_eq!(
+        firefox.long_description.as_deref(),
+        Some("Firefox is a fast browser.\n\n- Tracking protection\n- Sync")
+    );
+    assert_eq!(firefox.developer.as_deref(), Some("Mozilla"));
+    assert_eq!(firefox.categories, ["Network", "WebBrowser"]);
+    assert_eq!(firefox.content_rating, ["social-chat: intense"]);
+    assert_eq!(
+        firefox.releases,
+        [
+            Release {
+                version: "131.0".to_string(),
+                date: Some(1727740800)
+            },
+            Release {
+                version: "130.0".to_string(),
+                date: Some(1725321600)
+            },
+        ]
+    );
+    let icons = dir
+        .path()
+        .join("flathub")
+        .join(std::env::consts::ARCH)
+        .join("active/icons");
+    assert_eq!(
+        firefox.icons,
+        [
+            icons.join("128x128/org.mozilla.firefox.png"),
+            icons.join("64x64/org.mozilla.firefox.png"),
+        ]
+        .map(|path| path.to_string_lossy().into_owned())
+    );
+
+    let editor = &packages[1];
+    assert_eq!(editor.version, "Unknown");
+    assert_eq!(editor.branch.as_deref(), Some("beta"));
+    assert_eq!(editor.developer.as_deref(), Some("The GNOME Project"));
+    assert!(editor.keywords.is_empty());
+    assert!(editor.long_description.is_none());
+    assert!(editor.icons.is_empty());
+}
+
+#[test]
+fn reads_release_dates() {
+    let xml = r#"<components>
+      <component>
+        <bundle type="flatpak">app/org.example.App/x86_64/stable</bundle>
+        <releases>
+          <release version="2.0" date="2024-09-14"/>
+          <release version="1.0"/>
+        </releases>
+      </component>
+    </components>"#;
+    let packages = parse_appstream(xml, "flathub").unwrap();
+    assert_eq!(packages[0].version, "2.0");
+    assert_eq!(packages[0].releases[0].date, Some(1726272000));
+    assert_eq!(packages[0].releases[1].date, None);
+}
+
+#[test]
+fn reads_ref_kinds() {
+    let xml = r#"<components>
+      <component type="desktop-application">
+        <bundle type="flatpak">app/org.gimp.GIMP/aarch64/stable</bundle>
+      </component>
+      <component type="runtime">
+        <bundle type="flatpak">runtime/org.gnome.Platform/x86_64/47</bundle>
+      </component>
+      <component type="addon">
+        <bundle type="flatpak">runtime/org.gimp.GIMP.Plugin.GMic/x86_64/2-40</bundle>
+      </component>
+    </components>"#;
+    let packages = parse_appstream(xml, "flathub").unwrap();
+    let refs: Vec<_> = packages
+        .iter()
+        .map(|p| (p.kind, p.arch.as_deref(), p.branch.as_deref()))
+        .collect();
+    assert_eq!(
+        refs,
+        [
+            (Some(FlatpakKind::App), Some("aarch64"), Some("stable")),
+            (Some(FlatpakKind::Runtime), Some("x86_64"), Some("47")),
+            (Some(FlatpakKind::Extension), Some("x86_64"), Some("2-40")),
+        ]
+    );
+}
+
+#[test]
+fn rejects_malformed_xml() {
+    assert!(parse_appstream("<components><component>", "flathub").is_err());
+}
@@ -0,0 +1,172 @@
+//! Exercise the AUR RPC client against a local mock server.
+
+use package_dictionary::aur_rpc::{RpcClient, RpcError, SearchBy};
+use package_dictionary::{PackageInfo, Source};
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::oneshot;
+
+const SEARCH_RESPONSE: &str = r#"{
+  "resultcount": 2,
+  "results": [
+    {
+      "Description": "Fast, Private & Safe Web Browser - Nightly",
+      "FirstSubmitted": 1271436306,
+      "ID": 1534567,
+      "LastModified": 1728561600,
+      "Maintainer": "someone",
+      "Name": "firefox-nightly",
+      "NumVotes": 120,
+      "OutOfDate": null,
+      "PackageBase": "firefox-nightly",
+      "PackageBaseID": 42,
+      "Popularity": 1.234567,
+      "URL": "https://www.mozilla.org/firefox/channel/desktop/#nightly",
+      "URLPath": "/cgit/aur.git/snapshot/firefox-nightly.tar.gz",
+      "Version": "133.0a1.20241010-1"
+    },
+    {
+      "Description": null,
+      "FirstSubmitted": 1500000000,
+      "ID": 7,
+      "LastModified": 1600000000,
+      "Maintainer": null,
+      "Name": "firefox-orphan",
+      "NumVotes": 0,
+      "OutOfDate": 1700000000,
+      "PackageBase": "firefox-orphan",
+      "PackageBaseID": 7,
+      "Popularity": 0,
+      "URL": null,
+      "URLPath": null,
+      "Version": "1.0-1"
+    }
+  ],
+  "type": "search",
+  "version": 5
+}"#;
+
+const INFO_RESPONSE: &str = r#"{
+  "resultcount": 1,
+  "results": [
+    {
+      "Depends": ["gtk3", "libxt"],
+      "MakeDepends": ["rust"],
+      "OptDepends": ["hunspell: spell checking"],
+      "License": ["MPL-2.0"],
+      "Keywords": ["browser"],
+      "Description": "Fast, Private & Safe Web Browser - Nightly",
+      "ID": 1534567,
+      "LastModified": 1728561600,
+      "Maintainer": "someone",
+      "Name": "firefox-nightly",
+      "NumVotes": 120,
+      "PackageBase": "firefox-nightly",
+      "Popularity": 1.23,
+      "Version": "133.0a1.20241010-1"
+    }
+  ],
+  "type": "multiinfo",
+  "version": 5
+}"#;
+
+const ERROR_RESPONSE: &str =
+    r#"{"error":"Query arg too small.","resultcount":0,"results":[],"type":"error","version":5}"#;
+
+/// Serve a single HTTP request with `body`, reporting the request line back.
+async fn serve_once(
+    status: &'static str,
+    body: &'static str,
+) -> (String, oneshot::Receiver<String>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let base_url = format!("http://{}/rpc/v5", listener.local_addr().unwrap());
+    let (tx, rx) = oneshot::channel();
+
+    tokio::spawn(async move {
+        let (mut socket, _) = listener.accept().await.unwrap();
+        let mut request = Vec::new();
+        let mut buf = [0u8; 1024];
+        while !request.windows(4).any(|w| w == b"\r\n\r\n") {
+            let n = socket.read(&mut buf).await.unwrap();
+            if n == 0 {
+                break;
+            }
+            request.extend_from_slice(&buf[..n]);
+        }
+
+        let request = String::from_utf8_lossy(&request);
+        let request_line = request.lines().next().unwrap_or_default().to_string();
+        let _ = tx.send(request_line);
+
+        let response = format!(
+            "HTTP/1.1 {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            status,
+            body.len(),
+            body
+        );
+        socket.write_all(response.as_bytes()).await.unwrap();
+    });
+
+    (base_url, rx)
+}
+
+#[tokio::test]
+async fn search_maps_rpc_fields() {
+    let (base_url, request) = serve_once("200 OK", SEARCH_RESPONSE).await;
+    let client = RpcClient::with_base_url(base_url);
+
+    let results = client.search("fire fox", SearchBy::NameDesc).await.unwrap();
+    assert_eq!(
+        request.await.unwrap(),
+        "GET /rpc/v5/search/fire%20fox?by=name-desc HTTP/1.1"
+    );
+
+    let packages: Vec<PackageInfo> = results.into_iter().map(PackageInfo::from).collect();
+    assert_eq!(packages.len(), 2);
+
+    let nightly = &packages[0];
+    assert_eq!(nightly.source, Source::Aur);
+    assert_eq!(nightly.name, "firefox-nightly");
+    assert_eq!(nightly.version, "133.0a1.20241010-1");
+    assert_eq!(nightly.votes, Some(120));
+    assert_eq!(nightly.popularity, Some(1.234567));
+    assert_eq!(nightly.maintainer.as_deref(), Some("someone"));
+    assert!(!nightly.orphaned);
+    assert_eq!(nightly.out_of_date, None);
+    assert_eq!(nightly.last_modified, Some(1728561600));
+
+    let orphan = &packages[1];
+    assert_eq!(orphan.description, "No description.");
+    assert_eq!(orphan.maintainer, None);
+    assert!(orphan.orphaned);
+    assert_eq!(orphan.out_of_date, Some(1700000000));
+}
+
+#[tokio::test]
+async fn info_sends_every_name_and_reads_dependencies() {
+    let (base_url, request) = serve_once("200 OK", INFO_RESPONSE).await;
+    let client = RpcClient::with_base_url(base_url);
+
+    let results = client.info(&["firefox-nightly", "c++utils"]).await.unwrap();
+    assert_eq!(
+        request.await.unwrap(),
+        "GET /rpc/v5/info?arg%5B%5D=firefox-nightly&arg%5B%5D=c%2B%2Butils HTTP/1.1"
+    );
+
+    assert_eq!(results.len(), 1);
+    assert_eq!(results[0].depends, ["gtk3", "libxt"]);
+    assert_eq!(results[0].make_depends, ["rust"]);
+    assert_eq!(results[0].opt_depends, ["hunspell: spell checking"]);
+    assert_eq!(results[0].license, ["MPL-2.0"]);
+}
+
+#[tokio::test]
+async fn error_responses_surface_the_rpc_message() {
+    let (base_url, _request) = serve_once("400 Bad Request", ERROR_RESPONSE).await;
+    let client = RpcClient::with_base_url(base_url);
+
+    match client.search("f", SearchBy::Name).await {
+        Err(RpcError::Api(message)) => assert_eq!(message, "Query arg too small."),
+        other => panic!("expected an API error, got {:?}", other),
+    }
+}
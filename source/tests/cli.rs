@@ -0,0 +1,316 @@
+use std::process::{Command, Output};
+
+use package_dictionary::index::IndexBuilder;
+use package_dictionary::package::FlatpakKind;
+use package_dictionary::rank::relevance;
+use package_dictionary::{PackageInfo, Source};
+
+fn pd(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_pd"))
+        .args(args)
+        .env("PD_CONFIG", "/nonexistent/config.toml")
+        .env("XDG_CACHE_HOME", "/nonexistent/cache")
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn help_and_version() {
+    let help = pd(&["--help"]);
+    assert!(help.status.success());
+    let help = String::from_utf8_lossy(&help.stdout);
+    for command in ["search", "info", "provides", "install", "index", "config"] {
+        assert!(help.contains(command), "{} missing from --help", command);
+    }
+
+    let version = pd(&["--version"]);
+    assert!(version.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&version.stdout).trim(),
+        format!("pd {}", env!("CARGO_PKG_VERSION"))
+    );
+}
+
+#[test]
+fn invalid_usage_exits_with_status_2() {
+    for args in [
+        &[][..],
+        &["--bogus", "firefox"],
+        &["--format", "yaml", "firefox"],
+        &["--timeout", "soon", "firefox"],
+        &["--set", "nope=1", "firefox"],
+        &["--repo", "--backends", "aur", "firefox"],
+        &["--offline", "--installed"],
+        &["-i", "info", "firefox"],
+        &["info"],
+        &["provides", "a", "b"],
+        &["--upgradable"],
+    ] {
+        assert_eq!(pd(args).status.code(), Some(2), "pd {}", args.join(" "));
+    }
+}
+
+#[test]
+fn config_reflects_flags() {
+    let output = pd(&["config", "--repo", "--flatpak", "--color", "never"]);
+    assert!(output.status.success());
+    let settings = String::from_utf8_lossy(&output.stdout);
+    assert!(settings.contains(r#"backends = ["pacman", "flatpak"]"#));
+    assert!(settings.contains(r#"color = "never""#));
+
+    let output = pd(&["--config", "/tmp/pd.toml", "config", "path"]);
+    assert_eq!(
+        String::from_utf8_lossy(&output.stdout).trim(),
+        "/tmp/pd.toml"
+    );
+}
+
+#[test]
+fn search_is_the_default_subcommand() {
+    // Both search the installed packages; without pacman or flatpak they
+    // fail the same way, which is enough to tell they ran the same search
+    let implicit = pd(&["--format", "json", "--repo", "-Q"]);
+    let explicit = pd(&["search", "--format", "json", "--repo", "-Q"]);
+    assert_eq!(implicit.status.code(), explicit.status.code());
+    assert_eq!(implicit.stdout, explicit.stdout);
+}
+
+#[test]
+fn completion_scripts_and_man_page() {
+    for shell in ["bash", "zsh", "fish"] {
+        let script = pd(&["completions", shell]);
+        assert!(script.status.success(), "{}", shell);
+        assert!(String::from_utf8_lossy(&script.stdout).contains("COMPLETE="));
+    }
+
+    let man = pd(&["man"]);
+    assert!(man.status.success());
+    assert!(String::from_utf8_lossy(&man.stdout).starts_with(".ie"));
+}
+
+#[test]
+fn completes_package_names_from_the_index() {
+    let cache = tempfile::tempdir().unwrap();
+    let mut builder = IndexBuilder::new();
+    builder
+        .add(["yay", "yay-bin", "paru"].map(|name| PackageInfo::new(Source::Aur, name, "1-1", "")));
+    builder
+        .write(&cache.path().join("package-dictionary/index"))
+        .unwrap();
+
+    for subcommand in ["info", "install"] {
+        let output = Command::new(env!("CARGO_BIN_EXE_pd"))
+            .args(["--", "pd", subcommand, "ya"])
+            .env("COMPLETE", "fish")
+            .env("XDG_CACHE_HOME", cache.path())
+            .output()
+            .unwrap();
+        assert_eq!(String::from_utf8_lossy(&output.stdout), "yay\nyay-bin\n");
+    }
+}
+
+#[test]
+fn groups_flavors_and_installs_them_by_number() {
+    let cache = tempfile::tempdir().unwrap();
+    let mut builder = IndexBuilder::new();
+    builder.add([
+        PackageInfo {
+            repo: Some("extra".to_string()),
+            installed_size: Some(3 * 1024 * 1024),
+            ..PackageInfo::new(
+                Source::Pacman,
+                "gimp",
+                "2.10.38-1",
+                "GNU Image Manipulation Program",
+            )
+        },
+        PackageInfo::new(Source::Aur, "gimp-git", "3.0.0.r1-1", "GIMP from git"),
+        PackageInfo {
+            app_id: Some("org.gimp.GIMP".to_string()),
+            repo: Some("flathub".to_string()),
+            ..PackageInfo::new(Source::Flatpak, "GIMP (org.gimp.GIMP)", "2.10.38", "")
+        },
+    ]);
+    builder
+        .write(&cache.path().join("package-dictionary/index"))
+        .unwrap();
+    let pd = |args: &[&str]| {
+        Command::new(env!("CARGO_BIN_EXE_pd"))
+            .args(args)
+            .env("PD_CONFIG", "/nonexistent/config.toml")
+            .env("XDG_CACHE_HOME", cache.path())
+            .output()
+            .unwrap()
+    };
+
+    let search = pd(&["--offline", "--group", "--no-pager", "gimp"]);
+    assert!(search.status.success());
+    let search = String::from_utf8_lossy(&search.stdout);
+    assert!(search.contains("gimp\n  GNU Image Manipulation Program\n"));
+    assert!(search.contains("  1. gimp                  2.10.38-1   Pacman (extra)     3.0 MiB\n"));
+
+    let install = pd(&["install", "--dry-run", "3"]);
+    assert!(install.status.success());
+    assert_eq!(
+        String::from_utf8_lossy(&install.stdout),
+        "flatpak install flathub org.gimp.GIMP\n"
+    );
+}
+
+#[test]
+fn filters_flatpaks_by_remote_and_kind() {
+    let cache = tempfile::tempdir().unwrap();
+    let flatpak = |name: &str, app_id: &str, remote: &str, kind| PackageInfo {
+        app_id: Some(app_id.to_string()),
+        repo: Some(remote.to_string()),
+        kind: Some(kind),
+        ..PackageInfo::new(
+            Source::Flatpak,
+            format!("{} ({})", name, app_id),
+            "1.0",
+            "GNOME things",
+        )
+    };
+    let mut builder = IndexBuilder::new();
+    builder.add([
+        flatpak("Boxes", "org.gnome.Boxes", "flathub", FlatpakKind::App),
+        flatpak(
+            "Boxes",
+            "org.gnome.Boxes",
+            "gnome-nightly",
+            FlatpakKind::App,
+        ),
+        flatpak(
+            "Platform",
+            "org.gnome.Platform",
+            "flathub",
+            FlatpakKind::Runtime,
+        ),
+        flatpak(
+            "Boxes Locale",
+            "org.gnome.Boxes.Locale",
+            "flathub",
+            FlatpakKind::Extension,
+        ),
+    ]);
+    builder
+        .write(&cache.path().join("package-dictionary/index"))
+        .unwrap();
+    let search = |args: &[&str]| {
+        let output = Command::new(env!("CARGO_BIN_EXE_pd"))
+            .args(["--offline", "--flatpak", "--format", "ndjson", "gnome"])
+            .args(args)
+            .env("PD_CONFIG", "/nonexistent/config.toml")
+            .env("XDG_CACHE_HOME", cache.path())
+            .output()
+            .unwrap();
+        assert!(output.status.success());
+        let mut found: Vec<String> = String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .map(|line| {
+                let package: PackageInfo = serde_json::from_str(line).unwrap();
+                format!("{}/{}", package.repo.unwrap(), package.app_id.unwrap())
+            })
+            .collect();
+        found.sort();
+        found
+    };
+
+    assert_eq!(
+        search(&[]),
+        [
+            "flathub/org.gnome.Boxes",
+            "flathub/org.gnome.Boxes.Locale",
+            "gnome-nightly/org.gnome.Boxes"
+        ]
+    );
+    assert_eq!(
+        search(&["--apps-only", "--flatpak-remote", "flathub"]),
+        ["flathub/org.gnome.Boxes"]
+    );
+    assert_eq!(search(&["--include-runtimes"]).len(), 4);
+}
+
+#[test]
+fn shows_installed_flatpak_permissions() {
+    let cache = tempfile::tempdir().unwrap();
+    let data = tempfile::tempdir().unwrap();
+    let mut builder = IndexBuilder::new();
+    builder.add([PackageInfo {
+        app_id: Some("org.gimp.GIMP".to_string()),
+        repo: Some("flathub".to_string()),
+        ..PackageInfo::new(Source::Flatpak, "GIMP (org.gimp.GIMP)", "2.10.38", "")
+    }]);
+    builder
+        .write(&cache.path().join("package-dictionary/index"))
+        .unwrap();
+    let deployed = data.path().join("flatpak/app/org.gimp.GIMP/current/active");
+    std::fs::create_dir_all(&deployed).unwrap();
+    std::fs::write(
+        deployed.join("metadata"),
+        "[Context]\nshared=network;ipc;\nsockets=x11;wayland;\nfilesystems=host;\n",
+    )
+    .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_pd"))
+        .args(["info", "--offline", "--no-pager", "org.gimp.GIMP"])
+        .env("PD_CONFIG", "/nonexistent/config.toml")
+        .env("XDG_CACHE_HOME", cache.path())
+        .env("XDG_DATA_HOME", data.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let info = String::from_utf8_lossy(&output.stdout);
+    assert!(info.contains("Warning           full access to the host filesystem"));
+    assert!(info.contains("Network           yes\n"));
+    assert!(info.contains("Display           X11 and Wayland\n"));
+}
+
+#[test]
+fn ranks_index_results_like_live_ones() {
+    let cache = tempfile::tempdir().unwrap();
+    let packages = vec![
+        // BM25 favors the repeated term in the description
+        PackageInfo::new(
+            Source::Aur,
+            "librewolf",
+            "131.0-1",
+            "firefox fork, firefox without telemetry, firefox privacy",
+        ),
+        PackageInfo::new(Source::Aur, "firefox-nightly", "133.0a1-1", "Nightly"),
+        PackageInfo {
+            repo: Some("extra".to_string()),
+            ..PackageInfo::new(Source::Pacman, "firefox", "131.0-1", "Web browser")
+        },
+        PackageInfo::new(Source::Aur, "firefox", "131.0-1", "Web browser"),
+    ];
+    let mut builder = IndexBuilder::new();
+    builder.add(packages.clone());
+    builder
+        .write(&cache.path().join("package-dictionary/index"))
+        .unwrap();
+
+    let output = Command::new(env!("CARGO_BIN_EXE_pd"))
+        .args(["--offline", "--merged", "--format", "ndjson", "firefox"])
+        .env("PD_CONFIG", "/nonexistent/config.toml")
+        .env("XDG_CACHE_HOME", cache.path())
+        .output()
+        .unwrap();
+    assert!(output.status.success());
+    let found: Vec<(String, f64)> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(|line| {
+            let package: PackageInfo = serde_json::from_str(line).unwrap();
+            (package.name, package.score.unwrap())
+        })
+        .collect();
+
+    // The order and scores live results get
+    let mut live: Vec<(String, f64)> = packages
+        .iter()
+        .map(|p| (p.name.clone(), relevance(p, "firefox")))
+        .collect();
+    live.sort_by(|a, b| b.1.total_cmp(&a.1));
+    assert_eq!(found, live);
+    assert_eq!(found[3].0, "librewolf");
+}
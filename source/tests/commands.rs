@@ -0,0 +1,102 @@
+//! The command index behind `pd --cnf`.
+
+use std::fs::{self, File};
+use std::io::Write;
+
+use package_dictionary::commands::{guess_commands, CommandIndex, CommandIndexBuilder};
+use package_dictionary::{PackageInfo, Source};
+
+/// Write `<dir>/extra.files` holding the given `(entry, files)` pairs.
+fn files_dir(packages: &[(&str, &str)]) -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    let mut tar = tar::Builder::new(Vec::new());
+    for (entry, list) in packages {
+        let text = format!("%FILES%\n{}\n", list);
+        let mut header = tar::Header::new_gnu();
+        header.set_size(text.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        tar.append_data(&mut header, format!("{}/files", entry), text.as_bytes())
+            .unwrap();
+    }
+
+    let file = File::create(dir.path().join("extra.files")).unwrap();
+    let mut encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+    encoder.write_all(&tar.into_inner().unwrap()).unwrap();
+    encoder.finish().unwrap();
+    dir
+}
+
+fn aur(name: &str, provides: &[&str]) -> PackageInfo {
+    PackageInfo {
+        provides: provides.iter().map(|p| p.to_string()).collect(),
+        ..PackageInfo::new(Source::Aur, name, "1.0-1", "")
+    }
+}
+
+#[test]
+fn guesses_aur_and_flatpak_commands() {
+    assert_eq!(
+        guess_commands(&aur("dig-bin", &["libdig.so=1-64"])),
+        ["dig"]
+    );
+    assert_eq!(
+        guess_commands(&aur("neovim-git", &["neovim", "vim=9"])),
+        ["neovim", "vim"]
+    );
+
+    let gimp = PackageInfo {
+        app_id: Some("org.gimp.GIMP".to_string()),
+        provides: vec!["gimp-3.0".to_string()],
+        ..PackageInfo::new(Source::Flatpak, "GIMP (org.gimp.GIMP)", "3.0", "")
+    };
+    assert_eq!(guess_commands(&gimp), ["gimp", "gimp-3.0"]);
+
+    let pacman = PackageInfo::new(Source::Pacman, "bind", "9.20-1", "");
+    assert!(guess_commands(&pacman).is_empty());
+}
+
+#[test]
+fn looks_up_providers_repository_first() {
+    let files = files_dir(&[
+        (
+            "bind-9.20.2-1",
+            "usr/\nusr/bin/\nusr/bin/dig\nusr/bin/host\nusr/share/man/man1/dig.1.gz",
+        ),
+        ("ldns-1.8.4-1", "usr/bin/drill\nusr/bin/ldns-tools/dig"),
+    ]);
+    let mut builder = CommandIndexBuilder::new();
+    assert_eq!(builder.add_files_dir(files.path()).unwrap(), 3);
+    builder.add_packages(&[aur("dig-bin", &[])]);
+
+    let path = files.path().join("commands");
+    builder.write(&path).unwrap();
+    let index = CommandIndex::open(&path).unwrap();
+
+    let providers = index.lookup("dig");
+    let providers: Vec<(Source, &str, Option<&str>)> = providers
+        .iter()
+        .map(|p| (p.source, p.name.as_str(), p.repo.as_deref()))
+        .collect();
+    assert_eq!(
+        providers,
+        [
+            (Source::Pacman, "bind", Some("extra")),
+            (Source::Aur, "dig-bin", None),
+        ]
+    );
+    assert_eq!(index.lookup("drill")[0].name, "ldns");
+    assert_eq!(index.lookup("host")[0].name, "bind");
+    assert!(index.lookup("di").is_empty());
+    assert!(index.lookup("zzz").is_empty());
+    assert!(index.lookup("").is_empty());
+    assert!(index.lookup("ldns-tools").is_empty());
+}
+
+#[test]
+fn rejects_other_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("commands");
+    fs::write(&path, b"PDINDEX1 is the package index").unwrap();
+    assert!(CommandIndex::open(&path).is_err());
+}
@@ -0,0 +1,123 @@
+use std::time::Duration;
+
+use package_dictionary::backend::AurHelper;
+use package_dictionary::config::{AurSearch, Config, ConfigError};
+use package_dictionary::render::{ansi_color, ColorChoice, OutputFormat};
+use package_dictionary::Source;
+
+#[test]
+fn reads_config_file() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(
+        &path,
+        r#"
+backends = ["flatpak", "pacman"]
+format = "json"
+timeout = 2.5
+pager = []
+
+[timeouts]
+aur = 10
+
+[aur]
+search = "helper"
+helper = "yay"
+
+[colors]
+flatpak = "bright-magenta"
+"#,
+    )
+    .unwrap();
+
+    let config = Config::load(&path).unwrap();
+    assert_eq!(config.backends, [Source::Flatpak, Source::Pacman]);
+    assert_eq!(config.format, OutputFormat::Json);
+    assert_eq!(
+        config.timeout_for(Source::Pacman),
+        Duration::from_millis(2500)
+    );
+    assert_eq!(config.timeout_for(Source::Aur), Duration::from_secs(10));
+    assert!(config.pager.is_empty());
+    assert_eq!(config.aur.search, AurSearch::Helper);
+    assert_eq!(config.aur.helper, Some(AurHelper::Yay));
+
+    let theme = config.theme().unwrap();
+    assert_eq!(theme.flatpak, "\x1B[95m");
+    assert_eq!(theme.pacman, "\x1B[34m");
+}
+
+#[test]
+fn missing_file_uses_defaults() {
+    let dir = tempfile::tempdir().unwrap();
+    let config = Config::load(&dir.path().join("config.toml")).unwrap();
+    assert_eq!(
+        config.backends,
+        [Source::Pacman, Source::Aur, Source::Flatpak]
+    );
+    assert_eq!(config.timeout_for(Source::Flatpak), Duration::from_secs(5));
+    assert_eq!(config.pager, ["less", "-R", "-F"]);
+}
+
+#[test]
+fn rejects_unknown_keys() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, "timout = 3\n").unwrap();
+    assert!(matches!(Config::load(&path), Err(ConfigError::Parse(..))));
+}
+
+#[test]
+fn environment_then_overrides() {
+    let mut config = Config::default();
+    config
+        .apply_env([
+            ("PD_BACKENDS", "aur, pacman"),
+            ("PD_TIMEOUT_FLATPAK", "1"),
+            ("PD_PAGER", "more -d"),
+            ("PD_COLOR", "always"),
+            ("HOME", "/ignored"),
+        ])
+        .unwrap();
+    config.set("backends", "pacman").unwrap();
+
+    assert_eq!(config.backends, [Source::Pacman]);
+    assert_eq!(config.timeout_for(Source::Flatpak), Duration::from_secs(1));
+    assert_eq!(config.pager, ["more", "-d"]);
+    assert_eq!(config.color, ColorChoice::Always);
+
+    assert!(config.set("timeout", "soon").is_err());
+    assert!(config.set("color", "sometimes").is_err());
+    assert!(config.set("colors.aur", "mauve").is_ok());
+    assert!(config.theme().is_err());
+}
+
+#[test]
+fn color_names() {
+    assert_eq!(ansi_color("red").unwrap(), "\x1B[31m");
+    assert_eq!(ansi_color("208").unwrap(), "\x1B[38;5;208m");
+    assert_eq!(ansi_color("none").unwrap(), "");
+    assert!(ansi_color("bright-mauve").is_err());
+}
+
+#[test]
+fn prints_settings_as_config_file() {
+    let mut config = Config::default();
+    config.set("timeouts.aur", "10").unwrap();
+    config.set("aur.helper", "yay").unwrap();
+
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("config.toml");
+    std::fs::write(&path, config.to_toml()).unwrap();
+    let reloaded = Config::load(&path).unwrap();
+    assert_eq!(reloaded.timeout_for(Source::Aur), Duration::from_secs(10));
+    assert_eq!(reloaded.aur.helper, Some(AurHelper::Yay));
+    assert_eq!(reloaded.backends, config.backends);
+}
+
+#[test]
+fn color_choice() {
+    assert!(ColorChoice::Always.enabled(false));
+    assert!(!ColorChoice::Never.enabled(true));
+    assert!(!ColorChoice::Auto.enabled(false));
+}
@@ -0,0 +1,153 @@
+//! `pd provides`: file ownership from the files databases and the local
+//! database.
+
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+
+use package_dictionary::backend::FilesBackend;
+use package_dictionary::files::{search_files_db, FileQuery};
+use package_dictionary::{Backend, Source};
+
+const SYNC_FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/sync");
+
+/// Pack `tests/fixtures/sync/<repo>` with a `files` entry per package into
+/// `<dir>/<repo>.files`, and the plain sync database into `<dir>/<repo>.db`.
+fn pack_repo(dir: &Path, repo: &str, files: &[(&str, &str)]) {
+    let mut tar = tar::Builder::new(Vec::new());
+    tar.append_dir_all(".", Path::new(SYNC_FIXTURES).join(repo))
+        .unwrap();
+    let db = tar.into_inner().unwrap();
+    fs::write(dir.join(format!("{}.db", repo)), &db).unwrap();
+
+    let mut tar = tar::Builder::new(Vec::new());
+    for (entry, list) in files {
+        let text = format!("%FILES%\n{}\n", list);
+        let mut header = tar::Header::new_gnu();
+        header.set_size(text.len() as u64);
+        header.set_mode(0o644);
+        header.set_cksum();
+        tar.append_data(&mut header, format!("{}/files", entry), text.as_bytes())
+            .unwrap();
+    }
+    tar.append_dir_all(".", Path::new(SYNC_FIXTURES).join(repo))
+        .unwrap();
+
+    let file = File::create(dir.join(format!("{}.files", repo))).unwrap();
+    let mut encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+    encoder.write_all(&tar.into_inner().unwrap()).unwrap();
+    encoder.finish().unwrap();
+}
+
+fn fixture_sync_dir() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    pack_repo(
+        dir.path(),
+        "core",
+        &[(
+            "bash-5.2.037-1",
+            "usr/\nusr/bin/\nusr/bin/bash\nusr/bin/sh\nusr/share/bash-completion/completions/firefox",
+        )],
+    );
+    pack_repo(
+        dir.path(),
+        "extra",
+        &[
+            (
+                "firefox-131.0-1",
+                "usr/\nusr/bin/\nusr/bin/firefox\nusr/lib/firefox/libxul.so",
+            ),
+            ("gedit-48.0-1", "usr/\nusr/bin/\nusr/bin/gedit"),
+        ],
+    );
+    dir
+}
+
+#[test]
+fn parses_queries() {
+    assert_eq!(
+        FileQuery::parse("/usr/bin/dig"),
+        FileQuery::Path("usr/bin/dig".to_string())
+    );
+    assert_eq!(
+        FileQuery::parse("/sbin/ip"),
+        FileQuery::Path("usr/bin/ip".to_string())
+    );
+    assert_eq!(
+        FileQuery::parse("/lib64/libc.so.6"),
+        FileQuery::Path("usr/lib/libc.so.6".to_string())
+    );
+    assert_eq!(
+        FileQuery::parse("libssl.so.3"),
+        FileQuery::Name("libssl.so.3".to_string())
+    );
+
+    let name = FileQuery::parse("bin");
+    assert!(!name.matches("usr/bin/"));
+    assert!(name.matches("usr/share/doc/foo/bin"));
+}
+
+#[test]
+fn finds_owners_by_path_and_name() {
+    let dir = fixture_sync_dir();
+    let extra = dir.path().join("extra.files");
+
+    let by_path = search_files_db(&extra, "extra", &FileQuery::parse("/usr/bin/gedit")).unwrap();
+    assert_eq!(by_path.len(), 1);
+    assert_eq!(by_path[0].name, "gedit");
+    assert_eq!(by_path[0].repo.as_deref(), Some("extra"));
+    assert_eq!(by_path[0].matched_files, ["/usr/bin/gedit"]);
+
+    let by_name = search_files_db(&extra, "extra", &FileQuery::parse("libxul.so")).unwrap();
+    assert_eq!(by_name[0].name, "firefox");
+    assert_eq!(by_name[0].matched_files, ["/usr/lib/firefox/libxul.so"]);
+}
+
+#[tokio::test]
+async fn commands_win_over_other_files_with_the_same_name() {
+    let dir = fixture_sync_dir();
+    let backend = FilesBackend::new(Source::Pacman)
+        .with_sync_dir(dir.path())
+        .with_local_dir(dir.path().join("local"));
+
+    // bash ships a completion called `firefox`, firefox ships the command
+    let owners = backend.search("firefox").await.unwrap();
+    let names: Vec<_> = owners.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, ["firefox"]);
+    assert_eq!(owners[0].matched_files, ["/usr/bin/firefox"]);
+
+    let owners = backend.search("/bin/sh").await.unwrap();
+    assert_eq!(owners[0].name, "bash");
+}
+
+#[tokio::test]
+async fn foreign_owners_come_from_the_local_database() {
+    let sync = fixture_sync_dir();
+    let local = tempfile::tempdir().unwrap();
+    for (name, version, files) in [
+        ("bash", "5.2.037-1", "usr/bin/bash\n"),
+        (
+            "yay",
+            "12.4.2-1",
+            "usr/bin/yay\nusr/share/man/man8/yay.8.gz\n",
+        ),
+    ] {
+        let dir = local.path().join(format!("{}-{}", name, version));
+        fs::create_dir_all(&dir).unwrap();
+        let desc = format!("%NAME%\n{}\n\n%VERSION%\n{}\n\n", name, version);
+        fs::write(dir.join("desc"), desc).unwrap();
+        fs::write(dir.join("files"), format!("%FILES%\n{}\n", files)).unwrap();
+    }
+
+    let backend = FilesBackend::new(Source::Aur)
+        .with_sync_dir(sync.path())
+        .with_local_dir(local.path());
+    let owners = backend.search("yay").await.unwrap();
+    assert_eq!(owners.len(), 1);
+    assert_eq!(owners[0].source, Source::Aur);
+    assert_eq!(owners[0].installed_version.as_deref(), Some("12.4.2-1"));
+    assert_eq!(owners[0].matched_files, ["/usr/bin/yay"]);
+
+    // bash is a repository package, so it is not listed as foreign
+    assert!(backend.search("bash").await.unwrap().is_empty());
+}
@@ -0,0 +1,69 @@
+//! `flatpak search` and `flatpak remote-ls` output, used without cached
+//! appstream data.
+
+use package_dictionary::backend::{parse_flatpak_output, parse_flatpak_refs};
+use package_dictionary::package::FlatpakKind;
+
+#[test]
+fn guesses_the_kind_of_search_results() {
+    let packages = parse_flatpak_output(
+        b"GIMP\torg.gimp.GIMP\t2.10.38\tstable\tflathub,flathub-beta\tCreate images\n\
+          GNOME Application Platform\torg.gnome.Platform\t47\t47\tflathub\tShared libraries\n\
+          Translations\torg.gimp.GIMP.Locale\t\tstable\tflathub\tTranslations\n",
+    );
+    let found: Vec<(&str, Option<&str>, Option<FlatpakKind>)> = packages
+        .iter()
+        .map(|p| (p.app_id.as_deref().unwrap(), p.repo.as_deref(), p.kind))
+        .collect();
+    assert_eq!(
+        found,
+        [
+            ("org.gimp.GIMP", Some("flathub"), Some(FlatpakKind::App)),
+            (
+                "org.gimp.GIMP",
+                Some("flathub-beta"),
+                Some(FlatpakKind::App)
+            ),
+            (
+                "org.gnome.Platform",
+                Some("flathub"),
+                Some(FlatpakKind::Runtime)
+            ),
+            (
+                "org.gimp.GIMP.Locale",
+                Some("flathub"),
+                Some(FlatpakKind::Extension)
+            ),
+        ]
+    );
+}
+
+#[test]
+fn reads_kinds_from_refs() {
+    let kinds = parse_flatpak_refs(
+        b"app/org.gimp.GIMP/x86_64/stable\n\
+          runtime/org.kde.Platform/x86_64/6.7\n\
+          runtime/org.freedesktop.Platform.GL.default/x86_64/23.08\n\
+          not a ref\n",
+    );
+    assert_eq!(kinds.len(), 3);
+    assert_eq!(kinds["org.gimp.GIMP"], FlatpakKind::App);
+    assert_eq!(kinds["org.kde.Platform"], FlatpakKind::Runtime);
+    assert_eq!(
+        kinds["org.freedesktop.Platform.GL.default"],
+        FlatpakKind::Extension
+    );
+}
+
+#[test]
+fn skips_column_titles_and_empty_results() {
+    // Flatpak only prints column titles to a terminal
+    let titled = parse_flatpak_output(
+        b"Name\tApplication ID\tVersion\tBranch\tRemotes\tDescription\n\
+          GIMP\torg.gimp.GIMP\t2.10.38\tstable\tflathub\tCreate images\n",
+    );
+    assert_eq!(titled.len(), 1);
+    assert_eq!(titled[0].app_id.as_deref(), Some("org.gimp.GIMP"));
+
+    assert!(parse_flatpak_output(b"No matches found\n").is_empty());
+}
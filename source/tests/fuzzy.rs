@@ -0,0 +1,31 @@
+//! Fuzzy filtering in the interactive picker.
+
+use package_dictionary::tui::fuzzy_score;
+
+#[test]
+fn matches_subsequences_ignoring_case() {
+    assert!(fuzzy_score("ffx", "firefox").is_some());
+    assert!(fuzzy_score("FireFox", "firefox").is_some());
+    // Whitespace in the query is ignored
+    assert!(fuzzy_score("fire fox", "firefox").is_some());
+    assert!(fuzzy_score("", "firefox").is_some());
+}
+
+#[test]
+fn rejects_characters_missing_or_out_of_order() {
+    assert_eq!(fuzzy_score("xf", "firefox"), None);
+    assert_eq!(fuzzy_score("firefoxx", "firefox"), None);
+    assert_eq!(fuzzy_score("vim", "emacs"), None);
+}
+
+#[test]
+fn prefers_contiguous_and_word_start_matches() {
+    let score = |query, candidate| fuzzy_score(query, candidate).unwrap();
+
+    // Contiguous beats scattered
+    assert!(score("fox", "foxtrot") > score("fox", "flatbox"));
+    // A match at the start of a word beats one inside a word
+    assert!(score("code", "vs-code") > score("code", "vscode"));
+    // The start of the name counts as a word start
+    assert!(score("fi", "firefox") > score("fi", "wifi"));
+}
@@ -0,0 +1,130 @@
+use package_dictionary::group::{base_name, group_packages};
+use package_dictionary::{PackageInfo, Source};
+
+fn package(source: Source, name: &str) -> PackageInfo {
+    PackageInfo::new(source, name, "1.0", format!("{} description", name))
+}
+
+fn flatpak(name: &str, app_id: &str) -> PackageInfo {
+    PackageInfo {
+        app_id: Some(app_id.to_string()),
+        ..package(Source::Flatpak, &format!("{} ({})", name, app_id))
+    }
+}
+
+fn names(groups: &[package_dictionary::group::AppGroup]) -> Vec<Vec<&str>> {
+    groups
+        .iter()
+        .map(|g| g.flavors.iter().map(|p| p.name.as_str()).collect())
+        .collect()
+}
+
+#[test]
+fn strips_aur_suffixes() {
+    assert_eq!(base_name("gimp-git"), "gimp");
+    assert_eq!(base_name("yay-bin"), "yay");
+    assert_eq!(base_name("gimp"), "gimp");
+}
+
+#[test]
+fn groups_flavors_by_name_and_app_id() {
+    let groups = group_packages(vec![
+        flatpak("GIMP", "org.gimp.GIMP"),
+        package(Source::Aur, "gimp-git"),
+        package(Source::Pacman, "inkscape"),
+        package(Source::Pacman, "gimp"),
+        flatpak("Telegram Desktop", "org.telegram.desktop"),
+        package(Source::Aur, "telegram-desktop-bin"),
+    ]);
+
+    assert_eq!(
+        names(&groups),
+        vec![
+            vec!["gimp", "gimp-git", "GIMP (org.gimp.GIMP)"],
+            vec!["inkscape"],
+            vec![
+                "telegram-desktop-bin",
+                "Telegram Desktop (org.telegram.desktop)"
+            ],
+        ]
+    );
+    assert_eq!(groups[0].name, "gimp");
+    assert_eq!(groups[2].name, "telegram-desktop");
+}
+
+#[test]
+fn links_upstream_urls_across_sources_only() {
+    let with_url = |source, name: &str, url: &str| PackageInfo {
+        url: Some(url.to_string()),
+        ..package(source, name)
+    };
+    let groups = group_packages(vec![
+        with_url(
+            Source::Pacman,
+            "code",
+            "https://github.com/microsoft/vscode",
+        ),
+        with_url(
+            Source::Pacman,
+            "code-marketplace",
+            "https://github.com/microsoft/vscode",
+        ),
+        PackageInfo {
+            url: Some("http://www.github.com/Microsoft/vscode/".to_string()),
+            ..flatpak("Visual Studio Code", "com.visualstudio.editor")
+        },
+    ]);
+
+    assert_eq!(
+        names(&groups),
+        vec![
+            vec!["code", "Visual Studio Code (com.visualstudio.editor)"],
+            vec!["code-marketplace"],
+        ]
+    );
+}
+
+#[test]
+fn links_drop_in_replacements() {
+    let groups = group_packages(vec![
+        package(Source::Pacman, "vim"),
+        PackageInfo {
+            provides: vec!["vim=9.1".to_string(), "xxd".to_string()],
+            conflicts: vec!["vim".to_string()],
+            ..package(Source::Aur, "vim-huge")
+        },
+        // Conflicting without providing is not a replacement
+        PackageInfo {
+            conflicts: vec!["vim".to_string()],
+            ..package(Source::Aur, "neovim-symlinks")
+        },
+    ]);
+
+    assert_eq!(
+        names(&groups),
+        vec![vec!["vim", "vim-huge"], vec!["neovim-symlinks"]]
+    );
+}
+
+#[test]
+fn links_id_parts_to_native_packages_only() {
+    let groups = group_packages(vec![
+        flatpak("KDE Application Platform", "org.kde.Platform"),
+        flatpak("GNOME Application Platform", "org.gnome.Platform"),
+        flatpak("org.gimp.GIMP.Locale", "org.gimp.GIMP.Locale"),
+        flatpak("org.gnome.Boxes.Locale", "org.gnome.Boxes.Locale"),
+        flatpak("Visual Studio Code", "com.visualstudio.code"),
+        package(Source::Pacman, "code"),
+    ]);
+
+    assert_eq!(
+        names(&groups),
+        vec![
+            vec!["KDE Application Platform (org.kde.Platform)"],
+            vec!["GNOME Application Platform (org.gnome.Platform)"],
+            vec!["org.gimp.GIMP.Locale (org.gimp.GIMP.Locale)"],
+            vec!["org.gnome.Boxes.Locale (org.gnome.Boxes.Locale)"],
+            vec!["code", "Visual Studio Code (com.visualstudio.code)"],
+        ]
+    );
+}
@@ -0,0 +1,253 @@
+use std::fs;
+use std::io::Write;
+
+use package_dictionary::aur_rpc::read_metadata_dump;
+use package_dictionary::index::{Index, IndexBuilder};
+use package_dictionary::{PackageInfo, Source};
+
+const ALL: [Source; 3] = [Source::Pacman, Source::Aur, Source::Flatpak];
+
+fn package(source: Source, name: &str, description: &str) -> PackageInfo {
+    PackageInfo::new(source, name, "1.0-1", description)
+}
+
+fn build(packages: Vec<PackageInfo>) -> (tempfile::TempDir, Index) {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("index");
+    let mut builder = IndexBuilder::new();
+    builder.add(packages);
+    builder.write(&path).unwrap();
+
+    let index = Index::open(&path)
+        .unwrap()
+        .with_local_dir(dir.path().join("local"));
+    (dir, index)
+}
+
+fn names(results: &[package_dictionary::search::SourceResults], source: Source) -> Vec<String> {
+    results
+        .iter()
+        .find(|r| r.source == source)
+        .map(|r| r.packages.iter().map(|p| p.name.clone()).collect())
+        .unwrap_or_default()
+}
+
+fn fixture() -> Vec<PackageInfo> {
+    vec![
+        package(
+            Source::Pacman,
+            "vim",
+            "Vi Improved, a highly configurable text editor",
+        ),
+        package(
+            Source::Pacman,
+            "gvim",
+            "Vi Improved, with a graphical interface",
+        ),
+        package(
+            Source::Pacman,
+            "neovim",
+            "Fork of Vim aiming to improve user experience",
+        ),
+        PackageInfo {
+            groups: vec!["vim-plugins".to_string()],
+            ..package(Source::Pacman, "vim-airline", "Lean & mean status line")
+        },
+        PackageInfo {
+            keywords: vec!["editor".to_string()],
+            ..package(Source::Aur, "helix-git", "A post-modern modal text editor")
+        },
+        PackageInfo {
+            provides: vec!["libfoo.so=1-64".to_string()],
+            ..package(Source::Aur, "foo-bin", "Foo utilities")
+        },
+        PackageInfo {
+            app_id: Some("org.gnome.TextEditor".to_string()),
+            ..package(
+                Source::Flatpak,
+                "Text Editor (org.gnome.TextEditor)",
+                "Edit text files",
+            )
+        },
+    ]
+}
+
+#[test]
+fn ranks_name_matches_first() {
+    let (_dir, index) = build(fixture());
+    let results = index.search("vim", &ALL).unwrap();
+
+    assert_eq!(names(&results, Source::Pacman)[0], "vim");
+    assert!(names(&results, Source::Pacman).contains(&"neovim".to_string()));
+    let scores: Vec<f64> = results[0]
+        .packages
+        .iter()
+        .map(|p| p.score.unwrap())
+        .collect();
+    assert!(scores.windows(2).all(|pair| pair[0] >= pair[1]));
+}
+
+#[test]
+fn every_word_must_match() {
+    let (_dir, index) = build(fixture());
+    let results = index.search("text editor", &ALL).unwrap();
+
+    assert_eq!(names(&results, Source::Pacman), ["vim"]);
+    assert_eq!(names(&results, Source::Aur), ["helix-git"]);
+    assert_eq!(
+        names(&results, Source::Flatpak),
+        ["Text Editor (org.gnome.TextEditor)"]
+    );
+}
+
+#[test]
+fn matches_prefixes_keywords_and_provides() {
+    let (_dir, index) = build(fixture());
+
+    let results = index.search("improv", &ALL).unwrap();
+    assert_eq!(names(&results, Source::Pacman).len(), 3);
+    // Short words only match whole terms
+    assert!(names(&index.search("vi", &ALL).unwrap(), Source::Pacman).len() == 2);
+
+    assert_eq!(
+        names(&index.search("libfoo", &ALL).unwrap(), Source::Aur),
+        ["foo-bin"]
+    );
+    assert_eq!(
+        names(&index.search("plugins", &ALL).unwrap(), Source::Pacman),
+        ["vim-airline"]
+    );
+}
+
+#[test]
+fn filters_and_orders_sources() {
+    let (_dir, index) = build(fixture());
+    let results = index
+        .search("editor", &[Source::Flatpak, Source::Aur])
+        .unwrap();
+
+    let sources: Vec<Source> = results.iter().map(|r| r.source).collect();
+    assert_eq!(sources, [Source::Flatpak, Source::Aur]);
+    assert_eq!(index.count(Source::Pacman), 4);
+}
+
+#[test]
+fn marks_installed_packages() {
+    let (dir, index) = build(fixture());
+    fs::create_dir_all(dir.path().join("local/vim-9.1-1")).unwrap();
+
+    let results = index.search("vim", &[Source::Pacman]).unwrap();
+    let vim = &results[0].packages[0];
+    assert_eq!(vim.installed_version.as_deref(), Some("9.1-1"));
+}
+
+#[test]
+fn looks_up_exact_names_and_application_ids() {
+    let (_dir, index) = build(fixture());
+
+    let results = index.lookup("vim", &ALL).unwrap();
+    assert_eq!(names(&results, Source::Pacman), ["vim"]);
+    assert!(names(&results, Source::Aur).is_empty());
+
+    let by_id = index.lookup("org.gnome.TextEditor", &ALL).unwrap();
+    let by_name = index.lookup("text editor", &ALL).unwrap();
+    for results in [by_id, by_name] {
+        assert_eq!(
+            names(&results, Source::Flatpak),
+            ["Text Editor (org.gnome.TextEditor)"]
+        );
+    }
+}
+
+#[test]
+fn lists_install_names() {
+    let (_dir, index) = build(fixture());
+    let names: Vec<&str> = index.names().collect();
+    assert!(names.contains(&"vim-airline"));
+    assert!(names.contains(&"helix-git"));
+    assert!(names.contains(&"org.gnome.TextEditor"));
+    assert_eq!(names.len(), fixture().len());
+}
+
+#[test]
+fn rejects_other_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("index");
+    fs::write(&path, b"not an index, just some text").unwrap();
+    assert!(Index::open(&path).is_err());
+}
+
+#[test]
+fn reads_aur_metadata_dump() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("packages-meta-ext-v1.json.gz");
+    let json = r#"[{"ID":1,"Name":"yay","PackageBase":"yay","Version":"12.4.2-1",
+        "Description":"Yet another yogurt","NumVotes":2400,"Popularity":20.5,
+        "OutOfDate":null,"Maintainer":"jguer","FirstSubmitted":1475000000,
+        "LastModified":1727000000,"URLPath":"/cgit/aur.git/snapshot/yay.tar.gz",
+        "Keywords":["aur","helper"],"Depends":["pacman>6.1"]}]"#;
+    let mut encoder = flate2::write::GzEncoder::new(
+        fs::File::create(&path).unwrap(),
+        flate2::Compression::default(),
+    );
+    encoder.write_all(json.as_bytes()).unwrap();
+    encoder.finish().unwrap();
+
+    let packages = read_metadata_dump(&path).unwrap();
+    assert_eq!(packages.len(), 1);
+    assert_eq!(packages[0].source, Source::Aur);
+    assert_eq!(packages[0].keywords, ["aur", "helper"]);
+    assert_eq!(packages[0].depends, ["pacman>6.1"]);
+
+    let (_dir, index) = build(packages);
+    let results = index.search("helper", &ALL).unwrap();
+    assert_eq!(names(&results, Source::Aur), ["yay"]);
+    assert_eq!(results[1].packages[0].votes, Some(2400));
+}
+
+#[test]
+fn rejects_truncated_files() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("index");
+    let mut builder = IndexBuilder::new();
+    builder.add(fixture());
+    builder.write(&path).unwrap();
+    let index = fs::read(&path).unwrap();
+
+    // A header length far beyond the end of the file
+    let mut huge_header = index.clone();
+    huge_header[8..16].copy_from_slice(&u64::MAX.to_le_bytes());
+    fs::write(&path, &huge_header).unwrap();
+    let error = Index::open(&path).err().unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    assert!(error.to_string().contains("pd index update"), "{}", error);
+
+    for len in [16, 32, index.len() / 2] {
+        fs::write(&path, &index[..len]).unwrap();
+        assert!(Index::open(&path).is_err(), "opened {} bytes", len);
+    }
+}
+
+#[test]
+fn rejects_out_of_range_documents() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("index");
+    let mut builder = IndexBuilder::new();
+    builder.add([package(Source::Pacman, "vim", "aaa")]);
+    builder.write(&path).unwrap();
+
+    // The first posting is the first term's, `aaa`
+    let mut index = fs::read(&path).unwrap();
+    let header_len = u64::from_le_bytes(index[8..16].try_into().unwrap()) as usize;
+    let postings = 16 + header_len;
+    index[postings..postings + 4].copy_from_slice(&u32::MAX.to_le_bytes());
+    fs::write(&path, &index).unwrap();
+
+    let index = Index::open(&path).unwrap();
+    let error = index.search("aaa", &ALL).err().unwrap();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+    assert_eq!(
+        names(&index.search("vim", &ALL).unwrap(), Source::Pacman),
+        ["vim"]
+    );
+}
@@ -0,0 +1,104 @@
+//! The `pd info` card.
+
+use package_dictionary::render::{format_info, Theme};
+use package_dictionary::{PackageInfo, Source};
+
+fn strip(text: &str) -> String {
+    package_dictionary::parse::strip_ansi(text)
+}
+
+fn packages() -> Vec<PackageInfo> {
+    vec![
+        PackageInfo {
+            repo: Some("extra".to_string()),
+            depends: vec!["gtk3".to_string(), "nss".to_string()],
+            compressed_size: Some(3 * 1024 * 1024),
+            ..PackageInfo::new(Source::Pacman, "firefox", "131.0-1", "Web browser")
+        },
+        PackageInfo {
+            repo: Some("aur".to_string()),
+            votes: Some(12),
+            orphaned: true,
+            last_modified: Some(1_727_740_800),
+            out_of_date: Some(0),
+            ..PackageInfo::new(Source::Aur, "firefox", "132.0a1-1", "Nightly build")
+        },
+    ]
+}
+
+#[test]
+fn shows_every_source_side_by_side() {
+    let card = strip(&format_info(&packages(), 100, &Theme::default()));
+    let line = |label: &str| {
+        card.lines()
+            .find(|line| line.starts_with(label))
+            .unwrap_or_else(|| panic!("no {} row in\n{}", label, card))
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+    };
+
+    assert_eq!(line("Source"), "Source Pacman (extra) AUR");
+    assert_eq!(line("Version"), "Version 131.0-1 132.0a1-1");
+    assert_eq!(line("Depends On"), "Depends On gtk3 nss None");
+    assert_eq!(line("Download Size"), "Download Size 3.0 MiB None");
+    assert_eq!(line("Maintainer"), "Maintainer None orphan");
+    assert_eq!(line("Out Of Date"), "Out Of Date None yes");
+    // Rows without any value are left out
+    assert!(!card.contains("Replaces"));
+}
+
+#[test]
+fn wraps_long_values_within_their_column() {
+    let mut packages = packages();
+    packages[0].description = "word ".repeat(20);
+    let card = strip(&format_info(&packages, 80, &Theme::default()));
+
+    let description: Vec<&str> = card
+        .lines()
+        .skip_while(|line| !line.starts_with("Description"))
+        .take_while(|line| !line.starts_with("Depends On"))
+        .collect();
+    assert!(description.len() > 1);
+    assert!(description[0].ends_with("Nightly build"));
+    assert!(card.lines().all(|line| line.chars().count() <= 80));
+}
+
+#[test]
+fn flatpaks_are_named_by_display_name_or_id() {
+    let gimp = PackageInfo {
+        app_id: Some("org.gimp.GIMP".to_string()),
+        ..PackageInfo::new(Source::Flatpak, "GIMP (org.gimp.GIMP)", "3.0", "Editor")
+    };
+    assert!(gimp.is_named("gimp"));
+    assert!(gimp.is_named("org.gimp.GIMP"));
+    assert!(!gimp.is_named("gim"));
+
+    let pacman = PackageInfo::new(Source::Pacman, "gimp", "3.0-1", "Editor");
+    assert!(pacman.is_named("gimp"));
+    assert!(!pacman.is_named("GIMP"));
+}
+
+#[test]
+fn helper_results_have_no_maintainer() {
+    // `yay -Ss` output has votes and popularity, but not the maintainer
+    let helper = PackageInfo {
+        repo: Some("aur".to_string()),
+        votes: Some(12),
+        popularity: Some(0.5),
+        ..PackageInfo::new(Source::Aur, "firefox-nightly", "132.0a1-1", "Nightly")
+    };
+    let card = strip(&format_info(&[helper], 100, &Theme::default()));
+    assert!(card.contains("Votes"));
+    assert!(!card.contains("Maintainer"), "{}", card);
+    assert!(!card.contains("orphan"), "{}", card);
+
+    // Unless the helper marked it as orphaned
+    let orphan = package_dictionary::parse::parse_search_output(
+        b"aur/firefox-nightly 132.0a1-1 (+12 0.50) (Orphaned)\n    Nightly\n",
+        Source::Aur,
+    );
+    let card = strip(&format_info(&orphan, 100, &Theme::default()));
+    assert!(card.contains("Maintainer"), "{}", card);
+    assert!(card.contains("orphan"), "{}", card);
+}
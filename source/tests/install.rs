@@ -0,0 +1,152 @@
+use package_dictionary::backend::AurHelper;
+use package_dictionary::install::{
+    install_commands, install_origin, load_last_search, save_last_search, InstallTarget,
+};
+use package_dictionary::{PackageInfo, Source};
+
+fn packages() -> Vec<PackageInfo> {
+    vec![
+        PackageInfo {
+            repo: Some("extra".to_string()),
+            ..PackageInfo::new(Source::Pacman, "firefox", "131.0-1", "Web browser")
+        },
+        PackageInfo::new(Source::Aur, "yay-bin", "12.4.2-1", "AUR helper"),
+        PackageInfo {
+            repo: Some("flathub".to_string()),
+            app_id: Some("org.gimp.GIMP".to_string()),
+            ..PackageInfo::new(Source::Flatpak, "GIMP (org.gimp.GIMP)", "2.10", "Editor")
+        },
+    ]
+}
+
+#[test]
+fn parses_targets() {
+    assert_eq!(InstallTarget::parse("3"), InstallTarget::Number(3));
+    assert_eq!(
+        InstallTarget::parse("0"),
+        InstallTarget::Name("0".to_string())
+    );
+    assert_eq!(
+        InstallTarget::parse("7zip"),
+        InstallTarget::Name("7zip".to_string())
+    );
+}
+
+#[test]
+fn builds_one_command_per_source() {
+    let commands: Vec<String> = install_commands(&packages(), AurHelper::Yay)
+        .iter()
+        .map(ToString::to_string)
+        .collect();
+    assert_eq!(
+        commands,
+        [
+            "sudo pacman -S extra/firefox",
+            "yay -S --aur yay-bin",
+            "flatpak install flathub org.gimp.GIMP",
+        ]
+    );
+
+    let origins: Vec<String> = packages().iter().map(install_origin).collect();
+    assert_eq!(
+        origins,
+        ["extra/firefox", "aur/yay-bin", "flathub/org.gimp.GIMP"]
+    );
+}
+
+#[test]
+fn installs_flatpaks_from_the_chosen_remote_and_branch() {
+    let flatpak = |remote: Option<&str>, app_id: &str, branch: Option<&str>| PackageInfo {
+        repo: remote.map(str::to_string),
+        app_id: Some(app_id.to_string()),
+        branch: branch.map(str::to_string),
+        ..PackageInfo::new(Source::Flatpak, app_id, "1.0", "")
+    };
+    let commands: Vec<String> = install_commands(
+        &[
+            flatpak(Some("flathub-beta"), "org.gimp.GIMP", Some("beta")),
+            flatpak(Some("flathub"), "org.inkscape.Inkscape", Some("stable")),
+            flatpak(Some("flathub-beta"), "org.kde.krita", None),
+            flatpak(None, "org.gnome.Boxes", Some("stable")),
+        ],
+        AurHelper::Yay,
+    )
+    .iter()
+    .map(ToString::to_string)
+    .collect();
+    assert_eq!(
+        commands,
+        [
+            "flatpak install flathub-beta org.gimp.GIMP//beta org.kde.krita",
+            "flatpak install flathub org.inkscape.Inkscape//stable",
+            "flatpak install org.gnome.Boxes//stable",
+        ]
+    );
+}
+
+#[test]
+fn installs_flatpaks_into_their_installation() {
+    let flatpak = |app_id: &str, installations: &[&str]| PackageInfo {
+        repo: Some("flathub".to_string()),
+        app_id: Some(app_id.to_string()),
+        installations: installations.iter().map(|i| i.to_string()).collect(),
+        ..PackageInfo::new(Source::Flatpak, app_id, "1.0", "")
+    };
+    let commands: Vec<String> = install_commands(
+        &[
+            flatpak("org.gimp.GIMP", &["user"]),
+            flatpak("org.kde.krita", &["system", "user"]),
+            flatpak("org.gnome.Boxes", &[]),
+            flatpak("org.inkscape.Inkscape", &["user"]),
+            flatpak("org.videolan.VLC", &["extra"]),
+        ],
+        AurHelper::Yay,
+    )
+    .iter()
+    .map(ToString::to_string)
+    .collect();
+    assert_eq!(
+        commands,
+        [
+            "flatpak install --user flathub org.gimp.GIMP org.inkscape.Inkscape",
+            "flatpak install --system flathub org.kde.krita",
+            "flatpak install flathub org.gnome.Boxes",
+            "flatpak install --installation=extra flathub org.videolan.VLC",
+        ]
+    );
+}
+
+#[test]
+fn installs_from_the_repository_found_in() {
+    let pacman = |repo: Option<&str>, name: &str| PackageInfo {
+        repo: repo.map(str::to_string),
+        ..PackageInfo::new(Source::Pacman, name, "1.0-1", "")
+    };
+    let commands: Vec<String> = install_commands(
+        &[
+            pacman(Some("core-testing"), "linux"),
+            pacman(Some("local"), "vim"),
+            pacman(None, "git"),
+        ],
+        AurHelper::Yay,
+    )
+    .iter()
+    .map(ToString::to_string)
+    .collect();
+    assert_eq!(commands, ["sudo pacman -S core-testing/linux vim git"]);
+}
+
+#[test]
+fn remembers_the_last_search() {
+    let dir = tempfile::tempdir().unwrap();
+    let path = dir.path().join("cache/last-search.json");
+    assert!(load_last_search(&path).is_err());
+
+    save_last_search(&path, &packages()).unwrap();
+    let names: Vec<String> = load_last_search(&path)
+        .unwrap()
+        .into_iter()
+        .map(|p| p.name)
+        .collect();
+    assert_eq!(names, ["firefox", "yay-bin", "GIMP (org.gimp.GIMP)"]);
+}
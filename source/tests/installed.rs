@@ -0,0 +1,205 @@
+//! Search installed packages: pacman's local database and `flatpak list`.
+
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+
+use package_dictionary::backend::{
+    mark_flatpak_installed, parse_flatpak_list, parse_installed_flatpaks, parse_size, LocalBackend,
+};
+use package_dictionary::localdb::read_local_db;
+use package_dictionary::package::{FlatpakKind, InstallReason};
+use package_dictionary::render::format_size;
+use package_dictionary::{Backend, Source};
+
+const SYNC_FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/sync");
+
+/// Write a local database entry the way pacman does.
+fn install(local_dir: &Path, name: &str, version: &str, description: &str, reason: Option<u8>) {
+    let dir = local_dir.join(format!("{}-{}", name, version));
+    fs::create_dir_all(&dir).unwrap();
+
+    let mut desc = format!(
+        "%NAME%\n{}\n\n%VERSION%\n{}\n\n%DESC%\n{}\n\n\
+%INSTALLDATE%\n1727740800\n\n%SIZE%\n12902400\n\n",
+        name, version, description
+    );
+    if let Some(reason) = reason {
+        desc.push_str(&format!("%REASON%\n{}\n\n", reason));
+    }
+    fs::write(dir.join("desc"), desc).unwrap();
+}
+
+fn fixture_dirs() -> (tempfile::TempDir, tempfile::TempDir) {
+    let local = tempfile::tempdir().unwrap();
+    fs::write(local.path().join("ALPM_DB_VERSION"), "9\n").unwrap();
+    install(
+        local.path(),
+        "bash",
+        "5.2.037-1",
+        "The GNU Bourne Again shell",
+        Some(1),
+    );
+    install(
+        local.path(),
+        "firefox",
+        "131.0-1",
+        "Standalone web browser",
+        None,
+    );
+    install(local.path(), "gedit", "47.0-1", "GNOME Text Editor", None);
+    install(
+        local.path(),
+        "firefox-pwa",
+        "2.12.5-1",
+        "Progressive web apps",
+        None,
+    );
+
+    let sync = tempfile::tempdir().unwrap();
+    for repo in ["core", "extra"] {
+        let mut tar = tar::Builder::new(Vec::new());
+        tar.append_dir_all(".", Path::new(SYNC_FIXTURES).join(repo))
+            .unwrap();
+        let file = File::create(sync.path().join(format!("{}.db", repo))).unwrap();
+        let mut encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+        encoder.write_all(&tar.into_inner().unwrap()).unwrap();
+        encoder.finish().unwrap();
+    }
+
+    (local, sync)
+}
+
+#[test]
+fn reads_install_reason_date_and_size() {
+    let (local, _sync) = fixture_dirs();
+    let packages = read_local_db(local.path()).unwrap();
+
+    let names: Vec<_> = packages.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, ["bash", "firefox", "firefox-pwa", "gedit"]);
+
+    let bash = &packages[0];
+    assert_eq!(bash.install_reason, Some(InstallReason::Dependency));
+    assert_eq!(bash.install_date, Some(1727740800));
+    assert_eq!(bash.installed_size, Some(12902400));
+    assert_eq!(bash.installed_version.as_deref(), Some("5.2.037-1"));
+    assert_eq!(packages[1].install_reason, Some(InstallReason::Explicit));
+}
+
+#[tokio::test]
+async fn foreign_packages_belong_to_the_aur() {
+    let (local, sync) = fixture_dirs();
+    let backend = |source| {
+        LocalBackend::new(source)
+            .with_local_dir(local.path())
+            .with_sync_dir(sync.path())
+    };
+
+    let pacman = backend(Source::Pacman).search("firefox").await.unwrap();
+    assert_eq!(pacman.len(), 1);
+    assert_eq!(pacman[0].name, "firefox");
+    assert_eq!(pacman[0].repo.as_deref(), Some("extra"));
+
+    let aur = backend(Source::Aur).search("firefox").await.unwrap();
+    assert_eq!(aur.len(), 1);
+    assert_eq!(aur[0].name, "firefox-pwa");
+    assert_eq!(aur[0].source, Source::Aur);
+
+    // An empty term lists everything
+    assert_eq!(backend(Source::Pacman).search("").await.unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn available_versions_come_from_the_sync_databases() {
+    let (local, sync) = fixture_dirs();
+    let backend = LocalBackend::new(Source::Pacman)
+        .with_local_dir(local.path())
+        .with_sync_dir(sync.path());
+
+    let packages = backend.search("").await.unwrap();
+    let upgradable: Vec<_> = packages
+        .iter()
+        .filter(|p| p.is_upgradable())
+        .map(|p| {
+            (
+                p.name.as_str(),
+                p.installed_version.as_deref(),
+                p.version.as_str(),
+            )
+        })
+        .collect();
+    assert_eq!(upgradable, [("gedit", Some("47.0-1"), "48.0-1")]);
+}
+
+#[test]
+fn parses_flatpak_list() {
+    let output = "Firefox\torg.mozilla.firefox\t131.0\tstable\tx86_64\tflathub\tsystem\t\
+251.3\u{a0}MB\tFast, Private & Safe Web Browser\n\
+\torg.example.NoName\t\tbeta\tx86_64\tflathub-beta\tuser\t1.0 kB\t\n";
+
+    let packages = parse_flatpak_list(output.as_bytes(), InstallReason::Explicit);
+    assert_eq!(packages.len(), 2);
+
+    let firefox = &packages[0];
+    assert_eq!(firefox.name, "Firefox (org.mozilla.firefox)");
+    assert_eq!(firefox.app_id.as_deref(), Some("org.mozilla.firefox"));
+    assert_eq!(firefox.repo.as_deref(), Some("flathub"));
+    assert_eq!(firefox.installed_version.as_deref(), Some("131.0"));
+    assert_eq!(firefox.install_reason, Some(InstallReason::Explicit));
+    assert_eq!(firefox.installed_size, Some(251_300_000));
+    assert_eq!(firefox.branch.as_deref(), Some("stable"));
+    assert_eq!(firefox.arch.as_deref(), Some("x86_64"));
+    assert_eq!(firefox.kind, Some(FlatpakKind::App));
+    assert_eq!(firefox.installations, ["system"]);
+
+    let unnamed = &packages[1];
+    assert_eq!(unnamed.name, "org.example.NoName (org.example.NoName)");
+    assert_eq!(unnamed.version, "beta");
+    assert_eq!(unnamed.description, "No description.");
+    assert_eq!(unnamed.installed_size, Some(1000));
+    assert_eq!(unnamed.repo.as_deref(), Some("flathub-beta"));
+    assert_eq!(unnamed.installations, ["user"]);
+
+    let runtimes = parse_flatpak_list(
+        b"Mesa\torg.freedesktop.Platform.GL.default\t24.2\t24.08\tx86_64\tflathub\tsystem\t\t\n\
+Freedesktop Platform\torg.freedesktop.Platform\t24.08.5\t24.08\tx86_64\tflathub\tsystem\t\t\n",
+        InstallReason::Dependency,
+    );
+    let kinds: Vec<_> = runtimes.iter().map(|p| p.kind).collect();
+    assert_eq!(
+        kinds,
+        [Some(FlatpakKind::Extension), Some(FlatpakKind::Runtime)]
+    );
+}
+
+#[test]
+fn marks_installed_flatpaks() {
+    let mut packages = parse_flatpak_list(
+        b"Firefox\torg.mozilla.firefox\t132.0\tstable\tx86_64\tflathub\tsystem\t\t\n\
+Krita\torg.kde.krita\t5.2.6\tstable\tx86_64\tflathub\tsystem\t\t\n",
+        InstallReason::Explicit,
+    );
+    let installed = parse_installed_flatpaks(
+        b"org.mozilla.firefox\t131.0\tsystem\n\
+org.mozilla.firefox\t131.0\tuser\n\
+org.kde.krita\t\tuser\n",
+    );
+    mark_flatpak_installed(&mut packages, &installed);
+
+    assert!(packages[0].is_upgradable());
+    assert_eq!(packages[0].installed_version.as_deref(), Some("131.0"));
+    assert_eq!(packages[0].installations, ["system", "user"]);
+    // Without an installed version there is nothing to compare
+    assert!(!packages[1].is_upgradable());
+    assert_eq!(packages[1].installations, ["user"]);
+}
+
+#[test]
+fn sizes() {
+    assert_eq!(parse_size("512 bytes"), Some(512));
+    assert_eq!(parse_size("1,5 GB"), Some(1_500_000_000));
+    assert_eq!(parse_size("huge"), None);
+
+    assert_eq!(format_size(512), "512 B");
+    assert_eq!(format_size(12902400), "12.3 MiB");
+}
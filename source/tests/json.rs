@@ -0,0 +1,85 @@
+//! The `--format json` and `--format ndjson` output scripts rely on.
+
+use package_dictionary::render::{write_json, write_ndjson};
+use package_dictionary::search::SourceResults;
+use package_dictionary::{PackageInfo, Source};
+use serde_json::{json, Value};
+
+fn packages() -> Vec<PackageInfo> {
+    vec![
+        PackageInfo {
+            repo: Some("extra".to_string()),
+            installed_version: Some("131.0-1".to_string()),
+            ..PackageInfo::new(Source::Pacman, "firefox", "131.0-1", "Web browser")
+        },
+        PackageInfo {
+            repo: Some("aur".to_string()),
+            votes: Some(40),
+            popularity: Some(0.5),
+            ..PackageInfo::new(Source::Aur, "firefox-esr-bin", "128.3.1-1", "ESR")
+        },
+    ]
+}
+
+#[test]
+fn ndjson_writes_one_object_per_line() {
+    let mut output = Vec::new();
+    write_ndjson(&mut output, &packages()).unwrap();
+    let output = String::from_utf8(output).unwrap();
+
+    assert!(output.ends_with('\n'));
+    let lines: Vec<Value> = output
+        .lines()
+        .map(|line| serde_json::from_str(line).unwrap())
+        .collect();
+    assert_eq!(
+        lines,
+        [
+            json!({
+                "source": "pacman",
+                "name": "firefox",
+                "version": "131.0-1",
+                "description": "Web browser",
+                "repo": "extra",
+                "installed_version": "131.0-1",
+            }),
+            json!({
+                "source": "aur",
+                "name": "firefox-esr-bin",
+                "version": "128.3.1-1",
+                "description": "ESR",
+                "repo": "aur",
+                "votes": 40,
+                "popularity": 0.5,
+            }),
+        ]
+    );
+}
+
+#[test]
+fn json_keys_results_by_source_in_backend_order() {
+    let packages = packages();
+    let results = [
+        SourceResults {
+            name: "AUR".to_string(),
+            source: Source::Aur,
+            packages: packages[1..].to_vec(),
+            error: None,
+        },
+        SourceResults {
+            name: "Pacman".to_string(),
+            source: Source::Pacman,
+            packages: packages[..1].to_vec(),
+            error: None,
+        },
+    ];
+    let mut output = Vec::new();
+    write_json(&mut output, &results).unwrap();
+    let output = String::from_utf8(output).unwrap();
+
+    let value: Value = serde_json::from_str(&output).unwrap();
+    assert_eq!(value["aur"][0]["name"], "firefox-esr-bin");
+    assert_eq!(value["pacman"][0]["installed_version"], "131.0-1");
+    // Keys keep the backend order rather than being sorted
+    assert!(output.find("\"aur\"").unwrap() < output.find("\"pacman\"").unwrap());
+}
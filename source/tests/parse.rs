@@ -0,0 +1,175 @@
+//! Header-line grammar of `pacman -Ss` and AUR helper output.
+
+use package_dictionary::parse::{
+    parse_header_line, parse_search_output, parse_single_line_output, strip_ansi,
+};
+use package_dictionary::Source;
+
+#[test]
+fn group_is_not_mistaken_for_version() {
+    let header = parse_header_line("extra/gedit 48.0-1 (gnome)").unwrap();
+    assert_eq!(header.repo, "extra");
+    assert_eq!(header.name, "gedit");
+    assert_eq!(header.version, "48.0-1");
+    assert_eq!(header.groups, ["gnome"]);
+    assert_eq!(header.installed_version, None);
+}
+
+#[test]
+fn installed_markers() {
+    let same = parse_header_line("extra/firefox 131.0-1 [installed]").unwrap();
+    assert_eq!(same.installed_version.as_deref(), Some("131.0-1"));
+
+    let older =
+        parse_header_line("extra/gedit 48.0-1 (gnome gnome-extra) [installed: 47.0-1]").unwrap();
+    assert_eq!(older.groups, ["gnome", "gnome-extra"]);
+    assert_eq!(older.installed_version.as_deref(), Some("47.0-1"));
+}
+
+#[test]
+fn aur_annotations() {
+    let header = parse_header_line(
+        "aur/firefox-esr-bin 128.3.1-1 (+40 0.51) (Out-of-date: 2024-10-01) (Orphaned) (Installed)",
+    )
+    .unwrap();
+    assert_eq!(header.repo, "aur");
+    assert_eq!(header.version, "128.3.1-1");
+    assert_eq!(header.votes, Some(40));
+    assert_eq!(header.popularity, Some(0.51));
+    assert_eq!(header.out_of_date, Some(1727740800));
+    assert!(header.orphaned);
+    assert_eq!(header.installed_version.as_deref(), Some("128.3.1-1"));
+    assert!(header.groups.is_empty());
+
+    let bracketed = parse_header_line("aur/paru 2.0.4-1 [+1234 ~12.34] [Out-of-date]").unwrap();
+    assert_eq!(bracketed.votes, Some(1234));
+    assert_eq!(bracketed.popularity, Some(12.34));
+    assert_eq!(bracketed.out_of_date, Some(0));
+}
+
+#[test]
+fn orphans_are_flagged() {
+    let packages = parse_search_output(
+        b"aur/foo 1.0-1 (+1 0.00) (Orphaned)\n    Foo\naur/bar 1.0-1 (+1 0.00)\n    Bar\n",
+        Source::Aur,
+    );
+    assert!(packages[0].orphaned);
+    assert!(!packages[1].orphaned);
+    // Helper output doesn't name maintainers
+    assert_eq!(packages[0].maintainer, None);
+    assert_eq!(packages[1].maintainer, None);
+}
+
+#[test]
+fn descriptions_are_paired_with_their_header() {
+    let output = b"extra/foo 1.0-1\n\
+extra/bar 2.0-1 (group)\n    Bar does things\n\
+aur/baz 3.0-1 (+1 0.00)\n    Baz\n";
+
+    let packages = parse_search_output(output, Source::Pacman);
+    let summary: Vec<_> = packages
+        .iter()
+        .map(|p| (p.name.as_str(), p.version.as_str(), p.description.as_str()))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            ("foo", "1.0-1", "No description."),
+            ("bar", "2.0-1", "Bar does things"),
+            ("baz", "3.0-1", "Baz"),
+        ]
+    );
+
+    let aur_only = parse_search_output(output, Source::Aur);
+    assert_eq!(aur_only.len(), 1);
+    assert_eq!(aur_only[0].name, "baz");
+}
+
+fn fixture(name: &str) -> Vec<u8> {
+    let path = format!(
+        "{}/tests/fixtures/search/{}",
+        env!("CARGO_MANIFEST_DIR"),
+        name
+    );
+    std::fs::read(path).unwrap()
+}
+
+#[test]
+fn translated_pacman_markers() {
+    let packages = parse_search_output(&fixture("pacman-de.txt"), Source::Pacman);
+    let installed: Vec<_> = packages
+        .iter()
+        .map(|p| (p.name.as_str(), p.installed_version.as_deref()))
+        .collect();
+    assert_eq!(
+        installed,
+        [
+            ("firefox", Some("131.0-1")),
+            ("gedit", Some("47.0-1")),
+            ("firefox-i18n-de", None),
+        ]
+    );
+    assert_eq!(packages[1].groups, ["gnome", "gnome-extra"]);
+    assert_eq!(packages[2].groups, ["firefox-i18n"]);
+    assert_eq!(
+        packages[0].description,
+        "Eigenständiger Webbrowser von mozilla.org"
+    );
+}
+
+#[test]
+fn translated_aur_annotations() {
+    let packages = parse_search_output(&fixture("yay-de.txt"), Source::Aur);
+    assert_eq!(packages.len(), 2);
+
+    let esr = &packages[0];
+    assert_eq!(esr.votes, Some(40));
+    assert_eq!(esr.out_of_date, Some(1727740800));
+    // Translated words cannot be told apart, but are not groups either
+    assert!(esr.groups.is_empty());
+
+    let nightly = &packages[1];
+    assert_eq!(nightly.popularity, Some(0.08));
+    assert_eq!(nightly.out_of_date, None);
+}
+
+#[test]
+fn single_line_results() {
+    let single_line = fixture("yay-singleline-de.txt");
+    let packages = parse_single_line_output(&single_line, Source::Aur);
+    let two_lines = parse_search_output(&fixture("yay-de.txt"), Source::Aur);
+    assert_eq!(
+        serde_json::to_value(&packages).unwrap(),
+        serde_json::to_value(&two_lines).unwrap()
+    );
+
+    // Two-line output is left to `parse_search_output`
+    assert!(parse_single_line_output(&fixture("yay-de.txt"), Source::Aur).is_empty());
+    let all = parse_single_line_output(&single_line, Source::Pacman);
+    assert_eq!(all[2].name, "firefox");
+    assert_eq!(all[2].description, "Eigenständiger Webbrowser");
+}
+
+#[test]
+fn colored_output() {
+    let packages = parse_search_output(&fixture("paru-color.txt"), Source::Pacman);
+    let summary: Vec<_> = packages
+        .iter()
+        .map(|p| (p.repo.as_deref(), p.name.as_str(), p.version.as_str()))
+        .collect();
+    assert_eq!(
+        summary,
+        [
+            (Some("aur"), "firefox-esr-bin", "128.3.1-1"),
+            (Some("extra"), "firefox", "131.0-1"),
+        ]
+    );
+    assert_eq!(packages[0].votes, Some(40));
+    assert_eq!(packages[0].out_of_date, Some(1727740800));
+    assert_eq!(packages[0].installed_version.as_deref(), Some("128.3.1-1"));
+
+    assert_eq!(
+        strip_ansi("\x1b]8;;https://aur.archlinux.org\x07aur\x1b]8;;\x07 \x1b[1mok\x1b[0m"),
+        "aur ok"
+    );
+}
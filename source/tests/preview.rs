@@ -0,0 +1,53 @@
+//! Details shown in the interactive picker's preview pane.
+
+use std::thread;
+use std::time::{Duration, Instant};
+
+use package_dictionary::aur_rpc::RpcClient;
+use package_dictionary::backend::parse_flatpak_output;
+use package_dictionary::tui::Previewer;
+use package_dictionary::PackageInfo;
+use tokio::runtime::Runtime;
+
+fn preview(package: &PackageInfo) -> Result<String, String> {
+    let rt = Runtime::new().unwrap();
+    let previewer = Previewer::new(
+        rt.handle().clone(),
+        RpcClient::with_base_url("http://127.0.0.1:9"),
+    )
+    .offline();
+    previewer.request(0, package);
+
+    let start = Instant::now();
+    while start.elapsed() < Duration::from_secs(10) {
+        if let Some((_, details)) = previewer.poll().pop() {
+            return details;
+        }
+        thread::sleep(Duration::from_millis(10));
+    }
+    panic!("no preview for {}", package.name);
+}
+
+#[test]
+fn search_results_are_looked_up_with_remote_info() {
+    let packages =
+        parse_flatpak_output(b"GIMP\torg.gimp.GIMP\t2.10.38\tstable\tflathub\tCreate images\n");
+    assert_eq!(packages[0].branch.as_deref(), Some("stable"));
+
+    // Without flatpak installed this fails, but it must never be the
+    // appstream card, which would be missing most details
+    let details = preview(&packages[0]);
+    assert!(
+        !matches!(&details, Ok(card) if card.starts_with("Application ID")),
+        "{:?}",
+        details
+    );
+
+    let from_appstream = PackageInfo {
+        appstream: true,
+        ..packages[0].clone()
+    };
+    let card = preview(&from_appstream).unwrap();
+    assert!(card.starts_with("Application ID  : org.gimp.GIMP\n"));
+    assert!(card.contains("Branch          : stable\n"));
+}
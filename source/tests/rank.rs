@@ -0,0 +1,148 @@
+use package_dictionary::rank::{
+    matches_field, relevance, retain_matches, score_results, sort_packages, MatchField, SortOrder,
+};
+use package_dictionary::search::SourceResults;
+use package_dictionary::{PackageInfo, Source};
+
+fn gimp() -> PackageInfo {
+    PackageInfo {
+        app_id: Some("org.gimp.GIMP".to_string()),
+        ..PackageInfo::new(
+            Source::Flatpak,
+            "GIMP (org.gimp.GIMP)",
+            "2.10.38",
+            "Create images and edit photographs",
+        )
+    }
+}
+
+#[test]
+fn parses_match_fields() {
+    for field in ["name", "description", "id", "all"] {
+        assert_eq!(field.parse::<MatchField>().unwrap().to_string(), field);
+    }
+    assert!("summary".parse::<MatchField>().is_err());
+}
+
+#[test]
+fn matches_the_selected_field() {
+    let gimp = gimp();
+    assert!(matches_field(&gimp, "gimp", MatchField::Name));
+    assert!(!matches_field(&gimp, "org.gimp", MatchField::Name));
+    assert!(matches_field(&gimp, "org.gimp", MatchField::Id));
+    assert!(matches_field(&gimp, "edit images", MatchField::Description));
+    assert!(!matches_field(
+        &gimp,
+        "image editor",
+        MatchField::Description
+    ));
+    assert!(matches_field(&gimp, "image editor", MatchField::All));
+
+    // Without an application ID, the ID is the package name
+    let vim = PackageInfo::new(Source::Pacman, "vim", "9.1", "Vi Improved");
+    assert!(matches_field(&vim, "VIM", MatchField::Id));
+    assert!(!matches_field(&vim, "improved", MatchField::Id));
+}
+
+#[test]
+fn keeps_only_matching_packages() {
+    let mut results = vec![SourceResults {
+        name: "Flatpak".to_string(),
+        source: Source::Flatpak,
+        packages: vec![
+            gimp(),
+            PackageInfo::new(Source::Flatpak, "Krita", "5.2", "Digital painting"),
+        ],
+        error: None,
+    }];
+    retain_matches(&mut results, "photographs", MatchField::Description);
+    assert_eq!(results[0].packages.len(), 1);
+    assert_eq!(results[0].packages[0].name, "GIMP (org.gimp.GIMP)");
+}
+
+#[test]
+fn scores_names_and_ids_above_descriptions() {
+    let gimp = gimp();
+    let by_name = relevance(&gimp, "gimp");
+    let by_id = relevance(&gimp, "org.gimp");
+    let by_description = relevance(&gimp, "photographs");
+    assert!(by_name > by_id, "{} <= {}", by_name, by_id);
+    assert!(by_id > by_description, "{} <= {}", by_id, by_description);
+}
+
+#[test]
+fn ranks_exact_then_prefix_then_substring_then_description() {
+    let package =
+        |name: &str, description: &str| PackageInfo::new(Source::Aur, name, "1.0", description);
+    let exact = relevance(&package("firefox", ""), "firefox");
+    let prefix = relevance(&package("firefox-developer-edition", ""), "firefox");
+    let substring = relevance(&package("librewolf-firefox-shim", ""), "firefox");
+    let description = relevance(&package("librewolf", "A fork of Firefox"), "firefox");
+    assert!(exact > prefix, "{} <= {}", exact, prefix);
+    assert!(prefix > substring, "{} <= {}", prefix, substring);
+    assert!(substring > description, "{} <= {}", substring, description);
+
+    // `pd foo bar` matches `foo-bar` exactly
+    let joined = relevance(
+        &package("visual-studio-code-bin", ""),
+        "visual studio code bin",
+    );
+    assert_eq!(joined, exact);
+}
+
+#[test]
+fn source_bonus_never_outweighs_a_better_match() {
+    let firefox = PackageInfo {
+        repo: Some("extra".to_string()),
+        ..PackageInfo::new(Source::Pacman, "firefox", "131.0-1", "Web browser")
+    };
+    // Popular, installed addons still rank below the browser itself
+    let addon = PackageInfo {
+        popularity: Some(50.0),
+        installed_version: Some("1.0-1".to_string()),
+        ..PackageInfo::new(Source::Aur, "firefox-ublock-origin", "1.0-1", "Addon")
+    };
+    assert!(relevance(&firefox, "firefox") > relevance(&addon, "firefox"));
+
+    // Between equal matches the source decides
+    let pacman = relevance(&firefox, "firefox");
+    let flatpak = relevance(
+        &PackageInfo::new(Source::Flatpak, "firefox", "131.0", ""),
+        "firefox",
+    );
+    let aur = relevance(
+        &PackageInfo::new(Source::Aur, "firefox", "131.0-1", ""),
+        "firefox",
+    );
+    let installed = relevance(
+        &PackageInfo {
+            installed_version: Some("131.0-1".to_string()),
+            ..firefox.clone()
+        },
+        "firefox",
+    );
+    assert!(installed > pacman);
+    assert!(pacman > flatpak);
+    assert!(flatpak > aur);
+
+    let mut results = vec![SourceResults {
+        name: "AUR".to_string(),
+        source: Source::Aur,
+        packages: vec![addon, firefox],
+        error: None,
+    }];
+    score_results(&mut results, "firefox");
+    sort_packages(&mut results[0].packages, SortOrder::Relevance);
+    assert_eq!(results[0].packages[0].name, "firefox");
+}
+
+#[test]
+fn sorts_names_ignoring_case() {
+    let mut packages: Vec<PackageInfo> = ["Zoom", "atom", "Bitwarden", "zed"]
+        .into_iter()
+        .map(|name| PackageInfo::new(Source::Flatpak, name, "1.0", ""))
+        .collect();
+    sort_packages(&mut packages, SortOrder::Name);
+    let names: Vec<&str> = packages.iter().map(|p| p.name.as_str()).collect();
+    assert_eq!(names, ["atom", "Bitwarden", "zed", "Zoom"]);
+}
@@ -0,0 +1,97 @@
+use package_dictionary::sandbox::{parse_metadata, Permission, Permissions};
+
+const METADATA: &str = "\
+[Application]
+name=org.mozilla.firefox
+runtime=org.freedesktop.Platform/x86_64/23.08
+
+[Context]
+shared=network;ipc;
+sockets=x11;wayland;pulseaudio;pcsc;
+devices=all;
+filesystems=xdg-download;!host;/run/.heim_org.h5l.kcm-socket;
+
+[Session Bus Policy]
+org.freedesktop.FileManager1=talk
+org.mozilla.firefox.*=own
+org.a11y.Bus=see
+
+[System Bus Policy]
+org.freedesktop.NetworkManager=talk
+";
+
+#[test]
+fn parses_metadata() {
+    assert_eq!(
+        parse_metadata(METADATA),
+        Permissions {
+            filesystems: vec![
+                "xdg-download".to_string(),
+                "/run/.heim_org.h5l.kcm-socket".to_string()
+            ],
+            network: true,
+            sockets: vec![
+                "x11".to_string(),
+                "wayland".to_string(),
+                "pulseaudio".to_string(),
+                "pcsc".to_string()
+            ],
+            devices: vec!["all".to_string()],
+            talk_names: vec!["org.freedesktop.FileManager1".to_string()],
+            own_names: vec!["org.mozilla.firefox.*".to_string()],
+            system_talk_names: vec!["org.freedesktop.NetworkManager".to_string()],
+            system_own_names: vec![],
+        }
+    );
+    assert_eq!(parse_metadata(""), Permissions::default());
+}
+
+#[test]
+fn summarizes_permissions() {
+    let summary = parse_metadata(METADATA).summary();
+    assert_eq!(
+        summary[0],
+        (
+            "Filesystem",
+            "xdg-download  /run/.heim_org.h5l.kcm-socket".to_string()
+        )
+    );
+    assert!(summary.contains(&("Display", "X11 and Wayland".to_string())));
+    assert!(summary.contains(&("Sockets", "pulseaudio  pcsc".to_string())));
+    assert!(summary.contains(&(
+        "D-Bus Talk",
+        "org.freedesktop.FileManager1  org.freedesktop.NetworkManager (system)".to_string()
+    )));
+    assert!(summary.contains(&("D-Bus Own", "org.mozilla.firefox.*".to_string())));
+
+    let host = parse_metadata("[Context]\nfilesystems=host:ro;\nsockets=wayland;fallback-x11;\n");
+    assert!(host.has_host_filesystem());
+    assert_eq!(host.display(), "Wayland, X11 fallback");
+    assert_eq!(host.summary()[0].0, "Warning");
+    assert!(host.summary().contains(&("Network", "no".to_string())));
+
+    // Home access is not the whole host
+    let home = parse_metadata("[Context]\nfilesystems=home;host-os:ro;\n");
+    assert!(!home.has_host_filesystem());
+    assert_eq!(home.display(), "none");
+}
+
+#[test]
+fn looks_up_each_kind_of_access() {
+    let permissions = parse_metadata(METADATA);
+    assert_eq!(permissions.get(Permission::Warning), None);
+    assert_eq!(
+        permissions.get(Permission::Display).as_deref(),
+        Some("X11 and Wayland")
+    );
+    assert_eq!(
+        permissions.get(Permission::DBusOwn).as_deref(),
+        Some("org.mozilla.firefox.*")
+    );
+
+    // The summary lists every kind that applies, under its label
+    let host = parse_metadata("[Context]\nfilesystems=host;\n");
+    let labels: Vec<&str> = host.summary().into_iter().map(|(label, _)| label).collect();
+    let expected: Vec<&str> = Permission::ALL.iter().map(|kind| kind.label()).collect();
+    assert_eq!(labels, expected);
+}
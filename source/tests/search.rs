@@ -0,0 +1,151 @@
+use std::sync::Arc;
+use std::time::Duration;
+
+use futures::future::BoxFuture;
+use package_dictionary::backend::BackendError;
+use package_dictionary::search::{search_packages_with_timeout, search_stream};
+use package_dictionary::{Backend, PackageInfo, Source};
+
+/// A backend that answers with one package after `delay`.
+struct Delayed {
+    source: Source,
+    delay: Duration,
+}
+
+/// A backend whose tool is not installed.
+struct Missing;
+
+impl Backend for Missing {
+    fn name(&self) -> &str {
+        "Missing"
+    }
+
+    fn source(&self) -> Source {
+        Source::Aur
+    }
+
+    fn is_available(&self) -> BoxFuture<'_, bool> {
+        Box::pin(async { false })
+    }
+
+    fn search<'a>(
+        &'a self,
+        _term: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<PackageInfo>, BackendError>> {
+        unreachable!("unavailable backends are never searched")
+    }
+}
+
+impl Backend for Delayed {
+    fn name(&self) -> &str {
+        self.source.label()
+    }
+
+    fn source(&self) -> Source {
+        self.source
+    }
+
+    fn is_available(&self) -> BoxFuture<'_, bool> {
+        Box::pin(async { true })
+    }
+
+    fn search<'a>(
+        &'a self,
+        term: &'a str,
+    ) -> BoxFuture<'a, Result<Vec<PackageInfo>, BackendError>> {
+        Box::pin(async move {
+            tokio::time::sleep(self.delay).await;
+            Ok(vec![PackageInfo::new(self.source, term, "1.0", "")])
+        })
+    }
+}
+
+fn backends() -> Vec<Arc<dyn Backend>> {
+    vec![
+        Arc::new(Delayed {
+            source: Source::Pacman,
+            delay: Duration::from_millis(200),
+        }),
+        Arc::new(Delayed {
+            source: Source::Aur,
+            delay: Duration::from_millis(10),
+        }),
+        Arc::new(Delayed {
+            source: Source::Flatpak,
+            delay: Duration::from_secs(10),
+        }),
+    ]
+}
+
+#[tokio::test]
+async fn streams_in_completion_order() {
+    let mut stream = search_stream(backends(), "vim", |source| match source {
+        Source::Flatpak => Duration::from_millis(300),
+        _ => Duration::from_secs(5),
+    });
+
+    let mut order = Vec::new();
+    let mut errors = Vec::new();
+    while let Some((index, results)) = stream.recv().await {
+        order.push((index, results.source, results.packages.len()));
+        errors.push(results.error);
+    }
+    assert_eq!(
+        order,
+        [
+            (1, Source::Aur, 1),
+            (0, Source::Pacman, 1),
+            (2, Source::Flatpak, 0),
+        ]
+    );
+    assert_eq!(
+        errors,
+        [
+            None,
+            None,
+            Some(BackendError::Timeout(Duration::from_millis(300)))
+        ]
+    );
+}
+
+#[tokio::test]
+async fn collects_in_backend_order() {
+    let results =
+        search_packages_with_timeout(backends(), "vim", |_| Duration::from_millis(300)).await;
+    let sources: Vec<Source> = results.iter().map(|r| r.source).collect();
+    assert_eq!(sources, [Source::Pacman, Source::Aur, Source::Flatpak]);
+    assert_eq!(results[0].packages[0].name, "vim");
+}
+
+#[tokio::test]
+async fn reports_unavailable_backends() {
+    let results =
+        search_packages_with_timeout(vec![Arc::new(Missing)], "vim", |_| Duration::from_secs(1))
+            .await;
+    assert!(results[0].packages.is_empty());
+    assert_eq!(results[0].error, Some(BackendError::NotInstalled));
+}
+
+#[test]
+fn describes_failures() {
+    let exit = BackendError::Exit {
+        program: "yay".to_string(),
+        code: Some(1),
+        stderr: "\n -> error: failed to get search results\nmore detail\n".to_string(),
+    };
+    assert_eq!(
+        exit.to_string(),
+        "yay exited 1: -> error: failed to get search results"
+    );
+
+    let killed = BackendError::Exit {
+        program: "flatpak".to_string(),
+        code: None,
+        stderr: String::new(),
+    };
+    assert_eq!(killed.to_string(), "flatpak was killed");
+    assert_eq!(
+        BackendError::Timeout(Duration::from_secs(5)).to_string(),
+        "timed out after 5s"
+    );
+}
@@ -0,0 +1,133 @@
+//! Read sync databases packed from the text fixtures in `tests/fixtures/sync`.
+
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+
+use package_dictionary::backend::PacmanBackend;
+use package_dictionary::syncdb::{read_db, read_sync_dir};
+use package_dictionary::{Backend, Source};
+
+const FIXTURES: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/sync");
+
+enum Compression {
+    Gzip,
+    Zstd,
+}
+
+/// Pack `tests/fixtures/sync/<repo>` into `<dir>/<repo>.db`.
+fn pack_repo(dir: &Path, repo: &str, compression: Compression) {
+    let mut tar = tar::Builder::new(Vec::new());
+    tar.append_dir_all(".", Path::new(FIXTURES).join(repo))
+        .unwrap();
+    let tar = tar.into_inner().unwrap();
+
+    let file = File::create(dir.join(format!("{}.db", repo))).unwrap();
+    match compression {
+        Compression::Gzip => {
+            let mut encoder = flate2::write::GzEncoder::new(file, flate2::Compression::default());
+            encoder.write_all(&tar).unwrap();
+            encoder.finish().unwrap();
+        }
+        Compression::Zstd => {
+            let mut encoder = zstd::stream::write::Encoder::new(file, 0).unwrap();
+            encoder.write_all(&tar).unwrap();
+            encoder.finish().unwrap();
+        }
+    }
+}
+
+fn fixture_sync_dir() -> tempfile::TempDir {
+    let dir = tempfile::tempdir().unwrap();
+    pack_repo(dir.path(), "core", Compression::Gzip);
+    pack_repo(dir.path(), "extra", Compression::Zstd);
+    dir
+}
+
+#[test]
+fn reads_full_records_from_gzip_database() {
+    let dir = fixture_sync_dir();
+    let packages = read_db(&dir.path().join("core.db"), "core").unwrap();
+
+    assert_eq!(packages.len(), 1);
+    let bash = &packages[0];
+    assert_eq!(bash.source, Source::Pacman);
+    assert_eq!(bash.name, "bash");
+    assert_eq!(bash.version, "5.2.037-1");
+    assert_eq!(bash.description, "The GNU Bourne Again shell");
+    assert_eq!(bash.repo.as_deref(), Some("core"));
+    assert_eq!(bash.provides, ["sh"]);
+    assert_eq!(
+        bash.depends,
+        ["readline", "libreadline.so=8-64", "glibc", "ncurses"]
+    );
+    assert_eq!(bash.licenses, ["GPL-3.0-or-later"]);
+    assert_eq!(
+        bash.url.as_deref(),
+        Some("https://www.gnu.org/software/bash/bash.html")
+    );
+    assert_eq!(
+        bash.packager.as_deref(),
+        Some("Tobias Powalowski <tpowa@archlinux.org>")
+    );
+    assert_eq!(bash.build_date, Some(1734297962));
+    assert_eq!(bash.compressed_size, Some(1873216));
+    assert_eq!(bash.installed_size, Some(9289524));
+}
+
+#[test]
+fn reads_every_repository_in_a_sync_dir() {
+    let dir = fixture_sync_dir();
+    let packages = read_sync_dir(dir.path()).unwrap();
+
+    let mut names: Vec<_> = packages
+        .iter()
+        .map(|p| (p.repo.as_deref().unwrap(), p.name.as_str()))
+        .collect();
+    names.sort();
+    assert_eq!(
+        names,
+        [("core", "bash"), ("extra", "firefox"), ("extra", "gedit")]
+    );
+
+    let gedit = packages.iter().find(|p| p.name == "gedit").unwrap();
+    assert_eq!(gedit.groups, ["gnome"]);
+    assert_eq!(gedit.version, "48.0-1");
+}
+
+#[tokio::test]
+async fn backend_searches_names_and_descriptions() {
+    let dir = fixture_sync_dir();
+    let backend = PacmanBackend::new(dir.path());
+    assert!(backend.is_available().await);
+
+    let by_name = backend.search("FireFox").await.unwrap();
+    assert_eq!(by_name.len(), 1);
+    assert_eq!(by_name[0].name, "firefox");
+
+    let by_description = backend.search("text editor").await.unwrap();
+    assert_eq!(by_description.len(), 1);
+    assert_eq!(by_description[0].name, "gedit");
+
+    assert!(backend.search("no-such-package").await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn backend_info_keeps_exact_names_with_every_dependency() {
+    let dir = fixture_sync_dir();
+    let backend = PacmanBackend::new(dir.path());
+
+    let info = backend.info("firefox").await.unwrap();
+    assert_eq!(info.len(), 1);
+    assert_eq!(
+        info[0].opt_depends,
+        [
+            "hunspell-en_US: Spell checking, American English",
+            "libnotify: Notification integration"
+        ]
+    );
+    assert_eq!(info[0].make_depends, ["cbindgen", "nodejs", "rust"]);
+
+    // A search for "bas" finds bash, but bash is not called "bas"
+    assert!(backend.info("bas").await.unwrap().is_empty());
+}
@@ -0,0 +1,37 @@
+//! Cases from pacman's `vercmptest.sh`.
+
+use std::cmp::Ordering::{self, Equal, Greater, Less};
+
+use package_dictionary::vercmp::vercmp;
+
+fn check(a: &str, b: &str, expected: Ordering) {
+    assert_eq!(vercmp(a, b), expected, "vercmp {} {}", a, b);
+    assert_eq!(vercmp(b, a), expected.reverse(), "vercmp {} {}", b, a);
+}
+
+#[test]
+fn pkgver_segments() {
+    check("1.5.0", "1.5.0", Equal);
+    check("1.5.1", "1.5.0", Greater);
+    check("1.5.1", "1.5", Greater);
+    check("2.0", "10.0", Less);
+    check("1.01", "1.1", Equal);
+    check("1.5.a", "1.5", Greater);
+    check("1.5", "1.5a", Greater);
+    check("1.0alpha", "1.0", Less);
+    check("1.0a", "1.0alpha", Less);
+    check("1.0.a", "1.0.1", Less);
+    check("1..0", "1.0", Greater);
+    check("1_0", "1.0", Equal);
+}
+
+#[test]
+fn epoch_and_pkgrel() {
+    check("1.5-1", "1.5-2", Less);
+    check("1.5-2", "1.5.1-1", Less);
+    check("1.5-1", "1.5", Equal);
+    check("1:1.0-1", "2.0-1", Greater);
+    check("0:1.0", "1.0", Equal);
+    check("1:1.0", "2:1.0", Less);
+    check("1.5-1.1", "1.5-1", Greater);
+}